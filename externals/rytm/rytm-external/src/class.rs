@@ -12,6 +12,7 @@ use median::{
     wrapper::{MaxObjWrapped, MaxObjWrapper, ObjWrapped},
 };
 use parking_lot::Mutex;
+use rytm_object::sysex::SysexOutbox;
 use rytm_rs::RytmProject;
 use std::{
     ffi::CString,
@@ -95,17 +96,29 @@ impl MaxObjWrapped<Self> for RytmExternal {
                 target_device_id: AtomicIsize::new(0),
                 root_span,
                 subscriber: registry,
-                sysex_out: builder.add_int_outlet_with_assist("sysex output (connect to midiout)"),
-                query_out: builder.add_anything_outlet_with_assist("get query results (list)"),
+                sysex_out: builder.add_int_outlet_with_assist(
+                    "sysex output (connect to midiout), one int per byte when sysex_mode is 0",
+                ),
+                sysex_outbox: Mutex::new(SysexOutbox::default()),
+                chunk_delay: AtomicIsize::new(0),
+                query_out: builder.add_anything_outlet_with_assist(
+                    "get query results, device responses, timeouts and ready (list)",
+                ),
                 status_out: builder.add_int_outlet_with_assist(
                     "command status: 0 for success, 1 and 2 for error and warning (int)",
                 ),
-                inner: rytm_object::RytmObject {
-                    project: Arc::new(Mutex::new(project)),
-                    sysex_in_buffer: Arc::new(Mutex::new(Vec::new())),
-                    buffering_sysex: AtomicBool::new(false),
-                },
+                progress_out: builder.add_anything_outlet_with_assist(
+                    "progress of load, save and bulk transfers: busy <0 | 1>, <operation> <phase> <percent>, <operation> <done | failed | cancelled> (list)",
+                ),
+                sysex_list_out: builder.add_anything_outlet_with_assist(
+                    "sysex output as lists when sysex_mode is 1 (connect to midiout)",
+                ),
+                request_clock: builder.with_clockfn(Self::flush_requests),
+                sysex_clock: builder.with_clockfn(Self::flush_sysex_output),
+                inner: rytm_object::RytmObject::new(project),
                 logging_state,
+                alive: Arc::new(AtomicBool::new(true)),
+                background: Mutex::new(None),
             };
 
             info!("Rytm is instantiated ({:p}).", &instance.max_obj());
@@ -133,34 +146,142 @@ impl MaxObjWrapped<Self> for RytmExternal {
             )
             .expect("Failed to add sysex_id attribute");
 
-        // Adding the save flag to the attribute
-        // Currently this is not possible with median so it is saved with the patcher.
-
-        // c74_max_object.h is the source for this info.
-        // Mimicing this CLASS_ATTR_ATTR_PARSE(c,attrname,"save", c74::max::gensym("long"),flags,"1")
-
-        let attrname = CString::new("sysex_id").unwrap();
-        let attrname2 = CString::new("save").unwrap();
-        let parsestring = CString::new("1").unwrap();
-        let type_ = SymbolRef::try_from("long").unwrap();
-
-        unsafe {
-            max_sys::class_attr_addattr_parse(
-                class.inner(),
-                attrname.as_ptr(),
-                attrname2.as_ptr(),
-                type_.inner(),
-                (max_sys::e_max_attrflags::ATTR_GET_DEFER_LOW
-                    | max_sys::e_max_attrflags::ATTR_SET_DEFER_LOW)
-                    .into(),
-                parsestring.as_ptr(),
-            );
+        class
+            .add_attribute(
+                AttrBuilder::new_accessors(
+                    "interval",
+                    AttrType::Int64,
+                    Self::attr_get_interval_tramp,
+                    Self::attr_set_interval_tramp,
+                )
+                .clip(AttrClip::Set(AttrValClip::MinMax(0.0, 10000.0)))
+                .build()
+                .expect("Failed to build interval attribute"),
+            )
+            .expect("Failed to add interval attribute");
+
+        class
+            .add_attribute(
+                AttrBuilder::new_accessors(
+                    "timeout",
+                    AttrType::Int64,
+                    Self::attr_get_timeout_tramp,
+                    Self::attr_set_timeout_tramp,
+                )
+                .clip(AttrClip::Set(AttrValClip::MinMax(0.0, 60000.0)))
+                .build()
+                .expect("Failed to build timeout attribute"),
+            )
+            .expect("Failed to add timeout attribute");
+
+        class
+            .add_attribute(
+                AttrBuilder::new_accessors(
+                    "retries",
+                    AttrType::Int64,
+                    Self::attr_get_retries_tramp,
+                    Self::attr_set_retries_tramp,
+                )
+                .clip(AttrClip::Set(AttrValClip::MinMax(0.0, 10.0)))
+                .build()
+                .expect("Failed to build retries attribute"),
+            )
+            .expect("Failed to add retries attribute");
+
+        class
+            .add_attribute(
+                AttrBuilder::new_accessors(
+                    "sysex_mode",
+                    AttrType::Int64,
+                    Self::attr_get_sysex_mode_tramp,
+                    Self::attr_set_sysex_mode_tramp,
+                )
+                .clip(AttrClip::Set(AttrValClip::MinMax(0.0, 1.0)))
+                .build()
+                .expect("Failed to build sysex_mode attribute"),
+            )
+            .expect("Failed to add sysex_mode attribute");
+
+        class
+            .add_attribute(
+                AttrBuilder::new_accessors(
+                    "chunk_size",
+                    AttrType::Int64,
+                    Self::attr_get_chunk_size_tramp,
+                    Self::attr_set_chunk_size_tramp,
+                )
+                .clip(AttrClip::Set(AttrValClip::MinMax(0.0, 65536.0)))
+                .build()
+                .expect("Failed to build chunk_size attribute"),
+            )
+            .expect("Failed to add chunk_size attribute");
 
-            // Forget these, since they're heap allocations and we don't know how long they should live. It is in Max's hands now.
-            std::mem::forget(attrname);
-            std::mem::forget(attrname2);
-            std::mem::forget(parsestring);
-        }
+        class
+            .add_attribute(
+                AttrBuilder::new_accessors(
+                    "chunk_delay",
+                    AttrType::Int64,
+                    Self::attr_get_chunk_delay_tramp,
+                    Self::attr_set_chunk_delay_tramp,
+                )
+                .clip(AttrClip::Set(AttrValClip::MinMax(0.0, 1000.0)))
+                .build()
+                .expect("Failed to build chunk_delay attribute"),
+            )
+            .expect("Failed to add chunk_delay attribute");
+
+        class
+            .add_attribute(
+                AttrBuilder::new_accessors(
+                    "autosend",
+                    AttrType::Int64,
+                    Self::attr_get_autosend_tramp,
+                    Self::attr_set_autosend_tramp,
+                )
+                .clip(AttrClip::Set(AttrValClip::MinMax(0.0, 1.0)))
+                .build()
+                .expect("Failed to build autosend attribute"),
+            )
+            .expect("Failed to add autosend attribute");
+
+        class
+            .add_attribute(
+                AttrBuilder::new_accessors(
+                    "debounce",
+                    AttrType::Int64,
+                    Self::attr_get_debounce_tramp,
+                    Self::attr_set_debounce_tramp,
+                )
+                .clip(AttrClip::Set(AttrValClip::MinMax(0.0, 10000.0)))
+                .build()
+                .expect("Failed to build debounce attribute"),
+            )
+            .expect("Failed to add debounce attribute");
+
+        class
+            .add_attribute(
+                AttrBuilder::new_accessors(
+                    "indexbase",
+                    AttrType::Int64,
+                    Self::attr_get_indexbase_tramp,
+                    Self::attr_set_indexbase_tramp,
+                )
+                .clip(AttrClip::Set(AttrValClip::MinMax(0.0, 1.0)))
+                .build()
+                .expect("Failed to build indexbase attribute"),
+            )
+            .expect("Failed to add indexbase attribute");
+
+        add_save_flag(class, "sysex_id");
+        add_save_flag(class, "interval");
+        add_save_flag(class, "timeout");
+        add_save_flag(class, "retries");
+        add_save_flag(class, "sysex_mode");
+        add_save_flag(class, "chunk_size");
+        add_save_flag(class, "chunk_delay");
+        add_save_flag(class, "autosend");
+        add_save_flag(class, "debounce");
+        add_save_flag(class, "indexbase");
 
         // Methods
 
@@ -173,6 +294,36 @@ impl MaxObjWrapped<Self> for RytmExternal {
     }
 }
 
+// Adding the save flag to the attribute
+// Currently this is not possible with median so it is saved with the patcher.
+fn add_save_flag(class: &mut Class<MaxObjWrapper<RytmExternal>>, attribute_name: &str) {
+    // c74_max_object.h is the source for this info.
+    // Mimicing this CLASS_ATTR_ATTR_PARSE(c,attrname,"save", c74::max::gensym("long"),flags,"1")
+
+    let attrname = CString::new(attribute_name).unwrap();
+    let attrname2 = CString::new("save").unwrap();
+    let parsestring = CString::new("1").unwrap();
+    let type_ = SymbolRef::try_from("long").unwrap();
+
+    unsafe {
+        max_sys::class_attr_addattr_parse(
+            class.inner(),
+            attrname.as_ptr(),
+            attrname2.as_ptr(),
+            type_.inner(),
+            (max_sys::e_max_attrflags::ATTR_GET_DEFER_LOW
+                | max_sys::e_max_attrflags::ATTR_SET_DEFER_LOW)
+                .into(),
+            parsestring.as_ptr(),
+        );
+
+        // Forget these, since they're heap allocations and we don't know how long they should live. It is in Max's hands now.
+        std::mem::forget(attrname);
+        std::mem::forget(attrname2);
+        std::mem::forget(parsestring);
+    }
+}
+
 // impl FilePath {
 //     /// Get the full pathname using basic Max path formatting
 //     pub fn to_full_path(&self) -> Option<CString> {
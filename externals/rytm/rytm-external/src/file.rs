@@ -89,6 +89,24 @@ impl FilePathExt for FilePath {
     }
 }
 
+/// Get the absolute system path of a file in a folder of the Max preferences folder, the folder is made if it does not exist.
+pub fn preferences_file_path(folder: &str, file_name: &str) -> Option<CString> {
+    let folder = CString::new(folder).ok()?;
+    let mut vol: std::os::raw::c_short = 0;
+    unsafe {
+        if max_sys::preferences_path(folder.as_ptr(), 1, &mut vol) != 0 {
+            return None;
+        }
+    }
+
+    FilePath {
+        file_name: CString::new(file_name).ok()?,
+        vol,
+        typ: 0,
+    }
+    .to_absolute_system_path()
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum RytmProjectFileType {
     Sysex,
@@ -29,23 +29,49 @@ pub mod utils;
 
 use crate::{error::RytmExternalError, traits::Post};
 use error_logger_macro::log_errors;
+use lazy_static::lazy_static;
+use load_save::BackgroundWork;
 use median::{
     atom::Atom,
+    clock::ClockHandle,
     max_sys::t_atom_long,
     object::MaxObj,
     outlet::{OutAnything, OutInt, SendError},
     symbol::SymbolRef,
     wrapper::MaxObjWrapper,
 };
-use rytm_object::{api::Response, types::CommandType, value::RytmValue, RytmObject};
-use rytm_rs::RytmProject;
-use std::sync::{
-    atomic::{AtomicIsize, Ordering},
-    Arc,
+use parking_lot::Mutex;
+use rytm_object::{
+    api::device_prefix,
+    api::{batch, bulk_object_type, Response},
+    error::RytmObjectError,
+    macros::{self, Macros},
+    parse::types::DeviceObject,
+    progress::{Operation, ProgressEvent},
+    recording::{self, Direction},
+    scheduler::Progress,
+    schema::ParameterSchema,
+    snapshot::Mismatch,
+    sysex::{self, DeviceIdentity, OutputMode, Status, SysexEvent, SysexOutbox},
+    types::CommandType,
+    value::RytmValue,
+};
+use std::{
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, AtomicIsize, Ordering},
+        Arc,
+    },
 };
 use tracing::{error, info, info_span, instrument, span::EnteredSpan, warn};
 use tracing_setup::{get_default_env_filter, LoggingState};
 use traits::SerialSend;
+use trampoline::DeferTarget;
+
+lazy_static! {
+    /// The macros shared by every `rytm` object with the file they are kept in, read once on first use.
+    static ref MACROS: Mutex<Option<(PathBuf, Macros)>> = Mutex::new(None);
+}
 
 // This is the entry point for the Max external
 #[no_mangle]
@@ -65,10 +91,38 @@ pub struct RytmExternal {
     pub root_span: EnteredSpan,
     pub subscriber: Arc<dyn tracing::Subscriber + Send + Sync + 'static>,
     pub sysex_out: OutInt,
+    /// Outgoing sysex split to the pieces which are sent one at a time.
+    pub sysex_outbox: Mutex<SysexOutbox>,
+    /// Milliseconds between the pieces of outgoing sysex, 0 sends them all at once.
+    pub chunk_delay: AtomicIsize,
     pub query_out: OutAnything,
     pub status_out: OutInt,
+    /// Reports the busy state and the progress of loads, saves and bulk transfers.
+    pub progress_out: OutAnything,
+    /// Outgoing sysex as lists, used instead of the first outlet when `sysex_mode` is 1.
+    pub sysex_list_out: OutAnything,
+    /// Fires when the next scheduled query or send is due.
+    pub request_clock: ClockHandle,
+    /// Fires when the next piece of outgoing sysex is due.
+    pub sysex_clock: ClockHandle,
     pub inner: rytm_object::RytmObject,
     pub logging_state: Arc<LoggingState>,
+    /// Cleared when the object is freed, calls deferred from other threads check it before they touch the object.
+    pub alive: Arc<AtomicBool>,
+    /// The thread of the last load or save.
+    pub background: Mutex<Option<BackgroundWork>>,
+}
+
+impl Drop for RytmExternal {
+    fn drop(&mut self) {
+        // Calls which are still queued find the object freed and the decoder can not queue more.
+        self.alive.store(false, Ordering::SeqCst);
+        self.inner.stop_decoder();
+        // A load or a save stops at its next step without changing anything.
+        if let Some(work) = self.background.lock().take() {
+            work.stop();
+        }
+    }
 }
 
 impl RytmExternal {
@@ -80,11 +134,23 @@ impl RytmExternal {
 
     const SELECTOR_QUERY: &'static str = "query";
     const SELECTOR_SEND: &'static str = "send";
+    const SELECTOR_SEND_VERIFY: &'static str = "sendverify";
     const SELECTOR_SET: &'static str = "set";
     const SELECTOR_GET: &'static str = "get";
     const SELECTOR_LOG_LEVEL: &'static str = "loglevel";
     const SELECTOR_COPY: &'static str = "copy";
     const SELECTOR_RESET: &'static str = "reset";
+    const SELECTOR_IDENTIFY: &'static str = "identify";
+    const SELECTOR_RECORD: &'static str = "record";
+    const SELECTOR_REPLAY: &'static str = "replay";
+    const SELECTOR_CANCEL: &'static str = "cancel";
+    const SELECTOR_BATCH: &'static str = batch::BATCH;
+    const SELECTOR_DESCRIBE: &'static str = "describe";
+    const SELECTOR_DEFINE: &'static str = macros::DEFINE;
+    const SELECTOR_RUN: &'static str = macros::RUN;
+    const SELECTOR_MACROS: &'static str = macros::MACROS;
+    // Max delivers lists to the anything method when there is no list method.
+    const SELECTOR_LIST: &'static str = "list";
 
     // TODO: Implementations for these are sketches.
     // For proper impl move some of the logic to the RytmObject.
@@ -92,6 +158,32 @@ impl RytmExternal {
     const SELECTOR_LOAD: &'static str = "load";
     const SELECTOR_SAVE: &'static str = "save";
 
+    /// The selectors which can be used in a macro, macros can not define, run or list other macros.
+    ///
+    /// A batch can not be used either since its commands are separated like the ones of a macro.
+    const MACRO_SELECTORS: [&'static str; 15] = [
+        Self::SELECTOR_QUERY,
+        Self::SELECTOR_SEND,
+        Self::SELECTOR_SEND_VERIFY,
+        Self::SELECTOR_SET,
+        Self::SELECTOR_GET,
+        Self::SELECTOR_LOG_LEVEL,
+        Self::SELECTOR_LOAD,
+        Self::SELECTOR_SAVE,
+        Self::SELECTOR_COPY,
+        Self::SELECTOR_RESET,
+        Self::SELECTOR_IDENTIFY,
+        Self::SELECTOR_RECORD,
+        Self::SELECTOR_REPLAY,
+        Self::SELECTOR_CANCEL,
+        Self::SELECTOR_DESCRIBE,
+    ];
+
+    /// The folder in the Max preferences folder where the macros are kept.
+    const MACROS_FOLDER: &'static str = "rytm";
+    /// The file in [`Self::MACROS_FOLDER`] which keeps the macros.
+    const MACROS_FILE_NAME: &'static str = "macros.txt";
+
     pub fn int(&self, value: t_atom_long) -> Result<(), RytmExternalError> {
         tracing::subscriber::with_default(Arc::clone(&self.subscriber), || {
             self.root_span.in_scope(|| {
@@ -106,8 +198,82 @@ impl RytmExternal {
                             error!("{}", err);
                         }
                     )?;
+                if byte == sysex::SYSEX_START {
+                    self.start_decoder();
+                }
                 // This one already logs errors in the object.
-                Ok(self.inner.handle_sysex_byte(byte)?)
+                self.inner
+                    .handle_sysex_byte(byte)?
+                    .map_or(Ok(()), |event| self.report_sysex_event(event))
+            })
+        })
+    }
+
+    /// Accepts a complete sysex message or several concatenated messages as a list of bytes.
+    pub fn list(&self, atoms: &[Atom]) -> Result<(), RytmExternalError> {
+        tracing::subscriber::with_default(Arc::clone(&self.subscriber), || {
+            self.root_span.in_scope(|| {
+                let _function_span = info_span!("list", "length" = atoms.len()).entered();
+
+                let bytes = self
+                    .get_rytm_values(atoms)?
+                    .iter()
+                    .map(|value| match value {
+                        RytmValue::Int(byte) => u8::try_from(*byte).ok(),
+                        _ => None,
+                    })
+                    .collect::<Option<Vec<u8>>>()
+                    .ok_or_else(|| {
+                        RytmExternalError::from(
+                            "Rytm Error: Invalid input. A list sent to rytm should only contain sysex bytes which are integers in the range 0..=255.",
+                        )
+                    })
+                    .inspect_err(|err| {
+                        self.send_status_error();
+                        error!("{}", err);
+                    })?;
+
+                self.start_decoder();
+                // This one already logs errors in the object.
+                let events = self.inner.handle_sysex_bytes(&bytes).inspect_err(|_| {
+                    self.send_status_error();
+                })?;
+
+                // Report every message even if an earlier one is rejected.
+                events
+                    .into_iter()
+                    .map(|event| self.report_sysex_event(event))
+                    .fold(Ok(()), Result::and)
+            })
+        })
+    }
+
+    /// Starts decoding incoming dumps on a worker thread, their events are reported from the low priority queue.
+    ///
+    /// If the thread can not be started the dumps are decoded where they arrive.
+    fn start_decoder(&self) {
+        if self.inner.decoder.lock().is_some() {
+            return;
+        }
+        let target = DeferTarget::new(self.max_obj(), &self.alive);
+        // This one already logs errors in the object.
+        self.inner
+            .start_decoder(Box::new(move || target.defer_decoded()))
+            .ok();
+    }
+
+    /// Reports the dumps which are decoded on the worker thread since the last call.
+    pub fn report_decoded(&self) -> Result<(), RytmExternalError> {
+        tracing::subscriber::with_default(Arc::clone(&self.subscriber), || {
+            self.root_span.in_scope(|| {
+                let _function_span = info_span!("report_decoded").entered();
+
+                // Report every message even if an earlier one is rejected.
+                self.inner
+                    .take_decoded_events()
+                    .into_iter()
+                    .map(|event| self.report_sysex_event(event))
+                    .fold(Ok(()), Result::and)
             })
         })
     }
@@ -120,44 +286,68 @@ impl RytmExternal {
         tracing::subscriber::with_default(Arc::clone(&self.subscriber), || {
             self.root_span.in_scope(|| {
                 let _function_span = info_span!("anything_with_selector").entered();
-                let selector = sel
-                    .to_string()
-                    .map_err(|err|{
-                         self.send_status_error();
-                         RytmExternalError::Custom(err.to_string())
-                    })?;
+                let selector = sel.to_string().map_err(|err| {
+                    self.send_status_error();
+                    RytmExternalError::Custom(err.to_string())
+                })?;
 
-                let possible_selectors = [
-                    Self::SELECTOR_QUERY,
-                    Self::SELECTOR_SEND,
-                    Self::SELECTOR_SET,
-                    Self::SELECTOR_GET,
-                    Self::SELECTOR_LOG_LEVEL,
-                    Self::SELECTOR_LOAD,
-                    Self::SELECTOR_SAVE,
-                    Self::SELECTOR_COPY,
-                    Self::SELECTOR_RESET
-                ].join(", ");
-                match selector.as_str() {
-                    Self::SELECTOR_QUERY => self.query(atoms),
-                    Self::SELECTOR_SEND => self.send(atoms),
-                    Self::SELECTOR_SET => self.set(atoms),
-                    Self::SELECTOR_GET => self.get(atoms),
-                    Self::SELECTOR_LOG_LEVEL => self.change_log_level(atoms),
-                    Self::SELECTOR_LOAD => self.load(atoms),
-                    Self::SELECTOR_SAVE => self.save(atoms),
-                    Self::SELECTOR_COPY => self.copy(atoms),
-                    Self::SELECTOR_RESET => self.reset(atoms),
-                    _ => Err(format!("Parse Error: Invalid command type {selector}. Possible commands are {possible_selectors}.").into()),
-                }.inspect_err(|_| {
+                // Already reports its own errors.
+                if selector.as_str() == Self::SELECTOR_LIST {
+                    return self.list(atoms);
+                }
+                self.dispatch(&selector, atoms).inspect_err(|err| {
                     if selector.as_str() != Self::SELECTOR_LOG_LEVEL {
                         self.send_status_error();
                     }
+                    if let RytmExternalError::RytmObject(err) = err {
+                        self.suggestions_to_outlet(err).ok();
+                    }
                 })
             })
         })
     }
 
+    /// Runs a command by its selector, the commands of macros run through here too.
+    ///
+    /// Errors are reported by the caller so a failing command of a macro is reported once.
+    fn dispatch(&self, selector: &str, atoms: &[Atom]) -> Result<(), RytmExternalError> {
+        match selector {
+            Self::SELECTOR_QUERY => self.query(atoms),
+            Self::SELECTOR_SEND => self.send(atoms),
+            Self::SELECTOR_SEND_VERIFY => self.send_verify(atoms),
+            Self::SELECTOR_SET => self.set(atoms),
+            Self::SELECTOR_GET => self.get(atoms),
+            Self::SELECTOR_LOG_LEVEL => self.change_log_level(atoms),
+            Self::SELECTOR_LOAD => self.load(atoms),
+            Self::SELECTOR_SAVE => self.save(atoms),
+            Self::SELECTOR_COPY => self.copy(atoms),
+            Self::SELECTOR_RESET => self.reset(atoms),
+            Self::SELECTOR_IDENTIFY => self.identify(atoms),
+            Self::SELECTOR_RECORD => self.record(atoms),
+            Self::SELECTOR_REPLAY => self.replay(atoms),
+            Self::SELECTOR_CANCEL => self.cancel(atoms),
+            Self::SELECTOR_BATCH => self.batch(atoms),
+            Self::SELECTOR_DESCRIBE => self.describe(atoms),
+            Self::SELECTOR_DEFINE => self.define(atoms),
+            Self::SELECTOR_RUN => self.run(atoms),
+            Self::SELECTOR_MACROS => self.macros(atoms),
+            _ => {
+                let possible_selectors = Self::MACRO_SELECTORS
+                    .iter()
+                    .chain(&[
+                        Self::SELECTOR_DEFINE,
+                        Self::SELECTOR_RUN,
+                        Self::SELECTOR_MACROS,
+                        Self::SELECTOR_LIST,
+                    ])
+                    .copied()
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                Err(format!("Parse Error: Invalid command type {selector}. Possible commands are {possible_selectors}.").into())
+            }
+        }
+    }
+
     #[instrument(skip_all)]
     #[log_errors]
     pub fn change_log_level(&self, atoms: &[Atom]) -> Result<(), RytmExternalError> {
@@ -204,35 +394,242 @@ impl RytmExternal {
 
     #[instrument(skip_all)]
     pub fn query(&self, atoms: &[Atom]) -> Result<(), RytmExternalError> {
-        // Actually the attribute which sets this will is clipped to 0-127 but just in case:
+        self.inner.schedule_query(self.get_rytm_values(atoms)?)?;
+        self.flush_requests();
+        Ok(())
+    }
 
-        let device_id =
-            u8::try_from(self.target_device_id.load(Ordering::SeqCst)).map_err(|_| {
-                RytmExternalError::from(
-                    "Query Error: Invalid device id. Device id should be between 0 and 127.",
-                )
-            })?;
+    #[instrument(skip_all)]
+    pub fn send(&self, atoms: &[Atom]) -> Result<(), RytmExternalError> {
+        self.inner.schedule_send(self.get_rytm_values(atoms)?)?;
+        self.flush_requests();
+        Ok(())
+    }
+
+    /// Sends an object and queries it back to compare it with what was sent.
+    #[instrument(skip_all)]
+    pub fn send_verify(&self, atoms: &[Atom]) -> Result<(), RytmExternalError> {
+        self.inner
+            .schedule_verified_send(self.get_rytm_values(atoms)?)?;
+        self.flush_requests();
+        Ok(())
+    }
+
+    /// Sends a universal device inquiry to the device with the current or given sysex id.
+    ///
+    /// `identify scan` sends it to every device id and sets the sysex id from the first Analog Rytm which replies.
+    /// The inquiries leave spaced by the shorter inquiry interval, a scan of every device id takes about 6.4 seconds.
+    #[instrument(skip_all)]
+    pub fn identify(&self, atoms: &[Atom]) -> Result<(), RytmExternalError> {
+        let values = self.get_rytm_values(atoms)?;
+        let (device_ids, scan) = match values.as_slice() {
+            [] => (vec![self.device_id("Identify")?], false),
+            [RytmValue::Int(device_id)] => match u8::try_from(*device_id) {
+                Ok(device_id) if device_id <= 127 => (vec![device_id], false),
+                _ => {
+                    return Err(RytmExternalError::from(
+                        "Identify Error: Invalid device id. Device id should be between 0 and 127.",
+                    ))
+                }
+            },
+            [RytmValue::Symbol(scan)] if scan == "scan" => ((0..=127).collect(), true),
+            _ => {
+                return Err(RytmExternalError::from(
+                    "Identify Error: Invalid format. Use identify, identify <device-id> or identify scan.",
+                ))
+            }
+        };
 
-        if device_id > 127 {
+        self.inner.schedule_identify(device_ids, scan);
+        self.flush_requests();
+        Ok(())
+    }
+
+    /// Starts or stops recording the sysex traffic.
+    ///
+    /// `record start <path>` or `record stop`
+    #[instrument(skip_all)]
+    pub fn record(&self, atoms: &[Atom]) -> Result<(), RytmExternalError> {
+        let values = self.get_rytm_values(atoms)?;
+        match values.as_slice() {
+            [RytmValue::Symbol(start), RytmValue::Symbol(path)] if start == "start" => {
+                let path = self.make_utf8_path_buf_respect_tilde(path);
+                self.inner.start_recording(path.as_str())?;
+                self.send_status_success();
+                let info = format!("Recording sysex traffic to {path}.");
+                info!("{}", info);
+                info.obj_post(self.max_obj());
+            }
+            [RytmValue::Symbol(stop)] if stop == "stop" => {
+                if self.inner.stop_recording()? {
+                    self.send_status_success();
+                    info!("Recording stopped.");
+                } else {
+                    self.send_status_warning();
+                    let warning = "Record Warning: There is no recording running.";
+                    warning.obj_warn(self.max_obj());
+                    warn!("{}", warning);
+                }
+            }
+            _ => {
+                return Err(RytmExternalError::from(
+                    "Record Error: Invalid format. Use record start <file-path> or record stop.",
+                ))
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Feeds the received messages of a recording back into rytm.
+    ///
+    /// `replay <path>`
+    #[instrument(skip_all)]
+    pub fn replay(&self, atoms: &[Atom]) -> Result<(), RytmExternalError> {
+        let values = self.get_rytm_values(atoms)?;
+        let [RytmValue::Symbol(path)] = values.as_slice() else {
             return Err(RytmExternalError::from(
-                "Query Error: Invalid device id. Device id should be between 0 and 127.",
+                "Replay Error: Invalid format. Use replay <file-path>.",
             ));
-        }
+        };
 
-        let sysex = RytmObject::prepare_query(self.get_rytm_values(atoms)?, Some(device_id))?;
+        let path = self.make_utf8_path_buf_respect_tilde(path);
+        let messages = recording::read_recording(&path)?;
+        info!("Replaying {} messages from {}.", messages.len(), path);
 
-        sysex.serial_send_int(&self.sysex_out);
-        Ok(())
+        // Report every message even if an earlier one is rejected.
+        self.inner
+            .replay(&messages)?
+            .into_iter()
+            .map(|event| self.report_sysex_event(event))
+            .fold(Ok(()), Result::and)
     }
 
+    /// Cancels the running loads, saves and bulk transfers.
     #[instrument(skip_all)]
-    pub fn send(&self, atoms: &[Atom]) -> Result<(), RytmExternalError> {
-        let sysex = self.inner.prepare_sysex(self.get_rytm_values(atoms)?)?;
-        sysex.serial_send_int(&self.sysex_out);
+    pub fn cancel(&self, atoms: &[Atom]) -> Result<(), RytmExternalError> {
+        if !atoms.is_empty() {
+            return Err(RytmExternalError::from(
+                "Cancel Error: Invalid format. cancel does not accept any arguments.",
+            ));
+        }
 
+        if self.inner.cancel().is_empty() {
+            self.send_status_warning();
+            let warning = "Cancel Warning: There is nothing running to cancel.";
+            warning.obj_warn(self.max_obj());
+            warn!("{}", warning);
+        }
+        // Dropped bulk requests are reported right away, loads and saves report when they stop.
+        self.progress_events_to_outlet();
         Ok(())
     }
 
+    /// Sends a complete sysex message to the device and records it if a recording is running.
+    ///
+    /// The message leaves in the output mode and chunk size set by the attributes, after the pieces which are still waiting.
+    fn send_sysex(&self, sysex: &[u8]) {
+        self.inner.record(Direction::Out, sysex);
+        let mut outbox = self.sysex_outbox.lock();
+        let was_waiting = !outbox.is_empty();
+        outbox.push(sysex);
+        drop(outbox);
+
+        // The clock is already set for the waiting pieces.
+        if !was_waiting {
+            self.flush_sysex_output();
+        }
+    }
+
+    /// Sends the pieces of outgoing sysex, one per `chunk_delay` if it is set.
+    pub fn flush_sysex_output(&self) {
+        tracing::subscriber::with_default(Arc::clone(&self.subscriber), || {
+            self.root_span.in_scope(|| {
+                let _function_span = info_span!("flush_sysex_output").entered();
+
+                let delay = self.chunk_delay.load(Ordering::SeqCst);
+                loop {
+                    let mut outbox = self.sysex_outbox.lock();
+                    let Some(piece) = outbox.pop() else {
+                        return;
+                    };
+                    let mode = outbox.mode();
+                    let has_more = !outbox.is_empty();
+                    drop(outbox);
+
+                    match mode {
+                        OutputMode::Bytes => piece.serial_send_int(&self.sysex_out),
+                        OutputMode::List => piece.send_list(&self.sysex_list_out),
+                    }
+
+                    if delay > 0 && has_more {
+                        self.sysex_clock.delay(delay as i64);
+                        return;
+                    }
+                }
+            });
+        });
+    }
+
+    /// Sends every request which is due and schedules the clock for the next one.
+    pub fn flush_requests(&self) {
+        tracing::subscriber::with_default(Arc::clone(&self.subscriber), || {
+            self.root_span.in_scope(|| {
+                let _function_span = info_span!("flush_requests").entered();
+
+                for event in self.inner.expire_queries() {
+                    self.report_sysex_event(event).ok();
+                }
+                if self.inner.expire_inquiries() {
+                    self.send_status_error();
+                    let error = "Identify Error: No Analog Rytm answered the scan. Check the connection and send identify scan again.";
+                    error!("{}", error);
+                    error.obj_error(self.max_obj());
+                }
+
+                while let Some((scheduled, sysex)) = self.inner.next_scheduled_request() {
+                    match sysex {
+                        Ok(sysex) => {
+                            info!("Sending scheduled request: {}", scheduled.request);
+                            self.send_sysex(&sysex);
+                        }
+                        Err(err) => {
+                            self.send_status_error();
+                            let err = err.to_string();
+                            error!("{}", err);
+                            err.obj_error(self.max_obj());
+                        }
+                    }
+
+                    if let (Some(progress), Some(object)) =
+                        (scheduled.progress, scheduled.request.object())
+                    {
+                        self.progress_to_outlet(progress, object).ok();
+                    }
+                }
+
+                self.progress_events_to_outlet();
+
+                if let Some(wait) = self.inner.time_until_next_event() {
+                    // Never schedule for the same tick to avoid spinning.
+                    self.request_clock
+                        .delay((wait.as_micros().div_ceil(1000) as i64).max(1));
+                }
+            });
+        });
+    }
+
+    /// The sysex id of the device, the error is prefixed with the command which needs it, e.g. `Identify`.
+    fn device_id(&self, command: &str) -> Result<u8, RytmExternalError> {
+        // Actually the attribute which sets this is clipped to 0-127 but just in case:
+        match u8::try_from(self.target_device_id.load(Ordering::SeqCst)) {
+            Ok(device_id) if device_id <= 127 => Ok(device_id),
+            _ => Err(RytmExternalError::from(format!(
+                "{command} Error: Invalid device id. Device id should be between 0 and 127."
+            ))),
+        }
+    }
+
     #[instrument(skip_all)]
     pub fn set(&self, atoms: &[Atom]) -> Result<(), RytmExternalError> {
         self.response_to_outlet(
@@ -241,16 +638,26 @@ impl RytmExternal {
         )
         .ok();
 
+        // The object is waiting for more edits, the clock sends it when they settle.
+        if self.inner.autosend.load(Ordering::SeqCst) {
+            self.flush_requests();
+        }
+
         Ok(())
     }
 
     #[instrument(skip_all)]
     pub fn get(&self, atoms: &[Atom]) -> Result<(), RytmExternalError> {
-        self.response_to_outlet(
-            self.inner
-                .command(CommandType::Get, self.get_rytm_values(atoms)?)?,
-        )
-        .ok();
+        let values = self.get_rytm_values(atoms)?;
+        if let [RytmValue::Symbol(dirty)] = values.as_slice() {
+            if dirty == bulk_object_type::DIRTY {
+                self.dirty_to_outlet().ok();
+                return Ok(());
+            }
+        }
+
+        self.response_to_outlet(self.inner.command(CommandType::Get, values)?)
+            .ok();
 
         Ok(())
     }
@@ -266,6 +673,200 @@ impl RytmExternal {
         Ok(())
     }
 
+    /// Runs several `get` and `set` commands separated by `,` under one lock of each twin.
+    ///
+    /// The results leave in order followed by `batch <succeeded> <total>`, with one status for the whole batch.
+    #[instrument(skip_all)]
+    pub fn batch(&self, atoms: &[Atom]) -> Result<(), RytmExternalError> {
+        let results = self.inner.batch(self.get_rytm_values(atoms)?)?;
+        let total = results.len();
+
+        if results.iter().any(Result::is_err) {
+            self.send_status_error();
+        } else if results
+            .iter()
+            .any(|result| result.as_ref().is_ok_and(Response::is_unsupported))
+        {
+            self.send_status_warning();
+        } else {
+            self.send_status_success();
+        }
+
+        let mut succeeded = 0;
+        for (number, result) in results.into_iter().enumerate() {
+            match result {
+                Ok(response) => {
+                    if response.is_unsupported() {
+                        let warning = format!(
+                            "Batch Warning: Command {} is not fully supported.",
+                            number + 1
+                        );
+                        warning.obj_warn(self.max_obj());
+                        warn!("{}", warning);
+                    } else {
+                        succeeded += 1;
+                    }
+                    self.send_response(response).ok();
+                }
+                Err(err) => {
+                    let err = format!("Batch Error: Command {}. {err}", number + 1);
+                    err.obj_error(self.max_obj());
+                    error!("{}", err);
+                }
+            }
+        }
+
+        self.query_out
+            .send(
+                &[
+                    RytmValue::from("batch").as_atom(),
+                    Atom::from(succeeded as isize),
+                    Atom::from(total as isize),
+                ][..],
+            )
+            .ok();
+
+        // The edited objects are waiting for more edits, the clock sends them when they settle.
+        if self.inner.autosend.load(Ordering::SeqCst) {
+            self.flush_requests();
+        }
+
+        Ok(())
+    }
+
+    /// Describes the parameters of an object type or a single one of them.
+    ///
+    /// Every parameter leaves in the format `describe <scope> <identifier> <type> <min> <max> <default> <plockable>`.
+    #[instrument(skip_all)]
+    pub fn describe(&self, atoms: &[Atom]) -> Result<(), RytmExternalError> {
+        let parameters = self.inner.describe(self.get_rytm_values(atoms)?)?;
+        self.send_status_success();
+        parameters
+            .into_iter()
+            .try_for_each(|parameter| self.parameter_schema_to_outlet(parameter))
+            .ok();
+
+        Ok(())
+    }
+
+    /// Calls `f` with the shared macros and the file they are kept in, the file is read on the first call.
+    fn with_macros<T>(
+        f: impl FnOnce(&Path, &mut Macros) -> Result<T, RytmExternalError>,
+    ) -> Result<T, RytmExternalError> {
+        let mut guard = MACROS.lock();
+        if guard.is_none() {
+            let path = file::preferences_file_path(Self::MACROS_FOLDER, Self::MACROS_FILE_NAME)
+                .and_then(|path| path.into_string().ok())
+                .map(PathBuf::from)
+                .ok_or_else(|| {
+                    RytmExternalError::from(
+                        "Macro Error: Could not find the preferences folder of Max to keep the macros in.",
+                    )
+                })?;
+            let macros = Macros::read(&path)?;
+            *guard = Some((path, macros));
+        }
+        let (path, macros) = guard.as_mut().expect("Macros are read above.");
+        f(path, macros)
+    }
+
+    /// Defines a macro in the format `define <name> <command> , <command> ...` and keeps it in the Max preferences folder.
+    ///
+    /// A name alone removes the macro.
+    #[instrument(skip_all)]
+    pub fn define(&self, atoms: &[Atom]) -> Result<(), RytmExternalError> {
+        let values = self.get_rytm_values(atoms)?;
+        Self::with_macros(|path, macros| {
+            // The macros in memory only change when the file could be written.
+            let mut defined = macros.clone();
+            defined.define(&values, &Self::MACRO_SELECTORS)?;
+            defined.write(path)?;
+            *macros = defined;
+            Ok(())
+        })?;
+        self.send_status_success();
+
+        Ok(())
+    }
+
+    /// Runs the commands of a macro in order in the format `run <name> [<argument> ...]`.
+    ///
+    /// Every command reports like it does on its own, the macro stops at the first command which fails.
+    #[instrument(skip_all)]
+    pub fn run(&self, atoms: &[Atom]) -> Result<(), RytmExternalError> {
+        let values = self.get_rytm_values(atoms)?;
+        let Some((RytmValue::Symbol(name), arguments)) = values.split_first() else {
+            return Err(RytmExternalError::from(
+                "Macro Error: Invalid format. The name of the macro should follow run. Example: run stash 5",
+            ));
+        };
+        // The macros are not locked while the commands run, other objects may use them meanwhile.
+        let commands = Self::with_macros(|_, macros| Ok(macros.expand(name, arguments)?))?;
+        // The file may be edited by hand, a macro should never run itself.
+        if let Some(command) = commands.iter().find(|command| {
+            !matches!(command.first(), Some(RytmValue::Symbol(selector)) if Self::MACRO_SELECTORS.contains(&selector.as_str()))
+        }) {
+            return Err(format!(
+                "Macro Error: {name} has the command {command} which can not be used in a macro."
+            )
+            .into());
+        }
+
+        for (number, command) in commands.iter().enumerate() {
+            let Some((RytmValue::Symbol(selector), values)) = command.split_first() else {
+                unreachable!("Commands of macros start with their selector.");
+            };
+            let atoms = values.iter().map(RytmValue::as_atom).collect::<Vec<_>>();
+            self.dispatch(selector, &atoms).inspect_err(|_| {
+                let err = format!("Macro Error: Command {} of {name} failed.", number + 1);
+                err.obj_error(self.max_obj());
+                error!("{}", err);
+            })?;
+        }
+
+        Ok(())
+    }
+
+    /// Lists every macro in the format `macros <name> <command> , <command> ...`, `macros none` if there are none.
+    #[instrument(skip_all)]
+    pub fn macros(&self, atoms: &[Atom]) -> Result<(), RytmExternalError> {
+        if !atoms.is_empty() {
+            let warning =
+                "Warning: Macros command does not accept any arguments. Ignoring the arguments.";
+            warn!("{}", warning);
+            warning.obj_warn(self.max_obj());
+        }
+
+        let macros = Self::with_macros(|_, macros| Ok(macros.clone()))?;
+        self.send_status_success();
+        if macros.is_empty() {
+            self.results_to_outlet(
+                &[
+                    RytmValue::from(Self::SELECTOR_MACROS).as_atom(),
+                    RytmValue::from("none").as_atom(),
+                ][..],
+            )
+            .ok();
+            return Ok(());
+        }
+
+        for (name, commands) in macros.iter() {
+            let mut atoms = vec![
+                RytmValue::from(Self::SELECTOR_MACROS).as_atom(),
+                RytmValue::from(name).as_atom(),
+            ];
+            for (number, command) in commands.iter().enumerate() {
+                if number > 0 {
+                    atoms.push(RytmValue::from(batch::DELIMITER).as_atom());
+                }
+                atoms.extend(command.iter().map(RytmValue::as_atom));
+            }
+            self.results_to_outlet(&atoms[..]).ok();
+        }
+
+        Ok(())
+    }
+
     #[instrument(skip_all)]
     pub fn reset(&self, atoms: &[Atom]) -> Result<(), RytmExternalError> {
         if !atoms.is_empty() {
@@ -276,13 +877,11 @@ impl RytmExternal {
             warning.obj_warn(self.max_obj());
         }
 
-        if let Ok(p) = RytmProject::try_default().inspect_err(|err| {
+        if let Err(err) = self.inner.reset() {
             let err = format!("Error creating new RytmProject while resetting: {err}");
             self.send_status_error();
             error!("{}", err);
             err.obj_error(self.max_obj());
-        }) {
-            *self.inner.project.lock() = p;
         }
         Ok(())
     }
@@ -303,10 +902,18 @@ impl RytmExternal {
     #[instrument(skip(self))]
     fn response_to_outlet(&self, res: Response) -> Result<(), SendError> {
         self.send_status_success();
+        if res.is_unsupported() {
+            self.send_status_warning();
+        }
+        self.send_response(res)
+    }
+
+    /// Sends a response to the results outlet without a status.
+    fn send_response(&self, res: Response) -> Result<(), SendError> {
         match res {
             Response::Common { index, key, value } => self
                 .query_out
-                .send(&[Atom::from(index as isize), key.as_atom(), value.as_atom()][..]),
+                .send(&[Atom::from(response_index(index)), key.as_atom(), value.as_atom()][..]),
             Response::KitElement {
                 kit_index,
                 element_index,
@@ -314,7 +921,7 @@ impl RytmExternal {
                 value,
             } => self.query_out.send(
                 &[
-                    Atom::from(kit_index as isize),
+                    Atom::from(response_index(kit_index)),
                     Atom::from(element_index as isize),
                     element_type.as_atom(),
                     value.as_atom(),
@@ -327,7 +934,7 @@ impl RytmExternal {
                 value,
             } => self.query_out.send(
                 &[
-                    Atom::from(pattern_index as isize),
+                    Atom::from(response_index(pattern_index)),
                     Atom::from(track_index as isize),
                     key.as_atom(),
                     value.as_atom(),
@@ -341,21 +948,31 @@ impl RytmExternal {
                 value,
             } => self.query_out.send(
                 &[
-                    Atom::from(pattern_index as isize),
+                    Atom::from(response_index(pattern_index)),
                     Atom::from(track_index as isize),
                     Atom::from(trig_index as isize),
                     key.as_atom(),
                     value.as_atom(),
                 ][..],
             ),
+            Response::Variants {
+                enum_type,
+                variants,
+            } => {
+                let mut atoms = vec![RytmValue::from("variants").as_atom(), enum_type.as_atom()];
+                atoms.extend(variants.iter().map(RytmValue::as_atom));
+                self.query_out.send(&atoms[..])
+            }
             Response::Unsupported(reason) => {
-                self.send_status_warning();
                 let warning = format!("Warning: Unsupported action. Rytm will currently ignore this command. Reason: {reason}");
                 warning.obj_warn(self.max_obj());
                 warn!("{}", warning);
                 Ok(())
             }
             Response::Ok => Ok(()),
+            Response::Many(responses) => responses
+                .into_iter()
+                .try_for_each(|response| self.send_response(response)),
         }
         .inspect_err(|_| {
             "Error sending to results outlet due to stack overflow.".obj_warn(self.max_obj());
@@ -363,6 +980,387 @@ impl RytmExternal {
         })
     }
 
+    /// Offers the closest matches for a symbol which is not known in the format `suggest <symbol> <suggestion> ...`
+    fn suggestions_to_outlet(&self, err: &RytmObjectError) -> Result<(), SendError> {
+        let Some((symbol, suggestions)) = err.suggestions() else {
+            return Ok(());
+        };
+        let mut atoms = vec![
+            RytmValue::from("suggest").as_atom(),
+            RytmValue::from(symbol).as_atom(),
+        ];
+        atoms.extend(
+            suggestions
+                .iter()
+                .map(|suggestion| RytmValue::from(suggestion).as_atom()),
+        );
+
+        self.query_out.send(&atoms[..]).inspect_err(|_| {
+            "Error sending to results outlet due to stack overflow.".obj_warn(self.max_obj());
+            warn!("Error sending to results outlet due to stack overflow.");
+        })
+    }
+
+    /// Reports the progress of a bulk transfer in the format `[@device <device-id>] progress <done> <total> <object-type> [<index>]`
+    fn progress_to_outlet(
+        &self,
+        progress: Progress,
+        object: DeviceObject,
+    ) -> Result<(), SendError> {
+        let (object_type, index) = object.object.object_type_and_index();
+        let mut atoms = self.device_prefix_atoms(object.device_id);
+        atoms.extend([
+            RytmValue::from("progress").as_atom(),
+            Atom::from(progress.done as isize),
+            Atom::from(progress.total as isize),
+            RytmValue::from(object_type).as_atom(),
+        ]);
+        if let Some(index) = index {
+            atoms.push(Atom::from((index + self.inner.index_base()) as isize));
+        }
+
+        self.query_out.send(&atoms[..]).inspect_err(|_| {
+            "Error sending to results outlet due to stack overflow.".obj_warn(self.max_obj());
+            warn!("Error sending to results outlet due to stack overflow.");
+        })
+    }
+
+    /// Reports the progress events of loads, saves and bulk transfers which are collected since the last call.
+    pub fn report_progress(&self) {
+        tracing::subscriber::with_default(Arc::clone(&self.subscriber), || {
+            self.root_span.in_scope(|| {
+                let _function_span = info_span!("report_progress").entered();
+                self.progress_events_to_outlet();
+            });
+        });
+    }
+
+    /// Reports progress events on the progress outlet in the formats
+    /// `busy <0 | 1>`, `<operation> <phase> <percent>` and `<operation> <done | failed | cancelled>`.
+    fn progress_events_to_outlet(&self) {
+        for event in self.inner.take_progress_events() {
+            let atoms = match event {
+                ProgressEvent::Busy(busy) => vec![
+                    RytmValue::from("busy").as_atom(),
+                    Atom::from(isize::from(busy)),
+                ],
+                ProgressEvent::Advanced {
+                    operation,
+                    phase,
+                    percent,
+                } => vec![
+                    RytmValue::from(operation.to_string()).as_atom(),
+                    RytmValue::from(phase.to_string()).as_atom(),
+                    Atom::from(isize::from(percent)),
+                ],
+                ProgressEvent::Finished {
+                    operation,
+                    result: Ok(()),
+                } => {
+                    // Every request of a transfer already reported its own status.
+                    if operation != Operation::Transfer {
+                        self.send_status_success();
+                    }
+                    info!("The {} is done.", operation);
+                    vec![
+                        RytmValue::from(operation.to_string()).as_atom(),
+                        RytmValue::from("done").as_atom(),
+                    ]
+                }
+                ProgressEvent::Finished {
+                    operation,
+                    result: Err(err),
+                } => {
+                    self.send_status_error();
+                    let err = err.to_string();
+                    error!("{}", err);
+                    err.obj_error(self.max_obj());
+                    vec![
+                        RytmValue::from(operation.to_string()).as_atom(),
+                        RytmValue::from("failed").as_atom(),
+                    ]
+                }
+                ProgressEvent::Cancelled(operation) => {
+                    info!("The {} is cancelled.", operation);
+                    vec![
+                        RytmValue::from(operation.to_string()).as_atom(),
+                        RytmValue::from("cancelled").as_atom(),
+                    ]
+                }
+            };
+
+            self.progress_out
+                .send(&atoms[..])
+                .inspect_err(|_| {
+                    "Error sending to progress outlet due to stack overflow."
+                        .obj_warn(self.max_obj());
+                    warn!("Error sending to progress outlet due to stack overflow.");
+                })
+                .ok();
+        }
+    }
+
+    /// Reports the outcome of an incoming sysex message.
+    fn report_sysex_event(&self, event: SysexEvent) -> Result<(), RytmExternalError> {
+        if let Some(status) = event.status() {
+            self.send_status(status.code());
+        }
+
+        match event {
+            SysexEvent::Identified(identity) => {
+                self.identity_to_outlet(identity).ok();
+
+                if self.inner.finish_identify_scan(&identity) {
+                    self.target_device_id
+                        .store(identity.device_id as isize, Ordering::SeqCst);
+                    let info = format!(
+                        "Found a device with the sysex id {}. The sysex_id attribute is set accordingly.",
+                        identity.device_id
+                    );
+                    info!("{}", info);
+                    info.obj_post(self.max_obj());
+                }
+
+                if !identity.is_supported() {
+                    let warning = format!(
+                        "Warning: Unsupported device. rytm only supports the Analog Rytm with firmware {} but found {} with firmware {}.",
+                        sysex::SUPPORTED_FIRMWARE,
+                        identity.model(),
+                        identity.firmware
+                    );
+                    warn!("{}", warning);
+                    warning.obj_warn(self.max_obj());
+                }
+                Ok(())
+            }
+            SysexEvent::Applied(object) => {
+                self.sysex_event_to_outlet("updated", Some(object)).ok();
+                Ok(())
+            }
+            SysexEvent::Ready => {
+                self.query_out
+                    .send(&[RytmValue::from("ready").as_atom()][..])
+                    .inspect_err(|_| {
+                        "Error sending to results outlet due to stack overflow."
+                            .obj_warn(self.max_obj());
+                        warn!("Error sending to results outlet due to stack overflow.");
+                    })
+                    .ok();
+                Ok(())
+            }
+            SysexEvent::Rejected { object, error } => {
+                self.sysex_event_to_outlet("rejected", object).ok();
+                Err(error.into())
+            }
+            SysexEvent::Verified { object, mismatches } => {
+                self.sysex_event_to_outlet("updated", Some(object)).ok();
+                self.verification_to_outlet(object, &mismatches).ok();
+
+                if !mismatches.is_empty() {
+                    let warning = format!(
+                        "Warning: The {object} differs from what was sent in {} parameters.",
+                        mismatches.len()
+                    );
+                    warn!("{}", warning);
+                    warning.obj_warn(self.max_obj());
+                }
+                Ok(())
+            }
+            SysexEvent::TimedOut(object) => {
+                self.timeout_to_outlet(object).ok();
+                Ok(())
+            }
+        }
+    }
+
+    /// Reports the result of a verified send in the format `verify <object-type> <index> <work-buffer-flag> match`
+    ///
+    /// If the object differs `verify <object-type> <index> <work-buffer-flag> mismatch <count>` is sent,
+    /// followed by `mismatch <parameter-path> <sent-value> <device-value>` for every differing parameter.
+    /// A value which could not be read is reported as `none`.
+    fn verification_to_outlet(
+        &self,
+        object: DeviceObject,
+        mismatches: &[Mismatch],
+    ) -> Result<(), SendError> {
+        let mut atoms = self.object_atoms("verify", object);
+        if mismatches.is_empty() {
+            atoms.push(RytmValue::from("match").as_atom());
+        } else {
+            atoms.push(RytmValue::from("mismatch").as_atom());
+            atoms.push(Atom::from(mismatches.len() as isize));
+        }
+        self.results_to_outlet(&atoms)?;
+
+        mismatches.iter().try_for_each(|mismatch| {
+            let none = || RytmValue::from("none");
+            let atoms = std::iter::once(RytmValue::from("mismatch"))
+                .chain(mismatch.path.iter().cloned())
+                .chain([
+                    mismatch.local.clone().unwrap_or_else(none),
+                    mismatch.device.clone().unwrap_or_else(none),
+                ])
+                .map(|value| value.as_atom())
+                .collect::<Vec<_>>();
+            self.results_to_outlet(&atoms)
+        })
+    }
+
+    /// Reports every changed object in the format `[@device <device-id>] dirty <object-type> <index> <work-buffer-flag>`
+    ///
+    /// If nothing is changed only `dirty none` is sent.
+    fn dirty_to_outlet(&self) -> Result<(), SendError> {
+        self.send_status_success();
+        let dirty = self.inner.dirty_objects();
+        if dirty.is_empty() {
+            return self
+                .query_out
+                .send(
+                    &[
+                        RytmValue::from(bulk_object_type::DIRTY).as_atom(),
+                        RytmValue::from("none").as_atom(),
+                    ][..],
+                )
+                .inspect_err(|_| {
+                    "Error sending to results outlet due to stack overflow."
+                        .obj_warn(self.max_obj());
+                    warn!("Error sending to results outlet due to stack overflow.");
+                });
+        }
+
+        dirty.into_iter().try_for_each(|object| {
+            self.sysex_event_to_outlet(bulk_object_type::DIRTY, Some(object))
+        })
+    }
+
+    /// Reports a parameter of `describe`, the values which are not known are sent as `none`.
+    fn parameter_schema_to_outlet(&self, parameter: ParameterSchema) -> Result<(), SendError> {
+        let none = || RytmValue::from("none");
+        let (min, max) = parameter.range.map_or_else(
+            || (none(), none()),
+            |range| (range.min.into(), range.max.into()),
+        );
+
+        self.query_out
+            .send(
+                &[
+                    RytmValue::from("describe").as_atom(),
+                    RytmValue::from(parameter.scope.to_string()).as_atom(),
+                    RytmValue::from(parameter.identifier).as_atom(),
+                    parameter
+                        .value_type
+                        .map_or_else(none, |value_type| value_type.to_string().into())
+                        .as_atom(),
+                    min.as_atom(),
+                    max.as_atom(),
+                    parameter.default.unwrap_or_else(none).as_atom(),
+                    Atom::from(isize::from(parameter.plockable)),
+                ][..],
+            )
+            .inspect_err(|_| {
+                "Error sending to results outlet due to stack overflow.".obj_warn(self.max_obj());
+                warn!("Error sending to results outlet due to stack overflow.");
+            })
+    }
+
+    /// Reports an identity reply in the format `identity <device-id> <model> <firmware>`
+    fn identity_to_outlet(&self, identity: DeviceIdentity) -> Result<(), SendError> {
+        self.query_out
+            .send(
+                &[
+                    RytmValue::from("identity").as_atom(),
+                    Atom::from(identity.device_id as isize),
+                    RytmValue::from(identity.model()).as_atom(),
+                    RytmValue::from(identity.firmware.to_string()).as_atom(),
+                ][..],
+            )
+            .inspect_err(|_| {
+                "Error sending to results outlet due to stack overflow.".obj_warn(self.max_obj());
+                warn!("Error sending to results outlet due to stack overflow.");
+            })
+    }
+
+    /// Reports an incoming object in the format `[@device <device-id>] <event> <object-type> <index> <work-buffer-flag>`
+    ///
+    /// If the object is unknown only `<event> unknown` is sent.
+    fn sysex_event_to_outlet(
+        &self,
+        event: &str,
+        object: Option<DeviceObject>,
+    ) -> Result<(), SendError> {
+        let atoms = object.map_or_else(
+            || {
+                vec![
+                    RytmValue::from(event).as_atom(),
+                    RytmValue::from("unknown").as_atom(),
+                ]
+            },
+            |object| self.object_atoms(event, object),
+        );
+
+        self.results_to_outlet(&atoms)
+    }
+
+    /// `[@device <device-id>] <event> <object-type> <index> <work-buffer-flag>`
+    fn object_atoms(&self, event: &str, object: DeviceObject) -> Vec<Atom> {
+        let (object_type, index, is_work_buffer) = object.object.object_parts();
+        // Only indexes which are written in commands follow the index base.
+        let index = match object.object.object_type_and_index() {
+            (_, Some(_)) => index + self.inner.index_base(),
+            (_, None) => index,
+        };
+        let mut atoms = self.device_prefix_atoms(object.device_id);
+        atoms.extend([
+            RytmValue::from(event).as_atom(),
+            RytmValue::from(object_type).as_atom(),
+            Atom::from(index as isize),
+            Atom::from(isize::from(is_work_buffer)),
+        ]);
+        atoms
+    }
+
+    /// Output about devices other than the default one starts with `@device <device-id>`.
+    fn device_prefix_atoms(&self, device_id: u8) -> Vec<Atom> {
+        if device_id == self.inner.default_device_id() {
+            return Vec::new();
+        }
+        vec![
+            RytmValue::from(device_prefix::DEVICE).as_atom(),
+            Atom::from(isize::from(device_id)),
+        ]
+    }
+
+    fn results_to_outlet(&self, atoms: &[Atom]) -> Result<(), SendError> {
+        self.query_out.send(atoms).inspect_err(|_| {
+            "Error sending to results outlet due to stack overflow.".obj_warn(self.max_obj());
+            warn!("Error sending to results outlet due to stack overflow.");
+        })
+    }
+
+    /// Reports a query which did not get a response after all retries in the format `[@device <device-id>] timeout <object-type> [<index>]`
+    ///
+    /// The status outlet reports the error before, see [`SysexEvent::status`].
+    fn timeout_to_outlet(&self, object: DeviceObject) -> Result<(), SendError> {
+        let error = format!("Query Error: The device did not respond to the query for {object}.");
+        error!("{}", error);
+        error.obj_error(self.max_obj());
+
+        let (object_type, index) = object.object.object_type_and_index();
+        let mut atoms = self.device_prefix_atoms(object.device_id);
+        atoms.extend([
+            RytmValue::from("timeout").as_atom(),
+            RytmValue::from(object_type).as_atom(),
+        ]);
+        if let Some(index) = index {
+            atoms.push(Atom::from((index + self.inner.index_base()) as isize));
+        }
+
+        self.query_out.send(&atoms[..]).inspect_err(|_| {
+            "Error sending to results outlet due to stack overflow.".obj_warn(self.max_obj());
+            warn!("Error sending to results outlet due to stack overflow.");
+        })
+    }
+
     fn send_status(&self, code: isize) {
         self.status_out
             .send(code)
@@ -374,15 +1372,23 @@ impl RytmExternal {
     }
 
     fn send_status_success(&self) {
-        self.send_status(0);
+        self.send_status(Status::Success.code());
     }
 
     fn send_status_error(&self) {
-        self.send_status(1);
+        self.send_status(Status::Error.code());
     }
 
     fn send_status_warning(&self) {
-        self.send_status(2);
+        self.send_status(Status::Warning.code());
+    }
+}
+
+/// The index a response is sent with, objects which are not addressed with an index respond with 0.
+const fn response_index(index: Option<usize>) -> isize {
+    match index {
+        Some(index) => index as isize,
+        None => 0,
     }
 }
 
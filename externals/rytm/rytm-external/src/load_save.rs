@@ -2,15 +2,35 @@ use crate::{
     error::RytmExternalError,
     file::{FilePathExt, RytmProjectFileType},
     traits::Post,
+    trampoline::DeferTarget,
     types::{SaveTarget, SaveTargetIndex},
     RytmExternal,
 };
 use camino::Utf8PathBuf;
 use median::{atom::Atom, object::MaxObj, symbol::SymbolRef};
-use rytm_object::value::RytmValue;
-use rytm_rs::SysexCompatible;
+use rytm_object::{
+    error::RytmObjectError,
+    progress::{self, CancelToken, Operation, Phase, Reporter},
+    value::RytmValue,
+};
+use rytm_rs::{RytmProject, SysexCompatible};
+use std::{path::Path, sync::Arc, thread::JoinHandle};
 use tracing::{debug, error, instrument, warn};
 
+/// A load or a save running on its own thread.
+pub struct BackgroundWork {
+    thread: JoinHandle<()>,
+    token: CancelToken,
+}
+
+impl BackgroundWork {
+    /// Cancels the work and waits for its thread to stop.
+    pub fn stop(self) {
+        self.token.cancel();
+        self.thread.join().ok();
+    }
+}
+
 impl RytmExternal {
     #[instrument]
     pub fn validate_and_get_save_target_index(
@@ -97,96 +117,105 @@ impl RytmExternal {
         let maybe_ext = file_name_camino.extension();
         let file_type = Self::expect_our_file_types(maybe_ext)?;
 
+        let absolute_path = file
+            .to_absolute_system_path()
+            .ok_or_else(|| RytmExternalError::from("Load Error: Failed to get absolute path."))
+            .inspect_err(|err| error!("{}", err))?
+            .to_string_lossy()
+            .to_string();
+
         debug!("Loading project part from: {}.", file_name);
 
-        match file_type {
-            RytmProjectFileType::Sysex => {
-                let absolute_path = file
-                    .to_absolute_system_path()
-                    .ok_or_else(|| {
-                        RytmExternalError::from("Load Error: Failed to get absolute path.")
-                    })?
-                    .to_string_lossy()
-                    .to_string();
-
-                let Ok(bytes) = std::fs::read(&absolute_path) else {
-                    return Err(RytmExternalError::from(
-                        "Load Error: Failed to read sysex file.",
-                    ))
-                    .inspect_err(|err| {
-                        error!("{}", err);
-                    });
-                };
+        let project = Arc::clone(&self.inner.project);
+        let dirty = Arc::clone(&self.inner.dirty);
+        let verifications = Arc::clone(&self.inner.verifications);
+        let device_id = self.inner.default_device_id();
+        self.run_in_background(Operation::Load, move |reporter| {
+            let bytes = progress::read_file(Path::new(&absolute_path), reporter)?;
+            debug!("File loaded into memory.");
+
+            match file_type {
+                RytmProjectFileType::Sysex => {
+                    // Because this load will load the file into the exact place where it was before.
+                    // If it was kit 2 then it will be kit 2 again. We can not change that.
+                    // TODO: If we implement copy and pasting with some sysex magic we can extend this behaviour.
+
+                    reporter.advance(Phase::Applying, 0, 1)?;
+                    project
+                        .lock()
+                        .update_from_sysex_response(&bytes)
+                        .map_err(|err| {
+                            format!("Load Error: Failed to parse sysex file: {err:?}")
+                        })?;
 
-                debug!("Sysex file loaded into memory.");
-
-                // Because this load will load the file into the exact place where it was before.
-                // If it was kit 2 then it will be kit 2 again. We can not change that.
-                // TODO: If we implement copy and pasting with some sysex magic we can extend this behaviour.
-
-                self.inner
-                    .project
-                    .lock()
-                    .update_from_sysex_response(&bytes)
-                    .map_err(|err| {
-                        RytmExternalError::from(format!(
-                            "Load Error: Failed to parse sysex file: {err:?}"
-                        ))
-                    })
-                    .inspect_err(|err| {
-                        error!("{}", err);
-                    })?;
-
-                debug!("Project part loaded from {} (sysex).", file_name);
+                    debug!("Project part loaded from {} (sysex).", absolute_path);
+                }
+                RytmProjectFileType::Rytm => {
+                    reporter.advance(Phase::Parsing, 0, 1)?;
+                    let mut loaded = RytmProject::try_from_str(std::str::from_utf8(&bytes)?)
+                        .map_err(|err| format!("Load Error: Failed to parse project: {err:?}"))?;
+
+                    debug!("Complete project parsed.");
+
+                    // The last chance to cancel, the project is replaced right after.
+                    reporter.advance(Phase::Applying, 0, 1)?;
+                    loaded.set_device_id(device_id);
+                    *project.lock() = loaded;
+                    // Changes and sends of the replaced project do not apply to the loaded one.
+                    dirty.lock().retain(|object| object.device_id != device_id);
+                    verifications
+                        .lock()
+                        .retain(|object, _| object.device_id != device_id);
+
+                    debug!("Complete project loaded (rytm).");
+                }
             }
-            RytmProjectFileType::Rytm => {
-                let Ok(project_text) = file.read_text(median::file::TextLineBreak::Native, None)
-                else {
-                    return Err(RytmExternalError::from(
-                        "Load Error: Failed to read project.",
-                    ))
-                    .inspect_err(|err| {
-                        error!("{}", err);
-                    });
-                };
-
-                debug!(
-                    "Complete project loaded as text from: {}.",
-                    file.file_name.to_string_lossy()
-                );
-
-                let project_text = project_text.to_str()?;
+            Ok(())
+        })
+    }
 
-                let project: rytm_rs::RytmProject =
-                    rytm_rs::RytmProject::try_from_str(project_text)
-                        .map_err(|err| {
-                            RytmExternalError::from(format!(
-                                "Load Error: Failed to parse project: {err:?}"
-                            ))
-                        })
-                        .inspect_err(|err| {
-                            error!("{}", err);
-                        })?;
+    /// Runs a load or a save on a worker thread.
+    ///
+    /// Its progress and outcome are reported on the progress outlet, `cancel` stops it at its next step.
+    fn run_in_background(
+        &self,
+        operation: Operation,
+        work: impl FnOnce(&Reporter) -> Result<(), RytmObjectError> + Send + 'static,
+    ) -> Result<(), RytmExternalError> {
+        let target = DeferTarget::new(self.max_obj(), &self.alive);
+        let reporter = Reporter::begin(
+            &self.inner.progress,
+            operation,
+            Box::new(move || target.defer_progress()),
+        )?;
+
+        // Only one load or save runs at a time, the previous one already finished and its thread is only joined.
+        if let Some(previous) = self.background.lock().take() {
+            previous.thread.join().ok();
+        }
 
-                debug!("Complete project parsed.");
-
-                *self
-                    .inner
-                    .project
-                    .try_lock_for(std::time::Duration::from_secs(5))
-                    .ok_or_else(|| {
-                        RytmExternalError::from(
-                            "Load Error: rytm is busy try again after some time.",
-                        )
-                    })
-                    .inspect_err(|err| {
-                        error!("{}", err);
-                    })? = project;
-
-                debug!("Complete project loaded (rytm).");
+        let token = reporter.token();
+        let subscriber = Arc::clone(&self.subscriber);
+        match std::thread::Builder::new()
+            .name(format!("rytm-{operation}"))
+            .spawn(move || {
+                tracing::subscriber::with_default(subscriber, || {
+                    let result = work(&reporter);
+                    reporter.finish(result);
+                });
+            }) {
+            Ok(thread) => *self.background.lock() = Some(BackgroundWork { thread, token }),
+            Err(err) => {
+                // The reporter is gone with the thread, the failure is reported with the progress events.
+                self.inner.progress.lock().finish(
+                    operation,
+                    Err(
+                        format!("Rytm Error: Failed to start the {operation} thread: {err}").into(),
+                    ),
+                );
+                self.report_progress();
             }
         }
-        self.send_status_success();
         Ok(())
     }
 
@@ -195,7 +224,7 @@ impl RytmExternal {
     pub fn save(&self, atoms: &[Atom]) -> Result<(), RytmExternalError> {
         let span = tracing::Span::current();
 
-        let values = self.get_rytm_values(atoms)?;
+        let values = self.inner.rebase_indices(self.get_rytm_values(atoms)?)?;
         span.record("args", format!("{values:?}"));
         let mut values_f = values.iter().peekable();
         let mut values_b = values.iter().peekable().rev();
@@ -496,33 +525,18 @@ impl RytmExternal {
     pub fn save_entire_project(&self, path: &camino::Utf8PathBuf) -> Result<(), RytmExternalError> {
         debug!("Saving complete project to: {}.", path);
 
-        let project_text = self
-            .inner
-            .project
-            .try_lock_for(std::time::Duration::from_secs(5))
-            .ok_or_else(|| {
-                RytmExternalError::from("Save Error: rytm is busy try again after some time.")
-            })
-            .inspect_err(|err| error!("{}", err))?
-            .try_to_string()
-            .map_err(|err| {
-                RytmExternalError::from(format!(
-                    "Save Error: Failed to serialize project for saving: {err:?}"
-                ))
-            })
-            .inspect_err(|err| error!("{}", err))?;
-
-        std::fs::write(path, project_text)
-            .map_err(|err| {
-                RytmExternalError::from(format!(
-                    "Save Error: Failed to write project to file {path}: {err:?}"
-                ))
-            })
-            .inspect(|()| {
-                self.send_status_success();
-                debug!("Project saved to: {}.", path);
-            })
-            .inspect_err(|err| error!("{}", err))
+        let project = Arc::clone(&self.inner.project);
+        let path = path.clone();
+        self.run_in_background(Operation::Save, move |reporter| {
+            reporter.advance(Phase::Serializing, 0, 1)?;
+            let project_text = project.lock().try_to_string().map_err(|err| {
+                format!("Save Error: Failed to serialize project for saving: {err:?}")
+            })?;
+
+            progress::write_file(path.as_std_path(), project_text.as_bytes(), reporter)?;
+            debug!("Project saved to: {}.", path);
+            Ok(())
+        })
     }
 
     #[instrument(skip(self))]
@@ -555,16 +569,11 @@ impl RytmExternal {
                 RytmExternalError::from(format!("Save Error: Failed to serialize project part for saving: {err:?}"))
             }).inspect_err(|err| error!("{}", err))?;
 
-        std::fs::write(path, payload)
-            .map_err(|err| {
-                RytmExternalError::from(format!(
-                    "Save Error: Failed to write project part to file {path}: {err:?}"
-                ))
-            })
-            .inspect(|()| {
-                self.send_status_success();
-                debug!("Project part saved to: {}.", path);
-            })
-            .inspect_err(|err| error!("{}", err))
+        let path = path.clone();
+        self.run_in_background(Operation::Save, move |reporter| {
+            progress::write_file(path.as_std_path(), &payload, reporter)?;
+            debug!("Project part saved to: {}.", path);
+            Ok(())
+        })
     }
 }
@@ -1,27 +1,49 @@
 #![allow(clippy::not_unsafe_ptr_arg_deref)]
 
-use median::{max_sys, outlet::SendValue, symbol::SymbolRef};
+use median::{
+    atom::Atom,
+    max_sys,
+    outlet::{OutAnything, SendValue},
+    symbol::SymbolRef,
+};
 use std::ffi::CString;
 use tracing::warn;
 
-/// For flushing data from an outlet serially.
+/// For flushing sysex data from an outlet.
 pub trait SerialSend {
+    /// Sends one int per byte.
     #[allow(clippy::borrowed_box)]
-    fn serial_send_int(&self, out: &Box<dyn SendValue<isize> + Sync>);
+    fn serial_send_int(&self, outlet: &Box<dyn SendValue<isize> + Sync>);
+    /// Sends all bytes in one list.
+    fn send_list(&self, outlet: &OutAnything);
 }
 
-impl SerialSend for Vec<u8> {
+impl SerialSend for [u8] {
     fn serial_send_int(&self, outlet: &Box<dyn SendValue<isize> + Sync>) {
         for byte in self {
             outlet
                 .send(*byte as isize)
                 .inspect_err(|_| {
-                    median::error!("Error sending to status outlet due to stack overflow.");
-                    warn!("Error sending to status outlet due to stack overflow.");
+                    median::error!("Error sending to sysex outlet due to stack overflow.");
+                    warn!("Error sending to sysex outlet due to stack overflow.");
                 })
                 .ok();
         }
     }
+
+    fn send_list(&self, outlet: &OutAnything) {
+        let atoms = self
+            .iter()
+            .map(|byte| Atom::from(*byte as isize))
+            .collect::<Vec<_>>();
+        outlet
+            .send(&atoms[..])
+            .inspect_err(|_| {
+                median::error!("Error sending to sysex list outlet due to stack overflow.");
+                warn!("Error sending to sysex list outlet due to stack overflow.");
+            })
+            .ok();
+    }
 }
 
 // Post trait for posting to the max console.
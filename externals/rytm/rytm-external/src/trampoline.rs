@@ -1,12 +1,20 @@
 use super::RytmExternal;
 use core::ffi::c_void;
 use median::{
-    max_sys::{t_atom, t_atom_long, t_symbol},
+    max_sys::{self, t_atom, t_atom_long, t_symbol},
     method,
     object::MaxObj,
     wrapper::{MaxObjWrapper, WrapperWrapped},
 };
-use std::{os::raw::c_long, sync::atomic::Ordering};
+use rytm_object::sysex::OutputMode;
+use std::{
+    os::raw::c_long,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
 
 impl RytmExternal {
     // Methods:
@@ -30,6 +38,36 @@ impl RytmExternal {
         });
     }
 
+    /// Called from the low priority queue after the decoder thread decoded a dump.
+    pub extern "C" fn decoded_tramp(
+        wrapper: *const MaxObjWrapper<Self>,
+        _sel: *mut t_symbol,
+        _ac: c_long,
+        av: *mut t_atom,
+    ) {
+        // SAFETY: The call is deferred by `DeferTarget::defer`.
+        let Some(wrapper) = (unsafe { deferred_wrapper(wrapper, av) }) else {
+            return;
+        };
+        if let Err(err) = WrapperWrapped::wrapped(wrapper).report_decoded() {
+            err.obj_post(wrapper.wrapped().max_obj());
+        }
+    }
+
+    /// Called from the low priority queue when a load, save or bulk transfer has progress to report.
+    pub extern "C" fn progress_tramp(
+        wrapper: *const MaxObjWrapper<Self>,
+        _sel: *mut t_symbol,
+        _ac: c_long,
+        av: *mut t_atom,
+    ) {
+        // SAFETY: The call is deferred by `DeferTarget::defer`.
+        let Some(wrapper) = (unsafe { deferred_wrapper(wrapper, av) }) else {
+            return;
+        };
+        WrapperWrapped::wrapped(wrapper).report_progress();
+    }
+
     // Attributes:
 
     // Trampoline for getting frequency
@@ -59,7 +97,359 @@ impl RytmExternal {
             let external = WrapperWrapped::wrapped(wrapper);
             external.target_device_id.store(val, Ordering::SeqCst);
             // Value is always valid because it is clamped.
-            external.inner.project.lock().set_device_id(val as u8);
+            external.inner.set_default_device_id(val as u8);
+        });
+    }
+
+    #[allow(clippy::needless_pass_by_value)]
+    pub extern "C" fn attr_get_interval_tramp(
+        wrapper: &MaxObjWrapper<Self>,
+        _attr: c_void,
+        ac: *mut c_long,
+        av: *mut *mut t_atom,
+    ) {
+        median::attr::get(ac, av, || {
+            WrapperWrapped::wrapped(wrapper)
+                .inner
+                .scheduler
+                .lock()
+                .interval()
+                .as_millis() as isize
+        });
+    }
+
+    #[allow(clippy::needless_pass_by_value)]
+    pub extern "C" fn attr_set_interval_tramp(
+        wrapper: &MaxObjWrapper<Self>,
+        _attr: c_void,
+        ac: c_long,
+        av: *mut t_atom,
+    ) {
+        median::attr::set(ac, av, |val: isize| {
+            // Value is always valid because it is clamped.
+            WrapperWrapped::wrapped(wrapper)
+                .inner
+                .scheduler
+                .lock()
+                .set_interval(Duration::from_millis(val as u64));
+        });
+    }
+
+    #[allow(clippy::needless_pass_by_value)]
+    pub extern "C" fn attr_get_timeout_tramp(
+        wrapper: &MaxObjWrapper<Self>,
+        _attr: c_void,
+        ac: *mut c_long,
+        av: *mut *mut t_atom,
+    ) {
+        median::attr::get(ac, av, || {
+            WrapperWrapped::wrapped(wrapper)
+                .inner
+                .scheduler
+                .lock()
+                .timeout()
+                .as_millis() as isize
+        });
+    }
+
+    #[allow(clippy::needless_pass_by_value)]
+    pub extern "C" fn attr_set_timeout_tramp(
+        wrapper: &MaxObjWrapper<Self>,
+        _attr: c_void,
+        ac: c_long,
+        av: *mut t_atom,
+    ) {
+        median::attr::set(ac, av, |val: isize| {
+            // Value is always valid because it is clamped.
+            WrapperWrapped::wrapped(wrapper)
+                .inner
+                .scheduler
+                .lock()
+                .set_timeout(Duration::from_millis(val as u64));
+        });
+    }
+
+    #[allow(clippy::needless_pass_by_value)]
+    pub extern "C" fn attr_get_retries_tramp(
+        wrapper: &MaxObjWrapper<Self>,
+        _attr: c_void,
+        ac: *mut c_long,
+        av: *mut *mut t_atom,
+    ) {
+        median::attr::get(ac, av, || {
+            WrapperWrapped::wrapped(wrapper)
+                .inner
+                .scheduler
+                .lock()
+                .max_retries() as isize
+        });
+    }
+
+    #[allow(clippy::needless_pass_by_value)]
+    pub extern "C" fn attr_set_retries_tramp(
+        wrapper: &MaxObjWrapper<Self>,
+        _attr: c_void,
+        ac: c_long,
+        av: *mut t_atom,
+    ) {
+        median::attr::set(ac, av, |val: isize| {
+            // Value is always valid because it is clamped.
+            WrapperWrapped::wrapped(wrapper)
+                .inner
+                .scheduler
+                .lock()
+                .set_max_retries(val as usize);
+        });
+    }
+
+    #[allow(clippy::needless_pass_by_value)]
+    pub extern "C" fn attr_get_sysex_mode_tramp(
+        wrapper: &MaxObjWrapper<Self>,
+        _attr: c_void,
+        ac: *mut c_long,
+        av: *mut *mut t_atom,
+    ) {
+        median::attr::get(ac, av, || {
+            isize::from(WrapperWrapped::wrapped(wrapper).sysex_outbox.lock().mode())
+        });
+    }
+
+    #[allow(clippy::needless_pass_by_value)]
+    pub extern "C" fn attr_set_sysex_mode_tramp(
+        wrapper: &MaxObjWrapper<Self>,
+        _attr: c_void,
+        ac: c_long,
+        av: *mut t_atom,
+    ) {
+        median::attr::set(ac, av, |val: isize| {
+            // Value is always valid because it is clamped.
+            if let Ok(mode) = OutputMode::try_from(val) {
+                WrapperWrapped::wrapped(wrapper)
+                    .sysex_outbox
+                    .lock()
+                    .set_mode(mode);
+            }
+        });
+    }
+
+    #[allow(clippy::needless_pass_by_value)]
+    pub extern "C" fn attr_get_chunk_size_tramp(
+        wrapper: &MaxObjWrapper<Self>,
+        _attr: c_void,
+        ac: *mut c_long,
+        av: *mut *mut t_atom,
+    ) {
+        median::attr::get(ac, av, || {
+            WrapperWrapped::wrapped(wrapper)
+                .sysex_outbox
+                .lock()
+                .chunk_size() as isize
+        });
+    }
+
+    #[allow(clippy::needless_pass_by_value)]
+    pub extern "C" fn attr_set_chunk_size_tramp(
+        wrapper: &MaxObjWrapper<Self>,
+        _attr: c_void,
+        ac: c_long,
+        av: *mut t_atom,
+    ) {
+        median::attr::set(ac, av, |val: isize| {
+            // Value is always valid because it is clamped.
+            WrapperWrapped::wrapped(wrapper)
+                .sysex_outbox
+                .lock()
+                .set_chunk_size(val as usize);
+        });
+    }
+
+    #[allow(clippy::needless_pass_by_value)]
+    pub extern "C" fn attr_get_chunk_delay_tramp(
+        wrapper: &MaxObjWrapper<Self>,
+        _attr: c_void,
+        ac: *mut c_long,
+        av: *mut *mut t_atom,
+    ) {
+        median::attr::get(ac, av, || {
+            WrapperWrapped::wrapped(wrapper)
+                .chunk_delay
+                .load(Ordering::SeqCst)
+        });
+    }
+
+    #[allow(clippy::needless_pass_by_value)]
+    pub extern "C" fn attr_set_chunk_delay_tramp(
+        wrapper: &MaxObjWrapper<Self>,
+        _attr: c_void,
+        ac: c_long,
+        av: *mut t_atom,
+    ) {
+        median::attr::set(ac, av, |val: isize| {
+            // Value is always valid because it is clamped.
+            WrapperWrapped::wrapped(wrapper)
+                .chunk_delay
+                .store(val, Ordering::SeqCst);
+        });
+    }
+
+    #[allow(clippy::needless_pass_by_value)]
+    pub extern "C" fn attr_get_autosend_tramp(
+        wrapper: &MaxObjWrapper<Self>,
+        _attr: c_void,
+        ac: *mut c_long,
+        av: *mut *mut t_atom,
+    ) {
+        median::attr::get(ac, av, || {
+            isize::from(
+                WrapperWrapped::wrapped(wrapper)
+                    .inner
+                    .autosend
+                    .load(Ordering::SeqCst),
+            )
+        });
+    }
+
+    #[allow(clippy::needless_pass_by_value)]
+    pub extern "C" fn attr_set_autosend_tramp(
+        wrapper: &MaxObjWrapper<Self>,
+        _attr: c_void,
+        ac: c_long,
+        av: *mut t_atom,
+    ) {
+        median::attr::set(ac, av, |val: isize| {
+            WrapperWrapped::wrapped(wrapper)
+                .inner
+                .autosend
+                .store(val != 0, Ordering::SeqCst);
+        });
+    }
+
+    #[allow(clippy::needless_pass_by_value)]
+    pub extern "C" fn attr_get_debounce_tramp(
+        wrapper: &MaxObjWrapper<Self>,
+        _attr: c_void,
+        ac: *mut c_long,
+        av: *mut *mut t_atom,
+    ) {
+        median::attr::get(ac, av, || {
+            WrapperWrapped::wrapped(wrapper)
+                .inner
+                .scheduler
+                .lock()
+                .debounce()
+                .as_millis() as isize
+        });
+    }
+
+    #[allow(clippy::needless_pass_by_value)]
+    pub extern "C" fn attr_set_debounce_tramp(
+        wrapper: &MaxObjWrapper<Self>,
+        _attr: c_void,
+        ac: c_long,
+        av: *mut t_atom,
+    ) {
+        median::attr::set(ac, av, |val: isize| {
+            // Value is always valid because it is clamped.
+            WrapperWrapped::wrapped(wrapper)
+                .inner
+                .scheduler
+                .lock()
+                .set_debounce(Duration::from_millis(val as u64));
+        });
+    }
+
+    #[allow(clippy::needless_pass_by_value)]
+    pub extern "C" fn attr_get_indexbase_tramp(
+        wrapper: &MaxObjWrapper<Self>,
+        _attr: c_void,
+        ac: *mut c_long,
+        av: *mut *mut t_atom,
+    ) {
+        median::attr::get(ac, av, || {
+            WrapperWrapped::wrapped(wrapper).inner.index_base() as isize
+        });
+    }
+
+    #[allow(clippy::needless_pass_by_value)]
+    pub extern "C" fn attr_set_indexbase_tramp(
+        wrapper: &MaxObjWrapper<Self>,
+        _attr: c_void,
+        ac: c_long,
+        av: *mut t_atom,
+    ) {
+        median::attr::set(ac, av, |val: isize| {
+            // Value is always valid because it is clamped.
+            WrapperWrapped::wrapped(wrapper)
+                .inner
+                .index_base
+                .store(val as usize, Ordering::SeqCst);
         });
     }
 }
+
+/// The Max object of the external, for calling it back from other threads.
+///
+/// Calls which are still queued when the object is freed are dropped, see [`RytmExternal::alive`].
+#[derive(Debug, Clone)]
+pub struct DeferTarget {
+    max_obj: *mut max_sys::t_object,
+    alive: Arc<AtomicBool>,
+}
+
+// Max allows deferring calls to an object from any thread.
+unsafe impl Send for DeferTarget {}
+
+impl DeferTarget {
+    pub fn new(max_obj: *mut max_sys::t_object, alive: &Arc<AtomicBool>) -> Self {
+        Self {
+            max_obj,
+            alive: Arc::clone(alive),
+        }
+    }
+
+    /// Calls [`RytmExternal::decoded_tramp`] from the low priority queue.
+    pub fn defer_decoded(&self) {
+        self.defer(RytmExternal::decoded_tramp as *const c_void);
+    }
+
+    /// Calls [`RytmExternal::progress_tramp`] from the low priority queue.
+    pub fn defer_progress(&self) {
+        self.defer(RytmExternal::progress_tramp as *const c_void);
+    }
+
+    /// Queues a call of a trampoline which reads its object with [`deferred_wrapper`].
+    fn defer(&self, tramp: *const c_void) {
+        // The queued call owns a reference to the liveness flag, it is taken back when the call runs.
+        let alive = Arc::into_raw(Arc::clone(&self.alive));
+        unsafe {
+            let mut argument = std::mem::zeroed::<t_atom>();
+            max_sys::atom_setobj(&mut argument, alive.cast_mut().cast());
+            max_sys::defer_low(
+                self.max_obj.cast(),
+                // SAFETY: Max calls deferred methods as `method(object, symbol, argc, argv)`,
+                // the trampolines have that signature and `max_sys::method` is only its type erased form.
+                std::mem::transmute::<*const c_void, max_sys::method>(tramp),
+                std::ptr::null_mut(),
+                1,
+                &mut argument,
+            );
+        }
+    }
+}
+
+/// The object of a call which is deferred by [`DeferTarget`], `None` if the object is freed since.
+///
+/// # Safety
+///
+/// `av` should be the arguments of a call which is deferred by [`DeferTarget::defer`], every call should be read once.
+unsafe fn deferred_wrapper<'a>(
+    wrapper: *const MaxObjWrapper<RytmExternal>,
+    av: *mut t_atom,
+) -> Option<&'a MaxObjWrapper<RytmExternal>> {
+    if av.is_null() {
+        return None;
+    }
+    let alive = Arc::from_raw(max_sys::atom_getobj(av).cast::<AtomicBool>().cast_const());
+    // Deferred calls run on the main thread like the free method, so the object can not be freed after the check.
+    alive.load(Ordering::SeqCst).then(|| &*wrapper)
+}
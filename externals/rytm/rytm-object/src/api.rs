@@ -37,6 +37,76 @@ pub mod object_type {
     ];
 }
 
+/*** Bulk Object Types ***/
+
+pub mod bulk_object_type {
+
+    pub const ALL: &str = "all";
+    pub const PATTERNS: &str = "patterns";
+    pub const KITS: &str = "kits";
+    pub const SOUNDS: &str = "sounds";
+    pub const GLOBALS: &str = "globals";
+
+    pub const BULK_OBJECT_TYPES: &[&str] = &[ALL, PATTERNS, KITS, SOUNDS, GLOBALS];
+
+    /// Only for sending, covers the objects which are changed since they were last sent or received.
+    pub const CHANGED: &str = "changed";
+    /// Only for getting, lists the objects which are changed since they were last sent or received.
+    pub const DIRTY: &str = "dirty";
+}
+
+/*** Prefixes ***/
+
+pub mod device_prefix {
+    /// Addresses a command to a device by its sysex id, e.g. `get @device 1 pattern 0 kitnumber`.
+    pub const DEVICE: &str = "@device";
+}
+
+pub mod batch {
+    /// Runs many `get` and `set` commands in one message.
+    pub const BATCH: &str = "batch";
+    /// Separates the commands of a batch, e.g. `batch set pattern 0 0 0 enable 1 , set pattern 0 0 1 enable 1`.
+    pub const DELIMITER: &str = ",";
+}
+
+pub mod address {
+    /// Addresses every index in its place, e.g. `set pattern 3 * 0 vel 100`.
+    pub const WILDCARD: &str = "*";
+    /// Starts a list of indexes, e.g. `set pattern 3 0 [0 4 8 12] enable 1`.
+    pub const LIST_START: char = '[';
+    /// Ends a list of indexes.
+    pub const LIST_END: char = ']';
+    /// The most commands one command may expand to, as many as the trigs of a track in every pattern.
+    pub const MAX_EXPANDED_COMMANDS: usize = 128 * 64;
+}
+
+pub mod dump {
+    /// Ends a `get` which reads every identifier and enum of a level of an object, e.g. `get pattern 3 0 dump`.
+    pub const DUMP: &str = "dump";
+}
+
+pub mod enum_query {
+    /// Follows the colon of an enum type in a `get` to list its variants instead of reading it, e.g. `get pattern 0 speed:?`.
+    pub const VARIANTS: &str = "?";
+}
+
+/*** Relative Operations ***/
+
+pub mod relative_operation {
+    /// Adds to the current value, e.g. `set sound_wb 0 filtcutoff inc 5`.
+    pub const INCREMENT: &str = "inc";
+    /// Subtracts from the current value.
+    pub const DECREMENT: &str = "dec";
+    /// Multiplies the current value, e.g. `set sound_wb 0 filtcutoff scale 0.5`.
+    pub const SCALE: &str = "scale";
+    /// Mirrors the current value in the range of the parameter.
+    pub const INVERT: &str = "invert";
+    /// Picks a random value in the range of the parameter or between the given bounds.
+    pub const RANDOMIZE: &str = "randomize";
+
+    pub const RELATIVE_OPERATIONS: &[&str] = &[INCREMENT, DECREMENT, SCALE, INVERT, RANDOMIZE];
+}
+
 /*** Object Element Types ***/
 
 pub mod kit_element_type {
@@ -612,29 +682,117 @@ pub mod global_enum_type {
 #[derive(Debug)]
 pub enum Response {
     Common {
-        index: usize,
+        /// `None` for objects which are not addressed with an index, e.g. the settings or the work buffer.
+        index: Option<usize>,
         key: RytmValue,
         value: RytmValue,
     },
     KitElement {
-        kit_index: usize,
+        /// `None` for the work buffer.
+        kit_index: Option<usize>,
         element_index: usize,
         element_type: RytmValue,
         value: RytmValue,
     },
     Track {
-        pattern_index: usize,
+        /// `None` for the work buffer.
+        pattern_index: Option<usize>,
         track_index: usize,
         key: RytmValue,
         value: RytmValue,
     },
     Trig {
-        pattern_index: usize,
+        /// `None` for the work buffer.
+        pattern_index: Option<usize>,
         track_index: usize,
         trig_index: usize,
         key: RytmValue,
         value: RytmValue,
     },
+    /// The variants of an enum type, in response to `<enum-type>:?`.
+    Variants {
+        enum_type: RytmValue,
+        variants: Vec<RytmValue>,
+    },
     Unsupported(String),
     Ok,
+    /// One response for every index a command with ranges, wildcards or lists addresses, in order.
+    Many(Vec<Response>),
+}
+
+impl Response {
+    /// The value a getter responded with, `None` for responses which do not carry a value.
+    pub fn into_value(self) -> Option<RytmValue> {
+        match self {
+            Self::Common { value, .. }
+            | Self::KitElement { value, .. }
+            | Self::Track { value, .. }
+            | Self::Trig { value, .. } => Some(value),
+            Self::Variants { .. } | Self::Unsupported(_) | Self::Ok | Self::Many(_) => None,
+        }
+    }
+
+    /// Checks if the command or any of the addresses it expanded to is unsupported.
+    pub fn is_unsupported(&self) -> bool {
+        match self {
+            Self::Unsupported(_) => true,
+            Self::Many(responses) => responses.iter().any(Self::is_unsupported),
+            _ => false,
+        }
+    }
+
+    /// Shifts the indexes of the response by the index base so they read the way the command was written.
+    ///
+    /// Objects which are not addressed with an index keep having none.
+    pub fn rebased(self, base: usize) -> Self {
+        match self {
+            Self::Common { index, key, value } => Self::Common {
+                index: index.map(|index| index + base),
+                key,
+                value,
+            },
+            Self::KitElement {
+                kit_index,
+                element_index,
+                element_type,
+                value,
+            } => Self::KitElement {
+                kit_index: kit_index.map(|index| index + base),
+                element_index: element_index + base,
+                element_type,
+                value,
+            },
+            Self::Track {
+                pattern_index,
+                track_index,
+                key,
+                value,
+            } => Self::Track {
+                pattern_index: pattern_index.map(|index| index + base),
+                track_index: track_index + base,
+                key,
+                value,
+            },
+            Self::Trig {
+                pattern_index,
+                track_index,
+                trig_index,
+                key,
+                value,
+            } => Self::Trig {
+                pattern_index: pattern_index.map(|index| index + base),
+                track_index: track_index + base,
+                trig_index: trig_index + base,
+                key,
+                value,
+            },
+            Self::Many(responses) => Self::Many(
+                responses
+                    .into_iter()
+                    .map(|response| response.rebased(base))
+                    .collect(),
+            ),
+            Self::Variants { .. } | Self::Unsupported(_) | Self::Ok => self,
+        }
+    }
 }
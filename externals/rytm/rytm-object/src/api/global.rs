@@ -7,36 +7,33 @@ use crate::{
     parse::types::{Number, ParsedValue},
     types::CommandType,
     value::RytmValue,
-    RytmObject,
 };
 use error_logger_macro::log_errors;
-use rytm_rs::object::Global;
+use rytm_rs::{object::Global, RytmProject};
 use tracing::{error, instrument};
 
-#[instrument(skip(rytm))]
+#[instrument(skip(project))]
 pub fn handle(
-    rytm: &RytmObject,
+    project: &mut RytmProject,
     tokens: Vec<ParsedValue>,
     index: Option<usize>,
     command_type: CommandType,
 ) -> Result<Response, RytmObjectError> {
-    let mut guard = rytm.project.lock();
-
     let mut tokens = tokens[1..].iter();
     let next_token = tokens.next();
 
     match command_type {
         CommandType::Get => {
             let object =
-                index.map_or_else(|| guard.work_buffer().global(), |i| &guard.globals()[i]);
+                index.map_or_else(|| project.work_buffer().global(), |i| &project.globals()[i]);
             match next_token {
                 Some(ParsedValue::Enum(variant, value)) => Ok(Response::Common {
-                    index: object.index(),
+                    index,
                     key: variant.into(),
                     value: get_enum(object, variant, value)?,
                 }),
                 Some(ParsedValue::Identifier(action)) => Ok(Response::Common {
-                    index: object.index(),
+                    index,
                     key: action.into(),
                     value: get_action(object, &mut tokens, action)?,
                 }),
@@ -47,9 +44,9 @@ pub fn handle(
         }
         CommandType::Set => {
             let object = if let Some(i) = index {
-                &mut guard.globals_mut()[i]
+                &mut project.globals_mut()[i]
             } else {
-                guard.work_buffer_mut().global_mut()
+                project.work_buffer_mut().global_mut()
             };
 
             match next_token {
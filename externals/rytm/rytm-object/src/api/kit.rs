@@ -5,37 +5,35 @@ use crate::{
     parse::types::{Number, ParsedValue},
     types::CommandType,
     value::RytmValue,
-    RytmObject,
 };
 use error_logger_macro::log_errors;
-use rytm_rs::object::Kit;
+use rytm_rs::{object::Kit, RytmProject};
 use std::convert::TryInto;
 use tracing::{error, instrument};
 
-#[instrument(skip(rytm))]
+#[instrument(skip(project))]
 pub fn handle(
-    rytm: &RytmObject,
+    project: &mut RytmProject,
     tokens: Vec<ParsedValue>,
     index: Option<usize>,
     command_type: CommandType,
 ) -> Result<Response, RytmObjectError> {
-    let mut guard = rytm.project.lock();
     let mut tokens = tokens[1..].iter();
 
     let next_token = tokens.next();
 
     match command_type {
         CommandType::Get => {
-            let object = index.map_or_else(|| guard.work_buffer().kit(), |i| &guard.kits()[i]);
+            let object = index.map_or_else(|| project.work_buffer().kit(), |i| &project.kits()[i]);
 
             match next_token {
                 Some(ParsedValue::Enum(variant, value)) => Ok(Response::Common {
-                    index: object.index(),
+                    index,
                     key: variant.into(),
                     value: get_enum(object, variant, value)?,
                 }),
                 Some(ParsedValue::Identifier(action)) => Ok(Response::Common {
-                    index: object.index(),
+                    index,
                     key: action.into(),
                     value: get_action(object, &mut tokens, action)?,
                 }),
@@ -43,7 +41,6 @@ pub fn handle(
                     if element == SOUND {
                         if let Some(ParsedValue::SoundIndex(i)) = tokens.next() {
                             let res = sound::handle(
-                                rytm,
                                 tokens.cloned().collect::<Vec<ParsedValue>>(),
                                 *i,
                                 SoundSource::Kit(object),
@@ -51,7 +48,7 @@ pub fn handle(
                             )?;
 
                             let Response::Common {
-                                index: sound_index,
+                                index: Some(sound_index),
                                 key,
                                 value,
                             } = res
@@ -60,7 +57,7 @@ pub fn handle(
                             };
 
                             return Ok(Response::KitElement {
-                                kit_index: object.index(),
+                                kit_index: index,
                                 element_index: sound_index,
                                 element_type: key,
                                 value,
@@ -75,7 +72,7 @@ pub fn handle(
 
                     if let Some(ParsedValue::ElementIndex(element_index)) = tokens.next() {
                         Ok(Response::KitElement {
-                            kit_index: object.index(),
+                            kit_index: index,
                             element_index: *element_index,
                             element_type: element.into(),
                             value: get_kit_element_value(object, element, *element_index)?,
@@ -94,9 +91,9 @@ pub fn handle(
         }
         CommandType::Set => {
             let object = if let Some(i) = index {
-                &mut guard.kits_mut()[i]
+                &mut project.kits_mut()[i]
             } else {
-                guard.work_buffer_mut().kit_mut()
+                project.work_buffer_mut().kit_mut()
             };
 
             match next_token {
@@ -108,7 +105,6 @@ pub fn handle(
                     if element == SOUND {
                         if let Some(ParsedValue::SoundIndex(i)) = tokens.next() {
                             let res = sound::handle(
-                                rytm,
                                 tokens.cloned().collect::<Vec<ParsedValue>>(),
                                 *i,
                                 SoundSource::KitMut(object),
@@ -145,20 +141,20 @@ pub fn handle(
                         if i == target_index {
                             return Ok(Response::Ok);
                         }
-                        let source = guard.kits()[i].clone();
-                        let target = &mut guard.kits_mut()[target_index];
+                        let source = project.kits()[i].clone();
+                        let target = &mut project.kits_mut()[target_index];
                         target.copy_data_from(&source);
                     } else {
-                        let source = guard.work_buffer().kit().clone();
-                        let target = &mut guard.kits_mut()[target_index];
+                        let source = project.work_buffer().kit().clone();
+                        let target = &mut project.kits_mut()[target_index];
                         target.copy_data_from(&source);
                     }
                 }
                 None => {
                     // Copy to work buffer
                     if let Some(i) = index {
-                        let source = guard.kits()[i].clone();
-                        let target = guard.work_buffer_mut().kit_mut();
+                        let source = project.kits()[i].clone();
+                        let target = project.work_buffer_mut().kit_mut();
                         target.copy_data_from(&source);
                     } else {
                         // We're not going to copy from work buffer to work buffer.
@@ -176,6 +172,23 @@ pub fn handle(
     }
 }
 
+/// Copies a kit from one project to another, e.g. between the twins of two devices.
+///
+/// `None` as an index stands for the work buffer.
+pub fn copy_between_projects(
+    source: &RytmProject,
+    index: Option<usize>,
+    target: &mut RytmProject,
+    target_index: Option<usize>,
+) {
+    let source = index.map_or_else(|| source.work_buffer().kit(), |i| &source.kits()[i]);
+    let target = match target_index {
+        Some(i) => &mut target.kits_mut()[i],
+        None => target.work_buffer_mut().kit_mut(),
+    };
+    target.copy_data_from(source);
+}
+
 #[instrument(skip(object))]
 #[log_errors]
 fn get_kit_element_value(
@@ -6,27 +6,27 @@ use crate::{
     parse::types::ParsedValue,
     types::CommandType,
     value::RytmValue,
-    RytmObject,
 };
 use error_logger_macro::log_errors;
-use rytm_rs::object::{
-    pattern::{
-        track::{trig::HoldsTrigFlags, Track},
-        Trig,
+use rytm_rs::{
+    object::{
+        pattern::{
+            track::{trig::HoldsTrigFlags, Track},
+            Trig,
+        },
+        Pattern,
     },
-    Pattern,
+    RytmProject,
 };
 use tracing::{error, instrument};
 
-#[instrument(skip(rytm))]
+#[instrument(skip(project))]
 pub fn handle(
-    rytm: &RytmObject,
+    project: &mut RytmProject,
     tokens: Vec<ParsedValue>,
     index: Option<usize>,
     command_type: CommandType,
 ) -> Result<Response, RytmObjectError> {
-    let mut guard = rytm.project.lock();
-
     let mut tokens = tokens[1..].iter();
 
     if let CommandType::Copy = command_type {
@@ -37,20 +37,20 @@ pub fn handle(
                     if i == target_index {
                         return Ok(Response::Ok);
                     }
-                    let source = guard.patterns()[i].clone();
-                    let target = &mut guard.patterns_mut()[target_index];
+                    let source = project.patterns()[i].clone();
+                    let target = &mut project.patterns_mut()[target_index];
                     target.copy_data_from(&source);
                 } else {
-                    let source = guard.work_buffer().pattern().clone();
-                    let target = &mut guard.patterns_mut()[target_index];
+                    let source = project.work_buffer().pattern().clone();
+                    let target = &mut project.patterns_mut()[target_index];
                     target.copy_data_from(&source);
                 }
             }
             None => {
                 // Copy to work buffer
                 if let Some(i) = index {
-                    let source = guard.patterns()[i].clone();
-                    let target = guard.work_buffer_mut().pattern_mut();
+                    let source = project.patterns()[i].clone();
+                    let target = project.work_buffer_mut().pattern_mut();
                     target.copy_data_from(&source);
                 } else {
                     // We're not going to copy from work buffer to work buffer.
@@ -72,10 +72,10 @@ pub fn handle(
                 Some(ParsedValue::PlockOperation(op)) => {
                     // Treat as plock
                     let object = if let Some(i) = index {
-                        &mut guard.patterns_mut()[i].tracks_mut()[*track_index].trigs_mut()
+                        &mut project.patterns_mut()[i].tracks_mut()[*track_index].trigs_mut()
                             [*trig_index]
                     } else {
-                        &mut guard.work_buffer_mut().pattern_mut().tracks_mut()[*track_index]
+                        &mut project.work_buffer_mut().pattern_mut().tracks_mut()[*track_index]
                             .trigs_mut()[*trig_index]
                     };
 
@@ -89,23 +89,24 @@ pub fn handle(
                         CommandType::Get => {
                             let object = index.map_or_else(
                                 || {
-                                    &guard.work_buffer().pattern().tracks()[*track_index].trigs()
+                                    &project.work_buffer().pattern().tracks()[*track_index].trigs()
                                         [*trig_index]
                                 },
                                 |i| {
-                                    &guard.patterns()[i].tracks()[*track_index].trigs()[*trig_index]
+                                    &project.patterns()[i].tracks()[*track_index].trigs()
+                                        [*trig_index]
                                 },
                             );
                             match ident_or_enum {
                                 ParsedValue::Enum(variant, _) => Ok(Response::Trig {
-                                    pattern_index: index.unwrap_or(0),
+                                    pattern_index: index,
                                     track_index: *track_index,
                                     trig_index: object.index(),
                                     key: variant.into(),
                                     value: trig_get_enum(object, variant)?,
                                 }),
                                 ParsedValue::Identifier(action) => Ok(Response::Trig {
-                                    pattern_index: index.unwrap_or(0),
+                                    pattern_index: index,
                                     track_index: *track_index,
                                     trig_index: object.index(),
                                     key: action.into(),
@@ -120,10 +121,10 @@ pub fn handle(
                         }
                         CommandType::Set => {
                             let object = if let Some(i) = index {
-                                &mut guard.patterns_mut()[i].tracks_mut()[*track_index].trigs_mut()
-                                    [*trig_index]
+                                &mut project.patterns_mut()[i].tracks_mut()[*track_index]
+                                    .trigs_mut()[*trig_index]
                             } else {
-                                &mut guard.work_buffer_mut().pattern_mut().tracks_mut()
+                                &mut project.work_buffer_mut().pattern_mut().tracks_mut()
                                     [*track_index]
                                     .trigs_mut()[*trig_index]
                             };
@@ -156,18 +157,18 @@ pub fn handle(
                     CommandType::Copy => unreachable!("Copy command should be handled before."),
                     CommandType::Get => {
                         let object = index.map_or_else(
-                            || &guard.work_buffer().pattern().tracks()[*track_index],
-                            |i| &guard.patterns()[i].tracks()[*track_index],
+                            || &project.work_buffer().pattern().tracks()[*track_index],
+                            |i| &project.patterns()[i].tracks()[*track_index],
                         );
                         match ident_or_enum {
                             ParsedValue::Enum(variant, _) => Ok(Response::Track {
-                                pattern_index: index.unwrap_or(0),
+                                pattern_index: index,
                                 track_index: object.index(),
                                 key: variant.into(),
                                 value: track_get_enum(object, variant)?,
                             }),
                             ParsedValue::Identifier(action) => Ok(Response::Track {
-                                pattern_index: index.unwrap_or(0),
+                                pattern_index: index,
                                 track_index: object.index(),
                                 key: action.into(),
                                 value: track_get_action(object, action)?,
@@ -181,9 +182,9 @@ pub fn handle(
                     }
                     CommandType::Set => {
                         let object = if let Some(i) = index {
-                            &mut guard.patterns_mut()[i].tracks_mut()[*track_index]
+                            &mut project.patterns_mut()[i].tracks_mut()[*track_index]
                         } else {
-                            &mut guard.work_buffer_mut().pattern_mut().tracks_mut()[*track_index]
+                            &mut project.work_buffer_mut().pattern_mut().tracks_mut()[*track_index]
                         };
 
                         match ident_or_enum {
@@ -212,16 +213,18 @@ pub fn handle(
             match command_type {
                 CommandType::Copy => unreachable!("Copy command should be handled before."),
                 CommandType::Get => {
-                    let object = index
-                        .map_or_else(|| guard.work_buffer().pattern(), |i| &guard.patterns()[i]);
+                    let object = index.map_or_else(
+                        || project.work_buffer().pattern(),
+                        |i| &project.patterns()[i],
+                    );
                     match ident_or_enum {
                         ParsedValue::Enum(variant, _) => Ok(Response::Common {
-                            index: object.index(),
+                            index,
                             key: variant.into(),
                             value: pattern_get_enum(object, variant)?,
                         }),
                         ParsedValue::Identifier(action) => Ok(Response::Common {
-                            index: object.index(),
+                            index,
                             key: action.into(),
                             value: pattern_get_action(object, action)?,
                         }),
@@ -232,9 +235,9 @@ pub fn handle(
                 }
                 CommandType::Set => {
                     let object = if let Some(i) = index {
-                        &mut guard.patterns_mut()[i]
+                        &mut project.patterns_mut()[i]
                     } else {
-                        guard.work_buffer_mut().pattern_mut()
+                        project.work_buffer_mut().pattern_mut()
                     };
 
                     match ident_or_enum {
@@ -258,6 +261,23 @@ pub fn handle(
     }
 }
 
+/// Copies a pattern from one project to another, e.g. between the twins of two devices.
+///
+/// `None` as an index stands for the work buffer.
+pub fn copy_between_projects(
+    source: &RytmProject,
+    index: Option<usize>,
+    target: &mut RytmProject,
+    target_index: Option<usize>,
+) {
+    let source = index.map_or_else(|| source.work_buffer().pattern(), |i| &source.patterns()[i]);
+    let target = match target_index {
+        Some(i) => &mut target.patterns_mut()[i],
+        None => target.work_buffer_mut().pattern_mut(),
+    };
+    target.copy_data_from(source);
+}
+
 #[instrument(skip(object))]
 #[log_errors]
 fn pattern_get_enum(object: &Pattern, variant: &str) -> Result<RytmValue, RytmObjectError> {
@@ -253,14 +253,14 @@ pub fn handle_plock_commands(
 
             if let Some(value) = maybe_value {
                 Ok(Response::Common {
-                    index: trig_index,
+                    index: Some(trig_index),
                     key,
                     value,
                 })
             } else {
                 // Send the value as "unset" for a plock which is not set.
                 Ok(Response::Common {
-                    index: trig_index,
+                    index: Some(trig_index),
                     key,
                     value: RytmValue::from("unset"),
                 })
@@ -7,33 +7,31 @@ use crate::{
     parse::types::{Number, ParsedValue},
     types::CommandType,
     value::RytmValue,
-    RytmObject,
 };
 use error_logger_macro::log_errors;
-use rytm_rs::object::Settings;
+use rytm_rs::{object::Settings, RytmProject};
 use tracing::{error, instrument};
 
-#[instrument(skip(rytm))]
+#[instrument(skip(project))]
 pub fn handle(
-    rytm: &RytmObject,
+    project: &mut RytmProject,
     tokens: Vec<ParsedValue>,
     command_type: CommandType,
 ) -> Result<Response, RytmObjectError> {
-    let mut guard = rytm.project.lock();
     let mut tokens = tokens[1..].iter();
     let next_token = tokens.next();
 
     match command_type {
         CommandType::Get => {
-            let object = guard.settings();
+            let object = project.settings();
             match next_token {
                 Some(ParsedValue::Enum(variant, _)) => Ok(Response::Common {
-                    index: 0,
+                    index: None,
                     key: variant.into(),
                     value: get_enum(object, variant)?,
                 }),
                 Some(ParsedValue::Identifier(action)) => Ok(Response::Common {
-                    index: 0,
+                    index: None,
                     key: action.into(),
                     value: get_action(object, &mut tokens, action)?,
                 }),
@@ -43,7 +41,7 @@ pub fn handle(
             }
         }
         CommandType::Set => {
-            let object = guard.settings_mut();
+            let object = project.settings_mut();
             match next_token {
                 Some(ParsedValue::Enum(variant, value)) => set_enum(object, variant, value),
                 Some(ParsedValue::Identifier(action)) => set_action(object, &mut tokens, action),
@@ -4,16 +4,18 @@ use crate::{
     parse::types::{Number, ParsedValue},
     types::CommandType,
     value::RytmValue,
-    RytmObject,
 };
 use error_logger_macro::log_errors;
-use rytm_rs::object::{Kit, Sound};
+use rytm_rs::{
+    object::{Kit, Sound},
+    RytmProject,
+};
 use tracing::{error, instrument};
 
 #[derive(Debug)]
 pub enum SoundSource<'a> {
-    Pool,
-    WorkBuffer,
+    Pool(&'a mut RytmProject),
+    WorkBuffer(&'a mut RytmProject),
     Kit(&'a Kit),
     KitMut(&'a mut Kit),
 }
@@ -21,17 +23,16 @@ pub enum SoundSource<'a> {
 impl std::fmt::Display for SoundSource<'_> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            SoundSource::Pool => write!(f, "pool"),
-            SoundSource::WorkBuffer => write!(f, "workbuffer"),
+            SoundSource::Pool(_) => write!(f, "pool"),
+            SoundSource::WorkBuffer(_) => write!(f, "workbuffer"),
             SoundSource::Kit(_) => write!(f, "kit"),
             SoundSource::KitMut(_) => write!(f, "kit"),
         }
     }
 }
 
-#[instrument(skip(rytm, source), fields(source = %source, tokens = ?tokens, index = %index, command_type = ?command_type))]
+#[instrument(skip(source), fields(source = %source, tokens = ?tokens, index = %index, command_type = ?command_type))]
 pub fn handle(
-    rytm: &RytmObject,
     tokens: Vec<ParsedValue>,
     index: usize,
     source: SoundSource,
@@ -44,33 +45,23 @@ pub fn handle(
 
     let next_token = tokens.next();
 
-    let mut guard = None;
-
     match command_type {
         CommandType::Get => {
             let object = match source {
-                SoundSource::Pool => {
-                    let g = rytm.project.lock();
-                    guard.replace(g);
-                    &guard.as_ref().unwrap().pool_sounds()[index]
-                }
-                SoundSource::WorkBuffer => {
-                    let g = rytm.project.lock();
-                    guard.replace(g);
-                    &guard.as_ref().unwrap().work_buffer().sounds()[index]
-                }
+                SoundSource::Pool(project) => &project.pool_sounds()[index],
+                SoundSource::WorkBuffer(project) => &project.work_buffer().sounds()[index],
                 SoundSource::Kit(kit) => &kit.sounds()[index],
                 _ => panic!("Do not use SoundSource::KitMut for get in the codebase."),
             };
 
             match next_token {
                 Some(ParsedValue::Enum(variant, value)) => Ok(Response::Common {
-                    index: object.index(),
+                    index: Some(object.index()),
                     key: variant.into(),
                     value: get_enum(object, variant, value)?,
                 }),
                 Some(ParsedValue::Identifier(action)) => Ok(Response::Common {
-                    index: object.index(),
+                    index: Some(object.index()),
                     key: action.into(),
                     value: get_action(object, &mut tokens, action)?,
                 }),
@@ -81,15 +72,9 @@ pub fn handle(
         }
         CommandType::Set => {
             let object = match source {
-                SoundSource::Pool => {
-                    let g = rytm.project.lock();
-                    guard.replace(g);
-                    &mut guard.as_mut().unwrap().pool_sounds_mut()[index]
-                }
-                SoundSource::WorkBuffer => {
-                    let g = rytm.project.lock();
-                    guard.replace(g);
-                    &mut guard.as_mut().unwrap().work_buffer_mut().sounds_mut()[index]
+                SoundSource::Pool(project) => &mut project.pool_sounds_mut()[index],
+                SoundSource::WorkBuffer(project) => {
+                    &mut project.work_buffer_mut().sounds_mut()[index]
                 }
                 SoundSource::KitMut(kit) => &mut kit.sounds_mut()[index],
                 _ => panic!("Do not use SoundSource::Kit for set in the codebase."),
@@ -0,0 +1,34 @@
+//! State of the link to the device.
+//!
+//! After it is powered on the device answers the first sysex message it receives with an irrelevant message.
+//! Before the first request leaves, `rytm` sends a harmless query and swallows its reply, or the short message the device sends instead.
+
+use crate::parse::types::{DeviceObject, ObjectTypeSelector};
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum ConnectionState {
+    /// Nothing is sent to the device yet, the warm-up query was never answered or the default device changed.
+    #[default]
+    Unknown,
+    /// The warm-up query is sent, its reply is swallowed when it arrives.
+    WarmingUp(DeviceObject),
+    /// Messages from the device are applied.
+    Ready,
+}
+
+impl ConnectionState {
+    /// The query which is sent to warm the connection up, settings are the smallest object to dump.
+    pub const fn warm_up_query(device_id: u8) -> DeviceObject {
+        DeviceObject::new(device_id, ObjectTypeSelector::Settings)
+    }
+}
+
+impl std::fmt::Display for ConnectionState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Unknown => write!(f, "unknown"),
+            Self::WarmingUp(_) => write!(f, "warmingup"),
+            Self::Ready => write!(f, "ready"),
+        }
+    }
+}
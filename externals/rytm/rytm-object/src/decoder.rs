@@ -0,0 +1,165 @@
+//! Decoding of incoming sysex dumps on a worker thread.
+//!
+//! Decoding a pattern dump takes long enough to be heard when it runs on a Max thread.
+//! The worker decodes dumps into a scratch project of its own and only locks the project of the device
+//! for the moment it takes to swap the decoded object in.
+
+use crate::{
+    error::RytmObjectError,
+    parse::types::{DeviceObject, ObjectTypeSelector},
+};
+use parking_lot::Mutex;
+use rytm_rs::RytmProject;
+use std::{
+    collections::VecDeque,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc, Arc,
+    },
+    thread::JoinHandle,
+};
+use tracing::error;
+
+/// A dump waiting to be decoded.
+struct DecodeJob {
+    message: Vec<u8>,
+    object: Option<DeviceObject>,
+    project: Arc<Mutex<RytmProject>>,
+}
+
+/// The outcome of decoding a dump, the object is `None` if the header of the dump could not be read.
+#[derive(Debug)]
+pub struct Decoded {
+    pub object: Option<DeviceObject>,
+    pub result: Result<(), RytmObjectError>,
+}
+
+/// Decodes dumps on a worker thread in the order they arrive.
+pub struct Decoder {
+    jobs: Option<mpsc::Sender<DecodeJob>>,
+    decoded: Arc<Mutex<VecDeque<Decoded>>>,
+    stopped: Arc<AtomicBool>,
+    worker: Option<JoinHandle<()>>,
+}
+
+impl Decoder {
+    /// Starts the worker thread.
+    ///
+    /// `notify` is called from the worker thread every time a dump is decoded, collect the results with [`Decoder::take_decoded`].
+    pub fn spawn(notify: Box<dyn Fn() + Send>) -> Result<Self, RytmObjectError> {
+        let mut scratch = RytmProject::try_default()?;
+        let (jobs, receiver) = mpsc::channel::<DecodeJob>();
+        let decoded = Arc::new(Mutex::new(VecDeque::new()));
+        let stopped = Arc::new(AtomicBool::new(false));
+
+        let worker = {
+            let decoded = Arc::clone(&decoded);
+            let stopped = Arc::clone(&stopped);
+            std::thread::Builder::new()
+                .name("rytm-decoder".into())
+                .spawn(move || {
+                    for job in receiver {
+                        if stopped.load(Ordering::SeqCst) {
+                            break;
+                        }
+                        let result = decode(&mut scratch, &job);
+                        decoded.lock().push_back(Decoded {
+                            object: job.object,
+                            result,
+                        });
+                        notify();
+                    }
+                })
+                .map_err(|err| {
+                    format!("Decoder Error: Failed to start the decoder thread: {err}")
+                })?
+        };
+
+        Ok(Self {
+            jobs: Some(jobs),
+            decoded,
+            stopped,
+            worker: Some(worker),
+        })
+    }
+
+    /// Queues a dump to be decoded and applied to the given project.
+    pub fn decode(
+        &self,
+        message: Vec<u8>,
+        object: Option<DeviceObject>,
+        project: Arc<Mutex<RytmProject>>,
+    ) {
+        let job = DecodeJob {
+            message,
+            object,
+            project,
+        };
+        if let Some(Err(err)) = self.jobs.as_ref().map(|jobs| jobs.send(job)) {
+            error!("Decoder Error: The decoder thread is gone, a dump is dropped: {err}");
+        }
+    }
+
+    /// Takes the results of the dumps which are decoded since the last call, in order.
+    pub fn take_decoded(&self) -> Vec<Decoded> {
+        self.decoded.lock().drain(..).collect()
+    }
+}
+
+impl Drop for Decoder {
+    fn drop(&mut self) {
+        // Dumps which are not decoded yet are dropped.
+        self.stopped.store(true, Ordering::SeqCst);
+        self.jobs.take();
+        if let Some(worker) = self.worker.take() {
+            worker.join().ok();
+        }
+    }
+}
+
+fn decode(scratch: &mut RytmProject, job: &DecodeJob) -> Result<(), RytmObjectError> {
+    if let Some(object) = job.object {
+        scratch.set_device_id(object.device_id);
+    }
+    scratch.update_from_sysex_response(&job.message)?;
+
+    // A dump which decodes without a readable header does not belong to any object.
+    if let Some(object) = job.object {
+        swap_object(scratch, &mut job.project.lock(), object.object);
+    }
+    Ok(())
+}
+
+/// Swaps an object between two projects.
+fn swap_object(a: &mut RytmProject, b: &mut RytmProject, object: ObjectTypeSelector) {
+    use std::mem::swap;
+    match object {
+        ObjectTypeSelector::Pattern(index) => {
+            swap(&mut a.patterns_mut()[index], &mut b.patterns_mut()[index]);
+        }
+        ObjectTypeSelector::PatternWorkBuffer => swap(
+            a.work_buffer_mut().pattern_mut(),
+            b.work_buffer_mut().pattern_mut(),
+        ),
+        ObjectTypeSelector::Kit(index) => swap(&mut a.kits_mut()[index], &mut b.kits_mut()[index]),
+        ObjectTypeSelector::KitWorkBuffer => {
+            swap(a.work_buffer_mut().kit_mut(), b.work_buffer_mut().kit_mut());
+        }
+        ObjectTypeSelector::Sound(index) => swap(
+            &mut a.pool_sounds_mut()[index],
+            &mut b.pool_sounds_mut()[index],
+        ),
+        ObjectTypeSelector::SoundWorkBuffer(index) => swap(
+            &mut a.work_buffer_mut().sounds_mut()[index],
+            &mut b.work_buffer_mut().sounds_mut()[index],
+        ),
+        ObjectTypeSelector::Global(index) => {
+            swap(&mut a.globals_mut()[index], &mut b.globals_mut()[index]);
+        }
+        ObjectTypeSelector::GlobalWorkBuffer => swap(
+            a.work_buffer_mut().global_mut(),
+            b.work_buffer_mut().global_mut(),
+        ),
+        ObjectTypeSelector::Settings => swap(a.settings_mut(), b.settings_mut()),
+    }
+}
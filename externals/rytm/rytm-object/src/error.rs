@@ -17,6 +17,12 @@ pub enum ParseError {
     InvalidToken(String),
     #[error("Parse Error: {0}")]
     InvalidFormat(String),
+    #[error("Parse Error: Unexpected symbol '{symbol}'. Expected an identifier or enum.{}", did_you_mean(.suggestions))]
+    UnknownSymbol {
+        symbol: String,
+        /// The closest identifiers and enum types of the addressed level, closest first.
+        suggestions: Vec<String>,
+    },
     #[error("Parse Error: {0}")]
     ExpectedKitElementIndex(String),
     #[error("Parse Error: {0}")]
@@ -49,6 +55,27 @@ pub enum ParseError {
     InvalidQueryFormat,
 }
 
+impl ParseError {
+    /// The symbol which is not known and the closest matches for it, if the error has any.
+    pub fn suggestions(&self) -> Option<(&str, &[String])> {
+        match self {
+            Self::UnknownSymbol {
+                symbol,
+                suggestions,
+            } if !suggestions.is_empty() => Some((symbol, suggestions)),
+            _ => None,
+        }
+    }
+}
+
+fn did_you_mean(suggestions: &[String]) -> String {
+    match suggestions {
+        [] => String::new(),
+        [suggestion] => format!(" Did you mean {suggestion}?"),
+        suggestions => format!(" Did you mean one of {}?", suggestions.join(", ")),
+    }
+}
+
 #[derive(thiserror::Error, Debug)]
 #[non_exhaustive]
 pub enum QueryError {
@@ -315,6 +342,14 @@ impl From<String> for RytmObjectError {
 }
 
 impl RytmObjectError {
+    /// The symbol which is not known and the closest matches for it, if the error has any.
+    pub fn suggestions(&self) -> Option<(&str, &[String])> {
+        match self {
+            Self::Parse(err) => err.suggestions(),
+            _ => None,
+        }
+    }
+
     pub fn obj_post(&self, obj: *mut max_sys::t_object) {
         match self {
             Self::Custom(err) => median::object::error(obj, err.to_string()),
@@ -1,76 +1,506 @@
 use api::{
     global::{self},
-    kit, pattern, settings, sound, Response,
+    kit, pattern,
+    plock_type::{PLOCK_GET, PLOCK_SET},
+    settings, sound, Response,
 };
-use error::{QueryError, RytmObjectError, SendError};
+use connection::ConnectionState;
+use decoder::{Decoded, Decoder};
+use error::{EnumError, IdentifierError, ParseError, QueryError, RytmObjectError, SendError};
 use error_logger_macro::log_errors;
 use parking_lot::Mutex;
 use parse::{
-    parse_command,
-    types::{ObjectTypeSelector, ParsedValue},
+    expand_addresses, expand_dump, parse_batch, parse_bulk_selector, parse_command,
+    parse_device_prefix, parse_device_suffix, parse_dump, parse_relative_operation, rebase_indices,
+    types::{DeviceObject, ObjectTypeSelector, ParsedValue},
 };
+use progress::{Operation, Phase, ProgressEvent, ProgressTracker};
+use recording::{Direction, RecordedMessage, Recorder};
+use relative::RelativeOperation;
 use rytm_rs::{
     query::{GlobalQuery, KitQuery, PatternQuery, SettingsQuery, SoundQuery},
     RytmProject, SysexCompatible,
 };
-use std::sync::{
-    atomic::{AtomicBool, Ordering},
-    Arc,
+use scheduler::{Request, RequestScheduler, Scheduled};
+use schema::{ParameterRange, ParameterSchema, ParameterScope};
+use snapshot::{compare, parameter_paths, Mismatch, Parameter};
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    sync::{
+        atomic::{AtomicBool, AtomicU8, AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
 };
-use tracing::{error, instrument};
+use suggestion::suggestions;
+use sysex::{
+    device_inquiry, is_warm_up_reply, parse_dump_header, parse_identity_reply, DeviceIdentity,
+    SysexAssembler, SysexEvent,
+};
+use tracing::{error, info, instrument};
 use types::CommandType;
-use value::RytmValueList;
+use value::{RytmValue, RytmValueList};
 
 pub mod api;
+pub mod connection;
+pub mod decoder;
 pub mod error;
+pub mod macros;
 pub mod parse;
+pub mod progress;
+pub mod recording;
+pub mod relative;
+pub mod scheduler;
+pub mod schema;
+pub mod snapshot;
+pub mod suggestion;
+pub mod sysex;
 pub mod types;
 pub mod value;
+pub mod variants;
 
 pub struct RytmObject {
+    /// The twin of the default device, commands without an `@device` prefix are meant for it.
     pub project: Arc<Mutex<RytmProject>>,
-    pub sysex_in_buffer: Arc<Mutex<Vec<u8>>>,
-    pub buffering_sysex: AtomicBool,
+    /// Twins of the other devices by their sysex id, made when a device is first addressed with `@device` or identified.
+    pub devices: Mutex<BTreeMap<u8, Arc<Mutex<RytmProject>>>>,
+    default_device_id: AtomicU8,
+    /// Set while `identify scan` waits for the first Analog Rytm to answer.
+    pub identify_scan: AtomicBool,
+    pub sysex_input: Mutex<SysexAssembler>,
+    pub scheduler: Mutex<RequestScheduler>,
+    /// Objects which are changed by `set` or `copy` and not sent or received since, shared with the thread a project is loaded on.
+    pub dirty: Arc<Mutex<BTreeSet<DeviceObject>>>,
+    /// Records the sysex traffic while a recording is running.
+    pub recorder: Mutex<Option<Recorder>>,
+    /// Objects which are read back after they are sent, with the sysex they were sent with, shared with the thread a project is loaded on.
+    pub verifications: Arc<Mutex<HashMap<DeviceObject, Option<Vec<u8>>>>>,
+    /// Decodes incoming dumps on a worker thread while it is running, otherwise they are decoded where they arrive.
+    pub decoder: Mutex<Option<Decoder>>,
+    /// Sends the object of every successful `set` once the edits on it settle for the debounce window of the scheduler.
+    pub autosend: AtomicBool,
+    /// The index which is written for the first object, track, trig, sound or element, 0 or 1 to match the device display.
+    pub index_base: AtomicUsize,
+    pub connection: Mutex<ConnectionState>,
+    /// Progress of loads, saves and bulk transfers, shared with the threads they run on.
+    pub progress: Arc<Mutex<ProgressTracker>>,
 }
 
 impl RytmObject {
-    // Constants for MIDI SysEx messages
-    const SYSEX_START: u8 = 0xF0;
-    const SYSEX_END: u8 = 0xF7;
+    pub fn new(project: RytmProject) -> Self {
+        Self {
+            project: Arc::new(Mutex::new(project)),
+            devices: Mutex::new(BTreeMap::new()),
+            default_device_id: AtomicU8::new(0),
+            identify_scan: AtomicBool::new(false),
+            sysex_input: Mutex::new(SysexAssembler::default()),
+            scheduler: Mutex::new(RequestScheduler::default()),
+            dirty: Arc::new(Mutex::new(BTreeSet::new())),
+            recorder: Mutex::new(None),
+            verifications: Arc::new(Mutex::new(HashMap::new())),
+            decoder: Mutex::new(None),
+            autosend: AtomicBool::new(false),
+            index_base: AtomicUsize::new(0),
+            connection: Mutex::new(ConnectionState::default()),
+            progress: Arc::new(Mutex::new(ProgressTracker::default())),
+        }
+    }
 
-    // TODO: This is going to be called a lot is this fine to instrument?
-    #[instrument(skip(self))]
+    pub fn default_device_id(&self) -> u8 {
+        self.default_device_id.load(Ordering::SeqCst)
+    }
+
+    /// Makes another device the default one.
+    ///
+    /// If the device already has a twin, the twins of the two devices change places.
+    /// Otherwise the default twin is readdressed to the device.
+    /// The connection to the new default device is warmed up again before its first request.
+    pub fn set_default_device_id(&self, device_id: u8) {
+        let mut devices = self.devices.lock();
+        let previous = self.default_device_id.swap(device_id, Ordering::SeqCst);
+        if previous == device_id {
+            return;
+        }
+        *self.connection.lock() = ConnectionState::Unknown;
+
+        match devices.remove(&device_id) {
+            Some(twin) => {
+                std::mem::swap(&mut *self.project.lock(), &mut *twin.lock());
+                devices.insert(previous, twin);
+            }
+            None => self.project.lock().set_device_id(device_id),
+        }
+    }
+
+    pub fn index_base(&self) -> usize {
+        self.index_base.load(Ordering::SeqCst)
+    }
+
+    /// Reads the indexes of a query, send or save target written from the index base as 0-based ones.
+    pub fn rebase_indices(&self, values: RytmValueList) -> Result<RytmValueList, RytmObjectError> {
+        Ok(rebase_indices(values, self.index_base(), false)?)
+    }
+
+    /// The twin of a device, the twin is made with a default project if the device does not have one yet.
+    ///
+    /// Only call this for devices which are addressed with `@device` or identified, dumps of other devices are not accepted.
+    pub fn device_project(
+        &self,
+        device_id: u8,
+    ) -> Result<Arc<Mutex<RytmProject>>, RytmObjectError> {
+        if device_id == self.default_device_id() {
+            return Ok(Arc::clone(&self.project));
+        }
+
+        let mut devices = self.devices.lock();
+        if let Some(project) = devices.get(&device_id) {
+            return Ok(Arc::clone(project));
+        }
+
+        let mut project = RytmProject::try_default()?;
+        project.set_device_id(device_id);
+        let project = Arc::new(Mutex::new(project));
+        devices.insert(device_id, Arc::clone(&project));
+        Ok(project)
+    }
+
+    /// The twin of a device if it is the default device or already has one.
+    fn known_device_project(&self, device_id: u8) -> Option<Arc<Mutex<RytmProject>>> {
+        if device_id == self.default_device_id() {
+            return Some(Arc::clone(&self.project));
+        }
+        self.devices.lock().get(&device_id).map(Arc::clone)
+    }
+
+    /// The device a message addresses, the default one unless it starts with `@device`.
+    ///
+    /// A device which is addressed gets a twin so its responses are accepted.
+    fn addressed_device_id(&self, device_id: Option<u8>) -> Result<u8, RytmObjectError> {
+        match device_id {
+            Some(device_id) => self.device_project(device_id).map(|_| device_id),
+            None => Ok(self.default_device_id()),
+        }
+    }
+
+    /// Ids of the devices which have a twin, the default device first.
+    pub fn device_ids(&self) -> Vec<u8> {
+        let default_device_id = self.default_device_id();
+        std::iter::once(default_device_id)
+            .chain(
+                self.devices
+                    .lock()
+                    .keys()
+                    .copied()
+                    .filter(|device_id| *device_id != default_device_id),
+            )
+            .collect()
+    }
+
+    /// Buffers a byte of an incoming sysex message.
+    ///
+    /// Returns an event when the byte completes a message.
     #[log_errors]
-    pub fn handle_sysex_byte(&self, byte: u8) -> Result<(), RytmObjectError> {
-        if byte != Self::SYSEX_START && !self.buffering_sysex.load(Ordering::Acquire) {
+    pub fn handle_sysex_byte(&self, byte: u8) -> Result<Option<SysexEvent>, RytmObjectError> {
+        let mut sysex_input = self.sysex_input.lock();
+
+        if !sysex_input.accepts(byte) {
             return Err(RytmObjectError::from(
                 "Rytm Error: Invalid input. Rytm only understands SysEx messages. Please connect sysexin object to the Rytm inlet.",
             ));
         }
 
-        let mut sysex_buffer = self.sysex_in_buffer.lock();
+        Ok(sysex_input
+            .push(byte)
+            .and_then(|message| self.apply_sysex_message(&message)))
+    }
+
+    /// Buffers many bytes at once, e.g. a complete message or several concatenated messages.
+    ///
+    /// Returns an event for every message which is completed.
+    #[instrument(skip_all, fields(length = bytes.len()))]
+    #[log_errors]
+    pub fn handle_sysex_bytes(&self, bytes: &[u8]) -> Result<Vec<SysexEvent>, RytmObjectError> {
+        let mut sysex_input = self.sysex_input.lock();
 
-        if byte == Self::SYSEX_START {
-            self.buffering_sysex.store(true, Ordering::Release);
-            sysex_buffer.clear(); // Clear any previous incomplete message
+        if !bytes
+            .first()
+            .map_or(true, |&byte| sysex_input.accepts(byte))
+        {
+            return Err(RytmObjectError::from(
+                "Rytm Error: Invalid input. Rytm only understands SysEx messages. A list should start with 240 (0xF0) and contain one or more complete sysex messages.",
+            ));
         }
 
-        sysex_buffer.push(byte);
+        Ok(bytes
+            .iter()
+            .filter_map(|&byte| sysex_input.push(byte))
+            .filter_map(|message| self.apply_sysex_message(&message))
+            .collect())
+    }
 
-        // Process complete message
-        if byte == Self::SYSEX_END {
-            self.buffering_sysex.store(false, Ordering::Release);
+    #[instrument(skip_all, fields(length = message.len()))]
+    fn apply_sysex_message(&self, message: &[u8]) -> Option<SysexEvent> {
+        self.record(Direction::In, message);
 
-            // Process the complete message
-            let mut project = self.project.lock();
+        if let Some(identity) = parse_identity_reply(message) {
+            // Dumps of an identified device are accepted from now on.
+            if identity.is_analog_rytm() {
+                if let Err(error) = self.device_project(identity.device_id) {
+                    error!(
+                        "Failed to make a twin for device {}: {}",
+                        identity.device_id, error
+                    );
+                }
+            }
+            return Some(SysexEvent::Identified(identity));
+        }
 
-            project.update_from_sysex_response(&sysex_buffer)?;
-            sysex_buffer.clear();
+        if let Some(ready) = self.swallow_warm_up_reply(message) {
+            return Some(ready);
         }
 
+        // Messages without a readable header are handed to the default twin to find out what is wrong with them.
+        let object = parse_dump_header(message).map(|header| header.device_object());
+        let device_id = object.map_or_else(|| self.default_device_id(), |object| object.device_id);
+        let Some(project) = self.known_device_project(device_id) else {
+            return self.finish_decoding(
+                object,
+                Err(format!(
+                    "Sysex Error: Ignored a dump of device {device_id} which is not known. Address it with @device {device_id} or identify it first."
+                )
+                .into()),
+            );
+        };
+
+        if let Some(decoder) = self.decoder.lock().as_ref() {
+            decoder.decode(message.to_vec(), object, project);
+            return None;
+        }
+
+        let result = project
+            .lock()
+            .update_from_sysex_response(message)
+            .map_err(RytmObjectError::from);
+        self.finish_decoding(object, result)
+    }
+
+    /// Swallows the reply to the warm-up query, see [`is_warm_up_reply`].
+    ///
+    /// Returns `None` if the connection is not warming up or the message is not the reply.
+    fn swallow_warm_up_reply(&self, message: &[u8]) -> Option<SysexEvent> {
+        let mut connection = self.connection.lock();
+        let ConnectionState::WarmingUp(object) = *connection else {
+            return None;
+        };
+        if !is_warm_up_reply(message, object.device_id) {
+            return None;
+        }
+        *connection = ConnectionState::Ready;
+        self.scheduler.lock().resolve(Request::WarmUp(object));
+        info!("The connection to the device is warmed up.");
+        Some(SysexEvent::Ready)
+    }
+
+    /// Puts the warm-up query in front of the first request which is due while the connection is unknown.
+    fn warm_up_if_needed(&self) {
+        let mut connection = self.connection.lock();
+        if *connection != ConnectionState::Unknown {
+            return;
+        }
+
+        let mut scheduler = self.scheduler.lock();
+        if scheduler.time_until_next() != Some(Duration::ZERO) {
+            return;
+        }
+        // Device inquiries are answered by any device, the one to warm up may not even exist.
+        if matches!(scheduler.peek(), Some(Request::Identify(_))) {
+            return;
+        }
+        let object = ConnectionState::warm_up_query(self.default_device_id());
+        scheduler.enqueue_front(Request::WarmUp(object));
+        *connection = ConnectionState::WarmingUp(object);
+    }
+
+    /// Starts decoding incoming dumps on a worker thread, does nothing if it is already running.
+    ///
+    /// `notify` is called from the worker thread when a dump is decoded, then [`RytmObject::take_decoded_events`] should be called.
+    #[log_errors]
+    pub fn start_decoder(&self, notify: Box<dyn Fn() + Send>) -> Result<(), RytmObjectError> {
+        let mut decoder = self.decoder.lock();
+        if decoder.is_none() {
+            *decoder = Some(Decoder::spawn(notify)?);
+        }
         Ok(())
     }
 
+    /// Stops the worker thread, dumps which are not decoded yet are dropped.
+    pub fn stop_decoder(&self) {
+        self.decoder.lock().take();
+    }
+
+    /// Events of the dumps which are decoded on the worker thread since the last call, in the order the dumps arrived.
+    pub fn take_decoded_events(&self) -> Vec<SysexEvent> {
+        let decoded = self
+            .decoder
+            .lock()
+            .as_ref()
+            .map(Decoder::take_decoded)
+            .unwrap_or_default();
+
+        decoded
+            .into_iter()
+            .filter_map(|Decoded { object, result }| self.finish_decoding(object, result))
+            .collect()
+    }
+
+    fn finish_decoding(
+        &self,
+        object: Option<DeviceObject>,
+        result: Result<(), RytmObjectError>,
+    ) -> Option<SysexEvent> {
+        match (result, object) {
+            (Ok(()), Some(object)) => {
+                self.scheduler.lock().resolve(Request::Query(object));
+                self.dirty.lock().remove(&object);
+                Some(self.verify(object).unwrap_or(SysexEvent::Applied(object)))
+            }
+            (Ok(()), None) => None,
+            (Err(error), object) => {
+                error!("Rejected sysex message: {}", error);
+                Some(SysexEvent::Rejected { object, error })
+            }
+        }
+    }
+
+    /// Compares an object which is read back with the sysex it was sent with.
+    ///
+    /// Returns `None` if the object is not waiting to be verified.
+    fn verify(&self, object: DeviceObject) -> Option<SysexEvent> {
+        let sent = {
+            let mut verifications = self.verifications.lock();
+            // A response which arrives before the object is sent is not the read back.
+            let sent = verifications.get_mut(&object)?.take()?;
+            verifications.remove(&object);
+            sent
+        };
+
+        match self.sent_mismatches(object, &sent) {
+            Ok(mismatches) => Some(SysexEvent::Verified { object, mismatches }),
+            Err(err) => {
+                error!("Verify Error: Failed to compare {}: {}", object, err);
+                None
+            }
+        }
+    }
+
+    /// The parameters which differ between the sysex an object was sent with and the object on the twin of its device.
+    fn sent_mismatches(
+        &self,
+        object: DeviceObject,
+        sent: &[u8],
+    ) -> Result<Vec<Mismatch>, RytmObjectError> {
+        let project = self.device_project(object.device_id)?;
+        let mut project = project.lock();
+        if Self::project_object_sysex(&project, object.object)? == sent {
+            return Ok(Vec::new());
+        }
+
+        // Decoded only when the objects differ, to name the parameters which do.
+        let mut sent_project = RytmProject::try_default()?;
+        sent_project.set_device_id(object.device_id);
+        sent_project.update_from_sysex_response(sent)?;
+        Ok(compare(
+            &Self::snapshot(&mut sent_project, object.object),
+            &Self::snapshot(&mut project, object.object),
+        ))
+    }
+
+    /// Reads every parameter of an object of a project.
+    ///
+    /// Parameters which fail to read are left out, the failure is logged.
+    pub fn snapshot(project: &mut RytmProject, object: ObjectTypeSelector) -> Vec<Parameter> {
+        let (object_type, index) = object.object_type_and_index();
+        let mut prefix = vec![RytmValue::from(object_type)];
+        if let Some(index) = index {
+            prefix.push(RytmValue::Int(index as isize));
+        }
+
+        parameter_paths(object)
+            .into_iter()
+            .filter_map(|path| {
+                let mut values = prefix.clone();
+                values.extend(path.iter().cloned());
+                let tokens = parse_command(&RytmValueList::from(values), CommandType::Get)
+                    .map_err(RytmObjectError::from)
+                    .inspect_err(|err| error!("Snapshot Error: {}", err))
+                    .ok()?;
+                let value = Self::handle_command(project, object, tokens, CommandType::Get)
+                    .inspect_err(|err| error!("Snapshot Error: {}", err))
+                    .ok()?
+                    .into_value()?;
+                Some(Parameter { path, value })
+            })
+            .collect()
+    }
+
+    /// Starts recording the sysex traffic to a file, a running recording is stopped first.
+    #[instrument(skip(self))]
+    #[log_errors]
+    pub fn start_recording(&self, path: &str) -> Result<(), RytmObjectError> {
+        self.stop_recording()?;
+        let recorder = Recorder::create(path).map_err(|err| {
+            RytmObjectError::from(format!(
+                "Recording Error: Failed to create recording {path}: {err}"
+            ))
+        })?;
+        *self.recorder.lock() = Some(recorder);
+        Ok(())
+    }
+
+    /// Stops the running recording.
+    ///
+    /// Returns `false` if there was no recording running.
+    #[instrument(skip(self))]
+    #[log_errors]
+    pub fn stop_recording(&self) -> Result<bool, RytmObjectError> {
+        let Some(recorder) = self.recorder.lock().take() else {
+            return Ok(false);
+        };
+        recorder.finish().map_err(|err| {
+            RytmObjectError::from(format!(
+                "Recording Error: Failed to finish recording: {err}"
+            ))
+        })?;
+        Ok(true)
+    }
+
+    /// Writes a message to the running recording if there is one.
+    ///
+    /// If the recording can not be written anymore it is stopped.
+    pub fn record(&self, direction: Direction, message: &[u8]) {
+        let mut recorder = self.recorder.lock();
+        if let Some(Err(err)) = recorder
+            .as_mut()
+            .map(|recorder| recorder.record(direction, message))
+        {
+            error!("Recording Error: Failed to write recording, the recording is stopped: {err}");
+            *recorder = None;
+        }
+    }
+
+    /// Feeds the messages which were received from the device in a recording back in.
+    ///
+    /// Messages which were sent to the device are skipped.
+    #[instrument(skip_all, fields(length = messages.len()))]
+    pub fn replay(&self, messages: &[RecordedMessage]) -> Result<Vec<SysexEvent>, RytmObjectError> {
+        let bytes = messages
+            .iter()
+            .filter(|message| message.direction == Direction::In)
+            .flat_map(|message| message.bytes.iter().copied())
+            .collect::<Vec<u8>>();
+        self.handle_sysex_bytes(&bytes)
+    }
+
     #[instrument]
     #[log_errors]
     pub fn prepare_query(
@@ -82,9 +512,18 @@ impl RytmObject {
             (Some(object_type), other) => Ok((object_type, other)),
         }?;
 
+        Self::query_sysex(ObjectTypeSelector::try_from(pair)?, device_id)
+    }
+
+    #[instrument]
+    #[log_errors]
+    pub fn query_sysex(
+        selector: ObjectTypeSelector,
+        device_id: Option<u8>,
+    ) -> Result<Vec<u8>, RytmObjectError> {
         let device_id = device_id.unwrap_or(0x00);
 
-        Ok(match ObjectTypeSelector::try_from(pair)? {
+        Ok(match selector {
             ObjectTypeSelector::Pattern(index) => {
                 PatternQuery::new_with_device_id(index, device_id)
                     .unwrap()
@@ -120,14 +559,35 @@ impl RytmObject {
     #[instrument(skip(self))]
     #[log_errors]
     pub fn prepare_sysex(&self, selector: RytmValueList) -> Result<Vec<u8>, RytmObjectError> {
+        let (device_id, selector) = parse_device_prefix(selector)?;
+        let selector = self.rebase_indices(selector)?;
         let pair = match (selector.first(), selector.get(1)) {
             (None, Some(_) | None) => Err(SendError::InvalidFormat),
             (Some(object_type), other) => Ok((object_type, other)),
         }?;
 
-        let project = self.project.lock();
+        self.object_sysex(DeviceObject::new(
+            device_id.unwrap_or_else(|| self.default_device_id()),
+            ObjectTypeSelector::try_from(pair)?,
+        ))
+    }
+
+    #[instrument(skip(self))]
+    #[log_errors]
+    pub fn object_sysex(&self, object: DeviceObject) -> Result<Vec<u8>, RytmObjectError> {
+        Self::project_object_sysex(
+            &self.device_project(object.device_id)?.lock(),
+            object.object,
+        )
+    }
+
+    /// Serializes an object of any project, e.g. the one of a virtual device.
+    pub fn project_object_sysex(
+        project: &RytmProject,
+        selector: ObjectTypeSelector,
+    ) -> Result<Vec<u8>, RytmObjectError> {
         let work_buffer = project.work_buffer();
-        Ok(match ObjectTypeSelector::try_from(pair)? {
+        Ok(match selector {
             ObjectTypeSelector::Pattern(index) => project.patterns()[index].as_sysex(),
             ObjectTypeSelector::PatternWorkBuffer => work_buffer.pattern().as_sysex(),
             ObjectTypeSelector::Kit(index) => project.kits()[index].as_sysex(),
@@ -140,38 +600,1085 @@ impl RytmObject {
         }?)
     }
 
+    /// Queues a query to be sent to the device when the scheduler allows it.
+    ///
+    /// Bulk selectors like `all` or `patterns 0..16` queue every object they cover.
+    #[instrument(skip(self))]
+    #[log_errors]
+    pub fn schedule_query(&self, query: RytmValueList) -> Result<(), RytmObjectError> {
+        let (device_id, query) = parse_device_prefix(query)?;
+        let query = self.rebase_indices(query)?;
+        let device_id = self.addressed_device_id(device_id)?;
+
+        if let Some(selectors) = parse_bulk_selector(&query)? {
+            self.enqueue_bulk(
+                selectors
+                    .into_iter()
+                    .map(|selector| Request::Query(DeviceObject::new(device_id, selector))),
+            );
+            return Ok(());
+        }
+
+        let pair = match (query.first(), query.get(1)) {
+            (None, Some(_) | None) => Err(QueryError::InvalidFormat),
+            (Some(object_type), other) => Ok((object_type, other)),
+        }?;
+
+        self.scheduler
+            .lock()
+            .enqueue(Request::Query(DeviceObject::new(
+                device_id,
+                ObjectTypeSelector::try_from(pair)?,
+            )));
+        Ok(())
+    }
+
+    /// Queues an object to be sent to the device when the scheduler allows it.
+    ///
+    /// Bulk selectors like `all` or `patterns 0..16` queue every object they cover.
+    /// `changed` queues the changed objects of every device unless a device is given.
+    #[instrument(skip(self))]
+    #[log_errors]
+    pub fn schedule_send(&self, selector: RytmValueList) -> Result<(), RytmObjectError> {
+        let (device_id, selector) = parse_device_prefix(selector)?;
+        let selector = self.rebase_indices(selector)?;
+
+        if let [RytmValue::Symbol(changed)] = selector.as_slice() {
+            if changed == api::bulk_object_type::CHANGED {
+                let changed = self
+                    .dirty_objects()
+                    .into_iter()
+                    .filter(|object| device_id.map_or(true, |id| object.device_id == id))
+                    .collect::<Vec<_>>();
+                if !changed.is_empty() {
+                    self.enqueue_bulk(changed.into_iter().map(Request::Send));
+                }
+                return Ok(());
+            }
+        }
+
+        let device_id = self.addressed_device_id(device_id)?;
+        if let Some(selectors) = parse_bulk_selector(&selector)? {
+            self.enqueue_bulk(
+                selectors
+                    .into_iter()
+                    .map(|selector| Request::Send(DeviceObject::new(device_id, selector))),
+            );
+            return Ok(());
+        }
+
+        let pair = match (selector.first(), selector.get(1)) {
+            (None, Some(_) | None) => Err(SendError::InvalidFormat),
+            (Some(object_type), other) => Ok((object_type, other)),
+        }?;
+
+        self.scheduler
+            .lock()
+            .enqueue(Request::Send(DeviceObject::new(
+                device_id,
+                ObjectTypeSelector::try_from(pair)?,
+            )));
+        Ok(())
+    }
+
+    /// Queues an object to be sent to the device and queried back right after.
+    ///
+    /// When the object arrives back it is compared with what was sent, see [`SysexEvent::Verified`].
+    #[instrument(skip(self))]
+    #[log_errors]
+    pub fn schedule_verified_send(&self, selector: RytmValueList) -> Result<(), RytmObjectError> {
+        let (device_id, selector) = parse_device_prefix(selector)?;
+        let selector = self.rebase_indices(selector)?;
+        let pair = match (selector.first(), selector.get(1)) {
+            (None, Some(_) | None) => Err(SendError::InvalidFormat),
+            (Some(object_type), other) => Ok((object_type, other)),
+        }?;
+        let object = DeviceObject::new(
+            self.addressed_device_id(device_id)?,
+            ObjectTypeSelector::try_from(pair)?,
+        );
+
+        self.verifications.lock().insert(object, None);
+        let mut scheduler = self.scheduler.lock();
+        scheduler.enqueue(Request::Send(object));
+        scheduler.enqueue(Request::Query(object));
+        Ok(())
+    }
+
+    /// Queues device inquiries to be sent to the devices when the scheduler allows it.
+    ///
+    /// With `scan` the first Analog Rytm which answers becomes the default device, see [`RytmObject::finish_identify_scan`].
+    pub fn schedule_identify(&self, device_ids: impl IntoIterator<Item = u8>, scan: bool) {
+        let mut scheduler = self.scheduler.lock();
+        if scan {
+            // A scan which is already running starts over.
+            scheduler.cancel_inquiries();
+            self.identify_scan.store(true, Ordering::SeqCst);
+        }
+        for device_id in device_ids {
+            scheduler.enqueue(Request::Identify(device_id));
+        }
+    }
+
+    /// Ends a running scan with a device which answered it and makes the device the default one.
+    ///
+    /// The inquiries of the scan which are not sent yet are dropped.
+    /// Returns `false` if no scan is running or the device is not an Analog Rytm.
+    pub fn finish_identify_scan(&self, identity: &DeviceIdentity) -> bool {
+        if !identity.is_analog_rytm() || !self.identify_scan.swap(false, Ordering::SeqCst) {
+            return false;
+        }
+        self.scheduler.lock().cancel_inquiries();
+        self.set_default_device_id(identity.device_id);
+        true
+    }
+
+    /// Stops waiting for replies to device inquiries once the last one timed out.
+    ///
+    /// Returns `true` if a scan ended without any Analog Rytm answering it.
+    pub fn expire_inquiries(&self) -> bool {
+        self.scheduler.lock().expire_inquiries() && self.identify_scan.swap(false, Ordering::SeqCst)
+    }
+
+    /// Takes the next due request from the scheduler and renders it to sysex.
+    ///
+    /// Returns `None` if nothing is due yet, check [`RytmObject::time_until_next_event`] to know when to try again.
+    pub fn next_scheduled_request(&self) -> Option<(Scheduled, Result<Vec<u8>, RytmObjectError>)> {
+        self.warm_up_if_needed();
+        let scheduled = self.scheduler.lock().poll()?;
+        if let Some(progress) = scheduled.progress {
+            let mut tracker = self.progress.lock();
+            tracker.advance(
+                Operation::Transfer,
+                Phase::Transferring,
+                progress.done,
+                progress.total,
+            );
+            if progress.is_complete() {
+                tracker.finish(Operation::Transfer, Ok(()));
+            }
+        }
+        let sysex = match scheduled.request {
+            Request::Query(object) | Request::WarmUp(object) => {
+                Self::query_sysex(object.object, Some(object.device_id))
+            }
+            Request::Identify(device_id) => Ok(device_inquiry(device_id).to_vec()),
+            Request::Send(object) => self.object_sysex(object).inspect(|sysex| {
+                self.dirty.lock().remove(&object);
+                if let Some(sent) = self.verifications.lock().get_mut(&object) {
+                    *sent = Some(sysex.clone());
+                }
+            }),
+        };
+        Some((scheduled, sysex))
+    }
+
+    /// Queues requests as a bulk transfer and tracks its progress.
+    fn enqueue_bulk(&self, requests: impl IntoIterator<Item = Request>) {
+        let progress = self.scheduler.lock().enqueue_bulk(requests);
+        if progress.is_complete() {
+            return;
+        }
+        let mut tracker = self.progress.lock();
+        // A transfer never fails to start.
+        tracker.begin(Operation::Transfer).ok();
+        tracker.advance(
+            Operation::Transfer,
+            Phase::Transferring,
+            progress.done,
+            progress.total,
+        );
+    }
+
+    /// Cancels the running loads, saves and bulk transfers.
+    ///
+    /// The requests of a bulk transfer which did not leave yet are dropped right away,
+    /// loads and saves stop at their next step without changing anything.
+    /// Returns the cancelled operations.
+    pub fn cancel(&self) -> Vec<Operation> {
+        let mut tracker = self.progress.lock();
+        let cancelled = tracker.cancel();
+        if cancelled.contains(&Operation::Transfer) {
+            let dropped = self.scheduler.lock().cancel_bulk();
+            info!(
+                "Cancelled the bulk transfer, {} requests are dropped.",
+                dropped
+            );
+            tracker.finish(
+                Operation::Transfer,
+                Err("Cancel Error: The transfer is cancelled.".into()),
+            );
+        }
+        cancelled
+    }
+
+    /// Progress events of the running operations since the last call, in order.
+    pub fn take_progress_events(&self) -> Vec<ProgressEvent> {
+        self.progress.lock().take_events()
+    }
+
+    /// Queues the queries which were not answered in time again.
+    ///
+    /// Returns a [`SysexEvent::TimedOut`] for every object which did not get a response after all retries.
+    pub fn expire_queries(&self) -> Vec<SysexEvent> {
+        let expired = self.scheduler.lock().expire();
+        let mut verifications = self.verifications.lock();
+        for object in expired.iter().filter_map(Request::object) {
+            verifications.remove(&object);
+        }
+
+        // The device did not answer at all, the next request warms the connection up again.
+        let mut connection = self.connection.lock();
+        if let ConnectionState::WarmingUp(object) = *connection {
+            if expired.contains(&Request::WarmUp(object)) {
+                *connection = ConnectionState::Unknown;
+            }
+        }
+        expired
+            .iter()
+            .filter_map(Request::object)
+            .map(SysexEvent::TimedOut)
+            .collect()
+    }
+
+    /// Time left until the next request is due or a query times out.
+    pub fn time_until_next_event(&self) -> Option<Duration> {
+        let scheduler = self.scheduler.lock();
+        match (
+            scheduler.time_until_next(),
+            scheduler.time_until_next_timeout(),
+        ) {
+            (Some(request), Some(timeout)) => Some(request.min(timeout)),
+            (request, timeout) => request.or(timeout),
+        }
+    }
+
+    /// Replaces the twin of every device with a default project and forgets their changes and pending verifications.
+    ///
+    /// Devices keep their twins so their dumps are still accepted.
+    pub fn reset(&self) -> Result<(), RytmObjectError> {
+        let mut twins = Vec::new();
+        for device_id in self.device_ids() {
+            let mut project = RytmProject::try_default()?;
+            project.set_device_id(device_id);
+            twins.push((self.device_project(device_id)?, project));
+        }
+
+        // Nothing is replaced unless every default project could be made.
+        for (twin, project) in twins {
+            *twin.lock() = project;
+        }
+        self.dirty.lock().clear();
+        self.verifications.lock().clear();
+        Ok(())
+    }
+
+    /// Objects which differ from the device since they were last sent or received, in order.
+    pub fn dirty_objects(&self) -> Vec<DeviceObject> {
+        self.dirty.lock().iter().copied().collect()
+    }
+
+    /// Describes the parameters of an object type, or a single one of them, with their values in a default object.
+    ///
+    /// `describe <object-type> [<identifier>]`
+    #[instrument(skip(self))]
+    pub fn describe(&self, values: RytmValueList) -> Result<Vec<ParameterSchema>, RytmObjectError> {
+        let (object_type, identifier) =
+            match values.as_slice() {
+                [RytmValue::Symbol(object_type)] => (object_type, None),
+                [RytmValue::Symbol(object_type), RytmValue::Symbol(identifier)] => {
+                    (object_type, Some(identifier))
+                }
+                _ => return Err(
+                    "Describe Error: Invalid format. Use describe <object-type> [<identifier>]."
+                        .into(),
+                ),
+            };
+        let mut parameters = schema::parameters(object_type).ok_or_else(|| {
+            format!(
+                "Describe Error: Invalid object type {object_type}. Possible object types are {}.",
+                api::object_type::OBJECT_TYPES.join(", ")
+            )
+        })?;
+        if let Some(identifier) = identifier {
+            parameters.retain(|parameter| parameter.is_named(identifier));
+            if parameters.is_empty() {
+                return Err(format!(
+                    "Describe Error: {identifier} is not a parameter of {object_type}."
+                )
+                .into());
+            }
+        }
+
+        // Read from a project of its own so the defaults do not depend on what the twins hold.
+        let mut project = RytmProject::try_default()?;
+        for parameter in &mut parameters {
+            // Parameters without a getter are described without a default.
+            let Some(getter) = parameter.default_getter() else {
+                continue;
+            };
+            let default = parse_command(&RytmValueList::from(getter), CommandType::Get)
+                .map_err(RytmObjectError::from)
+                .and_then(|tokens| {
+                    let Some(ParsedValue::ObjectType(kind)) = tokens.first().cloned() else {
+                        unreachable!("Parser should have caught this.");
+                    };
+                    Self::handle_command(&mut project, kind, tokens, CommandType::Get)
+                })
+                .inspect_err(|err| {
+                    error!(
+                        "Describe Error: Failed to read the default of {}: {}",
+                        parameter.identifier, err
+                    )
+                })
+                .ok()
+                .and_then(Response::into_value);
+            if let Some(default) = default {
+                parameter.set_default(default);
+            }
+        }
+
+        Ok(parameters)
+    }
+
+    /// Runs a `get`, `set` or `copy` command on the twin of a device.
+    ///
+    /// Commands may start with `@device <device-id>` to address a device other than the default one.
+    /// A `copy` may also end with `@device <device-id>` to copy to another device.
+    /// A `get` or `set` which addresses many indexes with ranges, wildcards or lists responds with [`Response::Many`].
+    /// A `get` which ends with `dump` responds with [`Response::Many`] holding every identifier and enum of the level it addresses.
     #[instrument(skip(self))]
     pub fn command(
         &self,
         selector: CommandType,
         values: RytmValueList,
     ) -> Result<Response, RytmObjectError> {
-        let tokens = parse_command(&values, selector)?;
-        let Some(ParsedValue::ObjectType(kind)) = tokens.first().cloned() else {
-            unreachable!("Parser should have caught this.");
+        let command = self.prepare_command(selector, values)?;
+        let project = self.device_project(command.device_id)?;
+
+        if command.target_device_id != command.device_id {
+            // Only a copy has a target device and it always has a single address.
+            let address = &command.addresses[0];
+            let response = Self::copy_between_devices(
+                &project,
+                address.kind,
+                &self.device_project(command.target_device_id)?,
+                address.target_index,
+            )?;
+            if let Some(changed) = address.changed {
+                if !response.is_unsupported() {
+                    self.mark_changed(changed, selector);
+                }
+            }
+            return Ok(response);
+        }
+
+        let mut changes = Vec::new();
+        let response = Self::run_command(&mut project.lock(), command, &mut changes);
+        for (changed, selector) in changes {
+            self.mark_changed(changed, selector);
+        }
+
+        response
+    }
+
+    /// Runs several `get` and `set` commands separated by `,`, taking the lock of every twin they address once.
+    ///
+    /// Every command is parsed before any of them runs, the batch is rejected if one of them does not parse.
+    /// Returns the result of each command in order.
+    #[instrument(skip(self))]
+    pub fn batch(
+        &self,
+        values: RytmValueList,
+    ) -> Result<Vec<Result<Response, RytmObjectError>>, RytmObjectError> {
+        let commands = parse_batch(&values)?
+            .into_iter()
+            .enumerate()
+            .map(|(number, (selector, values))| {
+                self.prepare_command(selector, values).map_err(|err| {
+                    RytmObjectError::from(format!("Batch Error: Command {}. {err}", number + 1))
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let projects = commands
+            .iter()
+            .map(|command| command.device_id)
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .map(|device_id| Ok((device_id, self.device_project(device_id)?)))
+            .collect::<Result<BTreeMap<_, _>, RytmObjectError>>()?;
+        // Locked in the order of the device ids.
+        let mut guards = projects
+            .iter()
+            .map(|(device_id, project)| (*device_id, project.lock()))
+            .collect::<BTreeMap<_, _>>();
+
+        let mut changes = Vec::new();
+        let results = commands
+            .into_iter()
+            .map(|command| {
+                let project = guards
+                    .get_mut(&command.device_id)
+                    .expect("Every addressed device is locked.");
+                Self::run_command(project, command, &mut changes)
+            })
+            .collect::<Vec<_>>();
+        drop(guards);
+
+        for (changed, selector) in changes {
+            self.mark_changed(changed, selector);
+        }
+
+        Ok(results)
+    }
+
+    /// Takes the device prefix and suffix off a command and parses the rest of it.
+    fn prepare_command(
+        &self,
+        selector: CommandType,
+        values: RytmValueList,
+    ) -> Result<PreparedCommand, RytmObjectError> {
+        let (device_id, values) = parse_device_prefix(values)?;
+        let (values, target_device_id) = match selector {
+            CommandType::Copy => parse_device_suffix(values)?,
+            CommandType::Get | CommandType::Set => (values, None),
         };
-        match kind {
+        let device_id = device_id.unwrap_or_else(|| self.default_device_id());
+        let target_device_id = target_device_id.unwrap_or(device_id);
+        let index_base = self.index_base();
+        let values = rebase_indices(values, index_base, selector == CommandType::Copy)?;
+        let (values, operation) = match selector {
+            CommandType::Set => parse_relative_operation(values)?,
+            CommandType::Get | CommandType::Copy => (values, None),
+        };
+        let (values, dump) = match selector {
+            CommandType::Get => parse_dump(values),
+            CommandType::Set | CommandType::Copy => (values, false),
+        };
+
+        let mut commands = match selector {
+            CommandType::Copy => vec![values],
+            CommandType::Get | CommandType::Set => expand_addresses(values)?,
+        };
+        if dump {
+            commands = commands
+                .iter()
+                .map(expand_dump)
+                .collect::<Result<Vec<_>, _>>()?
+                .into_iter()
+                .flatten()
+                .collect();
+        }
+
+        let addresses = commands
+            .iter()
+            .map(|values| {
+                let relative = operation
+                    .map(|operation| Self::prepare_relative_change(values, operation))
+                    .transpose()?;
+                let tokens = if relative.is_some() {
+                    // The value is worked out when the command runs.
+                    let mut values = values.clone();
+                    values.push(RytmValue::Int(0));
+                    parse_command(&values, selector)?
+                } else {
+                    parse_command(values, selector)?
+                };
+                let Some(ParsedValue::ObjectType(kind)) = tokens.first().cloned() else {
+                    unreachable!("Parser should have caught this.");
+                };
+                let target_index = match tokens.get(1) {
+                    Some(ParsedValue::CopyTargetIndex(target_index)) => Some(*target_index),
+                    _ => None,
+                };
+                let changed = match selector {
+                    CommandType::Set => Some(DeviceObject::new(device_id, kind)),
+                    CommandType::Copy => kind
+                        .copy_target(target_index)
+                        .map(|target| DeviceObject::new(target_device_id, target))
+                        // Copying an object onto itself is a no-op.
+                        .filter(|target| *target != DeviceObject::new(device_id, kind)),
+                    CommandType::Get => None,
+                };
+
+                Ok(PreparedAddress {
+                    kind,
+                    target_index,
+                    tokens,
+                    changed,
+                    relative,
+                })
+            })
+            .collect::<Result<Vec<_>, RytmObjectError>>()?;
+
+        Ok(PreparedCommand {
+            selector,
+            device_id,
+            target_device_id,
+            addresses,
+            dump,
+            index_base,
+        })
+    }
+
+    /// Prepares the `get` which reads the parameter a relative `set` changes.
+    fn prepare_relative_change(
+        values: &RytmValueList,
+        operation: RelativeOperation,
+    ) -> Result<RelativeChange, RytmObjectError> {
+        let read = values
+            .iter()
+            .map(|value| match value {
+                RytmValue::Symbol(plock) if plock == PLOCK_SET => RytmValue::from(PLOCK_GET),
+                value => value.clone(),
+            })
+            .collect::<Vec<_>>();
+        let read = parse_command(&RytmValueList::from(read), CommandType::Get)?;
+        let (scope, identifier) = ParameterScope::of(&read).ok_or_else(|| {
+            RytmObjectError::from(format!(
+                "Set Error: {operation} should follow a numeric parameter."
+            ))
+        })?;
+
+        let range = scope.range(identifier).ok_or_else(|| {
+            RytmObjectError::from(format!(
+                "Set Error: {identifier} does not have a known range, it can not be changed with {operation}."
+            ))
+        })?;
+
+        Ok(RelativeChange {
+            operation,
+            range,
+            identifier: identifier.to_owned(),
+            read,
+        })
+    }
+
+    /// Runs every address of a command on the twin of its device, collecting the objects it changes.
+    ///
+    /// Stops at the first address which fails, the ones before it stay applied.
+    fn run_command(
+        project: &mut RytmProject,
+        command: PreparedCommand,
+        changes: &mut Vec<(DeviceObject, CommandType)>,
+    ) -> Result<Response, RytmObjectError> {
+        let mut responses = Vec::with_capacity(command.addresses.len());
+        for address in command.addresses {
+            let mut tokens = address.tokens;
+            if let Some(relative) = address.relative {
+                let current = if relative.operation.reads_current_value() {
+                    Self::handle_command(project, address.kind, relative.read, CommandType::Get)?
+                        .into_value()
+                } else {
+                    None
+                };
+                let value = relative.operation.apply(
+                    &relative.identifier,
+                    current.as_ref(),
+                    relative.range,
+                )?;
+                if let Some(placeholder) = tokens.last_mut() {
+                    *placeholder = ParsedValue::Parameter(value);
+                }
+            }
+
+            let response = Self::handle_command(project, address.kind, tokens, command.selector)?;
+            if let Some(changed) = address.changed {
+                if !response.is_unsupported() {
+                    changes.push((changed, command.selector));
+                }
+            }
+            responses.push(response);
+        }
+
+        let response = if responses.len() == 1 && !command.dump {
+            responses.remove(0)
+        } else {
+            Response::Many(responses)
+        };
+        Ok(response.rebased(command.index_base))
+    }
+
+    /// Marks the object a command changed dirty and schedules it to be sent if autosend is on.
+    fn mark_changed(&self, changed: DeviceObject, selector: CommandType) {
+        self.dirty.lock().insert(changed);
+        if selector == CommandType::Set && self.autosend.load(Ordering::SeqCst) {
+            self.scheduler
+                .lock()
+                .enqueue_debounced(Request::Send(changed));
+        }
+    }
+
+    fn handle_command(
+        project: &mut RytmProject,
+        kind: ObjectTypeSelector,
+        tokens: Vec<ParsedValue>,
+        selector: CommandType,
+    ) -> Result<Response, RytmObjectError> {
+        // Listing the variants of an enum does not read the object.
+        if let Some(ParsedValue::EnumVariants(enum_type)) = tokens.last() {
+            let variants = variants::enum_variants(enum_type).ok_or_else(|| {
+                format!("Variants Error: {enum_type}: has no fixed set of variants to list.")
+            })?;
+            return Ok(Response::Variants {
+                enum_type: RytmValue::from(format!("{enum_type}:")),
+                variants: variants
+                    .iter()
+                    .map(|&variant| RytmValue::from(variant))
+                    .collect(),
+            });
+        }
+
+        let scope = ParameterScope::of_level(&tokens);
+        // Suggestions keep the variant the enum type was written with.
+        let written_enums = tokens
+            .iter()
+            .filter_map(|token| match token {
+                ParsedValue::Enum(enum_type, variant) => Some(format!(
+                    "{enum_type}:{}",
+                    variant.as_deref().unwrap_or_default()
+                )),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        let response = match kind {
             ObjectTypeSelector::Pattern(index) => {
-                pattern::handle(self, tokens, Some(index), selector)
+                pattern::handle(project, tokens, Some(index), selector)
+            }
+            ObjectTypeSelector::PatternWorkBuffer => {
+                pattern::handle(project, tokens, None, selector)
             }
-            ObjectTypeSelector::PatternWorkBuffer => pattern::handle(self, tokens, None, selector),
-            ObjectTypeSelector::Kit(index) => kit::handle(self, tokens, Some(index), selector),
-            ObjectTypeSelector::KitWorkBuffer => kit::handle(self, tokens, None, selector),
+            ObjectTypeSelector::Kit(index) => kit::handle(project, tokens, Some(index), selector),
+            ObjectTypeSelector::KitWorkBuffer => kit::handle(project, tokens, None, selector),
             ObjectTypeSelector::Sound(index) => {
-                sound::handle(self, tokens, index, sound::SoundSource::Pool, selector)
+                sound::handle(tokens, index, sound::SoundSource::Pool(project), selector)
             }
             ObjectTypeSelector::SoundWorkBuffer(index) => sound::handle(
-                self,
                 tokens,
                 index,
-                sound::SoundSource::WorkBuffer,
+                sound::SoundSource::WorkBuffer(project),
                 selector,
             ),
             ObjectTypeSelector::Global(index) => {
-                global::handle(self, tokens, Some(index), selector)
+                global::handle(project, tokens, Some(index), selector)
+            }
+            ObjectTypeSelector::GlobalWorkBuffer => global::handle(project, tokens, None, selector),
+            ObjectTypeSelector::Settings => settings::handle(project, tokens, selector),
+        };
+
+        // Identifiers and enum types which the level does not handle are answered with the closest ones it has.
+        response.map_err(|err| match (err, scope) {
+            (RytmObjectError::Identifier(IdentifierError::InvalidType(symbol)), Some(scope)) => {
+                ParseError::UnknownSymbol {
+                    suggestions: suggestions(&symbol, scope),
+                    symbol,
+                }
+                .into()
+            }
+            (RytmObjectError::Enum(EnumError::InvalidEnumType(enum_type)), Some(scope)) => {
+                let prefix = format!("{enum_type}:");
+                let symbol = written_enums
+                    .into_iter()
+                    .find(|written| written.starts_with(&prefix))
+                    .unwrap_or(prefix);
+                ParseError::UnknownSymbol {
+                    suggestions: suggestions(&symbol, scope),
+                    symbol,
+                }
+                .into()
+            }
+            (err, _) => err,
+        })
+    }
+
+    /// Copies a pattern or a kit from the twin of one device to the twin of another.
+    ///
+    /// A missing target index copies to the work buffer of the target device.
+    fn copy_between_devices(
+        source: &Mutex<RytmProject>,
+        kind: ObjectTypeSelector,
+        target: &Mutex<RytmProject>,
+        target_index: Option<usize>,
+    ) -> Result<Response, RytmObjectError> {
+        let source = source.lock();
+        let mut target = target.lock();
+
+        match kind {
+            ObjectTypeSelector::Pattern(index) => {
+                pattern::copy_between_projects(&source, Some(index), &mut target, target_index);
+            }
+            ObjectTypeSelector::PatternWorkBuffer => {
+                pattern::copy_between_projects(&source, None, &mut target, target_index);
+            }
+            ObjectTypeSelector::Kit(index) => {
+                kit::copy_between_projects(&source, Some(index), &mut target, target_index);
+            }
+            ObjectTypeSelector::KitWorkBuffer => {
+                kit::copy_between_projects(&source, None, &mut target, target_index);
+            }
+            _ => {
+                return Ok(Response::Unsupported(
+                    "Copying between devices is only supported for patterns and kits.".into(),
+                ))
+            }
+        }
+
+        Ok(Response::Ok)
+    }
+}
+
+/// A command which is parsed and ready to run on the twin of its device.
+struct PreparedCommand {
+    selector: CommandType,
+    device_id: u8,
+    /// The device a `copy` copies to, the device of the command otherwise.
+    target_device_id: u8,
+    /// One for every index the ranges, wildcards and lists of the command stand for.
+    addresses: Vec<PreparedAddress>,
+    /// Set for a `get` which reads every identifier and enum of a level, it fails at the first one which can not be read like any other command.
+    dump: bool,
+    /// The indexes of the responses are shifted by it to match how the command was written.
+    index_base: usize,
+}
+
+/// A single object, track, trig or kit element which a command addresses.
+struct PreparedAddress {
+    kind: ObjectTypeSelector,
+    target_index: Option<usize>,
+    tokens: Vec<ParsedValue>,
+    /// The object the command changes if it succeeds.
+    changed: Option<DeviceObject>,
+    /// Set for a `set` which changes the parameter relative to its current value.
+    relative: Option<RelativeChange>,
+}
+
+/// A relative change of the parameter an address points to.
+struct RelativeChange {
+    operation: RelativeOperation,
+    /// The tokens of the `get` which reads the current value.
+    read: Vec<ParsedValue>,
+    range: ParameterRange,
+    identifier: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::recording::parse_recording;
+
+    #[test]
+    fn identity_fixture_replays() {
+        let rytm = RytmObject::new(RytmProject::try_default().unwrap());
+        let messages = parse_recording(include_str!("../fixtures/identify.txt")).unwrap();
+
+        let events = rytm.replay(&messages).unwrap();
+
+        let [SysexEvent::Identified(identity)] = events.as_slice() else {
+            panic!("Expected an identity reply but got {events:?}");
+        };
+        assert_eq!(identity.device_id, 0);
+        assert!(identity.is_supported());
+    }
+
+    #[test]
+    fn batch_runs_every_command_or_none() {
+        let rytm = RytmObject::new(RytmProject::try_default().unwrap());
+        let mut batch: Vec<RytmValue> = Vec::new();
+        for track in 0_isize..4 {
+            batch.extend([
+                "set".into(),
+                "kit_wb".into(),
+                "tracklevel".into(),
+                track.into(),
+                (track * 10).into(),
+                ",".into(),
+            ]);
+        }
+        batch.extend([
+            "set".into(),
+            "@device".into(),
+            1_isize.into(),
+            "kit_wb".into(),
+            "tracklevel".into(),
+            0_isize.into(),
+            90_isize.into(),
+            ",".into(),
+            "get".into(),
+            "kit_wb".into(),
+            "tracklevel".into(),
+            3_isize.into(),
+        ]);
+
+        let results = rytm.batch(RytmValueList::from(batch)).unwrap();
+        assert_eq!(results.len(), 6);
+        assert!(results.iter().all(Result::is_ok));
+        assert!(matches!(
+            results.last(),
+            Some(Ok(Response::KitElement {
+                element_index: 3,
+                value: RytmValue::Int(30),
+                ..
+            }))
+        ));
+        assert_eq!(
+            rytm.project
+                .lock()
+                .work_buffer()
+                .kit()
+                .track_level(2)
+                .unwrap(),
+            20
+        );
+        assert_eq!(
+            rytm.device_project(1)
+                .unwrap()
+                .lock()
+                .work_buffer()
+                .kit()
+                .track_level(0)
+                .unwrap(),
+            90
+        );
+        assert_eq!(
+            rytm.dirty_objects(),
+            vec![
+                DeviceObject::new(0, ObjectTypeSelector::KitWorkBuffer),
+                DeviceObject::new(1, ObjectTypeSelector::KitWorkBuffer),
+            ]
+        );
+
+        // A command which does not parse rejects the whole batch.
+        let rejected = rytm.batch(RytmValueList::from(vec![
+            "set".into(),
+            "kit_wb".into(),
+            "tracklevel".into(),
+            0_isize.into(),
+            127_isize.into(),
+            ",".into(),
+            "get".into(),
+            "pattern".into(),
+        ]));
+        assert!(rejected.is_err());
+        assert_eq!(
+            rytm.project
+                .lock()
+                .work_buffer()
+                .kit()
+                .track_level(0)
+                .unwrap(),
+            0
+        );
+    }
+
+    #[test]
+    fn ranges_and_lists_address_many_indexes() {
+        let rytm = RytmObject::new(RytmProject::try_default().unwrap());
+
+        rytm.command(
+            CommandType::Set,
+            RytmValueList::from(vec![
+                "kit_wb".into(),
+                "tracklevel".into(),
+                "[0".into(),
+                4_isize.into(),
+                "8]".into(),
+                100_isize.into(),
+            ]),
+        )
+        .unwrap();
+        rytm.command(
+            CommandType::Set,
+            RytmValueList::from(vec![
+                "kit_wb".into(),
+                "tracklevel".into(),
+                "10..=12".into(),
+                50_isize.into(),
+            ]),
+        )
+        .unwrap();
+
+        let Response::Many(responses) = rytm
+            .command(
+                CommandType::Get,
+                RytmValueList::from(vec!["kit_wb".into(), "tracklevel".into(), "*".into()]),
+            )
+            .unwrap()
+        else {
+            panic!("A wildcard should respond for every index.");
+        };
+        let levels = responses
+            .into_iter()
+            .map(|response| response.into_value().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(levels.len(), 13);
+        for track in [0, 4, 8] {
+            assert_eq!(levels[track], RytmValue::Int(100));
+        }
+        assert_eq!(levels[10..], vec![RytmValue::Int(50); 3]);
+        assert_eq!(
+            rytm.dirty_objects(),
+            vec![DeviceObject::new(0, ObjectTypeSelector::KitWorkBuffer)]
+        );
+    }
+
+    #[test]
+    fn indexes_follow_the_index_base() {
+        let rytm = RytmObject::new(RytmProject::try_default().unwrap());
+        rytm.index_base.store(1, Ordering::SeqCst);
+
+        rytm.command(
+            CommandType::Set,
+            RytmValueList::from(vec![
+                "pattern".into(),
+                "A02".into(),
+                "kitnumber".into(),
+                7_isize.into(),
+            ]),
+        )
+        .unwrap();
+        assert_eq!(rytm.project.lock().patterns()[1].kit_number(), 7);
+
+        let response = rytm
+            .command(
+                CommandType::Get,
+                RytmValueList::from(vec!["pattern".into(), 2_isize.into(), "kitnumber".into()]),
+            )
+            .unwrap();
+        assert!(matches!(
+            response,
+            Response::Common {
+                index: Some(2),
+                value: RytmValue::Int(7),
+                ..
+            }
+        ));
+        assert!(rytm
+            .command(
+                CommandType::Get,
+                RytmValueList::from(vec!["pattern".into(), 0_isize.into(), "kitnumber".into()]),
+            )
+            .is_err());
+
+        // Objects which are not addressed with an index do not get one.
+        let response = rytm
+            .command(
+                CommandType::Get,
+                RytmValueList::from(vec!["settings".into(), "projectbpm".into()]),
+            )
+            .unwrap();
+        assert!(matches!(response, Response::Common { index: None, .. }));
+        let response = rytm
+            .command(
+                CommandType::Get,
+                RytmValueList::from(vec![
+                    "pattern_wb".into(),
+                    3_isize.into(),
+                    5_isize.into(),
+                    "note".into(),
+                ]),
+            )
+            .unwrap();
+        assert!(matches!(
+            response,
+            Response::Trig {
+                pattern_index: None,
+                track_index: 3,
+                trig_index: 5,
+                ..
             }
-            ObjectTypeSelector::GlobalWorkBuffer => global::handle(self, tokens, None, selector),
-            ObjectTypeSelector::Settings => settings::handle(self, tokens, selector),
+        ));
+        let response = rytm
+            .command(
+                CommandType::Get,
+                RytmValueList::from(vec!["pattern_wb".into(), 3_isize.into(), "steps".into()]),
+            )
+            .unwrap();
+        assert!(matches!(
+            response,
+            Response::Track {
+                pattern_index: None,
+                track_index: 3,
+                ..
+            }
+        ));
+        for object in ["pattern_wb", "global_wb"] {
+            let response = rytm
+                .command(
+                    CommandType::Get,
+                    RytmValueList::from(vec![object.into(), "index".into()]),
+                )
+                .unwrap();
+            assert!(matches!(response, Response::Common { index: None, .. }));
+        }
+        let response = rytm
+            .command(
+                CommandType::Get,
+                RytmValueList::from(vec!["kit_wb".into(), "name".into()]),
+            )
+            .unwrap();
+        assert!(matches!(response, Response::Common { index: None, .. }));
+        let response = rytm
+            .command(
+                CommandType::Get,
+                RytmValueList::from(vec!["kit_wb".into(), "tracklevel".into(), 1_isize.into()]),
+            )
+            .unwrap();
+        assert!(matches!(
+            response,
+            Response::KitElement {
+                kit_index: None,
+                element_index: 1,
+                ..
+            }
+        ));
+        let response = rytm
+            .command(
+                CommandType::Get,
+                RytmValueList::from(vec![
+                    "kit".into(),
+                    4_isize.into(),
+                    "tracklevel".into(),
+                    1_isize.into(),
+                ]),
+            )
+            .unwrap();
+        assert!(matches!(
+            response,
+            Response::KitElement {
+                kit_index: Some(4),
+                element_index: 1,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn reset_replaces_every_twin_and_forgets_changes() {
+        let rytm = RytmObject::new(RytmProject::try_default().unwrap());
+        for device in [vec![], vec!["@device".into(), 1_isize.into()]] {
+            let mut command: Vec<RytmValue> = device;
+            command.extend([
+                "kit_wb".into(),
+                "tracklevel".into(),
+                0_isize.into(),
+                90_isize.into(),
+            ]);
+            rytm.command(CommandType::Set, RytmValueList::from(command))
+                .unwrap();
+        }
+        assert_eq!(rytm.dirty_objects().len(), 2);
+
+        rytm.reset().unwrap();
+
+        assert!(rytm.dirty_objects().is_empty());
+        assert_eq!(rytm.device_ids(), vec![0, 1]);
+        for device_id in rytm.device_ids() {
+            let project = rytm.device_project(device_id).unwrap();
+            assert_ne!(
+                project.lock().work_buffer().kit().track_level(0).unwrap(),
+                90
+            );
         }
     }
 }
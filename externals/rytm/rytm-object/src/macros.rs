@@ -0,0 +1,398 @@
+//! User defined macros, named sequences of commands which run one after the other with `run <name> [<argument> ...]`.
+//!
+//! Macros are plain text. The first line is [`MACROS_HEADER`] and every following line is a macro:
+//!
+//! `<name> <command> , <command> ...`
+//!
+//! Every command starts with its selector and `%1`, `%2` ... stand for the arguments of `run`, e.g.
+//!
+//! ```text
+//! # rytm macros v1
+//! stash copy pattern_wb %1 , send pattern %1 , query kit_wb
+//! ```
+//!
+//! Empty lines and lines starting with `#` are ignored.
+
+use crate::{
+    api::batch::{BATCH, DELIMITER},
+    error::RytmObjectError,
+    progress::partial_path,
+    value::{RytmValue, RytmValueList},
+};
+use std::{collections::BTreeMap, path::Path, str::FromStr};
+
+pub const MACROS_HEADER: &str = "# rytm macros v1";
+
+/// Defines a macro, e.g. `define stash copy pattern_wb %1 , send pattern %1`.
+pub const DEFINE: &str = "define";
+/// Runs a macro, e.g. `run stash 5`.
+pub const RUN: &str = "run";
+/// Lists the macros.
+pub const MACROS: &str = "macros";
+
+/// Starts the placeholder of an argument, `%1` is the first argument of `run`.
+///
+/// `$1` is not used since Max replaces it in message boxes.
+pub const ARGUMENT_PREFIX: char = '%';
+
+/// Macros by their names.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Macros(BTreeMap<String, Vec<RytmValueList>>);
+
+impl Macros {
+    /// Reads the macros from the file system, there are no macros yet if the file does not exist.
+    pub fn read(path: impl AsRef<Path>) -> Result<Self, RytmObjectError> {
+        let path = path.as_ref();
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let text = std::fs::read_to_string(path).map_err(|err| {
+            format!(
+                "Macro Error: Failed to read macros {}: {err}",
+                path.display()
+            )
+        })?;
+        text.parse()
+    }
+
+    /// Writes the macros to the file system, the directory of the file is made if it does not exist.
+    ///
+    /// The macros are written next to the file and moved over it, a failed write leaves the file as it was.
+    pub fn write(&self, path: impl AsRef<Path>) -> Result<(), RytmObjectError> {
+        let path = path.as_ref();
+        let partial = partial_path(path);
+        let write = || -> std::io::Result<()> {
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::fs::write(&partial, self.to_string())?;
+            std::fs::rename(&partial, path)
+        };
+        write().map_err(|err| {
+            std::fs::remove_file(&partial).ok();
+            format!(
+                "Macro Error: Failed to write macros {}: {err}",
+                path.display()
+            )
+            .into()
+        })
+    }
+
+    /// Defines a macro from `<name> <command> , <command> ...`, a macro with the same name is replaced.
+    ///
+    /// A name alone removes the macro. Every command should start with one of the `selectors`.
+    pub fn define(
+        &mut self,
+        values: &RytmValueList,
+        selectors: &[&str],
+    ) -> Result<(), RytmObjectError> {
+        let Some((RytmValue::Symbol(name), values)) = values.split_first() else {
+            return Err(format!(
+                "Macro Error: Invalid format. A macro should start with its name. Example: {DEFINE} stash copy pattern_wb %1 {DELIMITER} send pattern %1"
+            )
+            .into());
+        };
+        if name.is_empty() || name.starts_with('#') || name.contains(char::is_whitespace) {
+            return Err(format!(
+                "Macro Error: Invalid name {name}. Names can not be empty, start with # or contain spaces."
+            )
+            .into());
+        }
+
+        let commands = values
+            .split(|value| matches!(value, RytmValue::Symbol(delimiter) if delimiter == DELIMITER))
+            .filter(|command| !command.is_empty())
+            .enumerate()
+            .map(|(number, command)| {
+                Self::validate_command(command, selectors).map_err(|err| {
+                    RytmObjectError::from(format!(
+                        "Macro Error: Command {} of {name}. {err}",
+                        number + 1
+                    ))
+                })?;
+                Ok(RytmValueList::from(command.to_vec()))
+            })
+            .collect::<Result<Vec<_>, RytmObjectError>>()?;
+
+        if commands.is_empty() {
+            return self.0.remove(name).map(|_| ()).ok_or_else(|| {
+                format!("Macro Error: There is no macro named {name} to remove.").into()
+            });
+        }
+        self.0.insert(name.clone(), commands);
+        Ok(())
+    }
+
+    fn validate_command(command: &[RytmValue], selectors: &[&str]) -> Result<(), String> {
+        match command.first() {
+            // The commands of a batch would be split into commands of the macro.
+            Some(RytmValue::Symbol(selector)) if selector == BATCH => return Err(format!(
+                "{BATCH} can not be used in a macro since its commands are separated with {DELIMITER} like the ones of a macro. Use its commands in the macro instead."
+            )),
+            Some(RytmValue::Symbol(selector)) if selectors.contains(&selector.as_str()) => {}
+            Some(other) => return Err(format!(
+                "{other} is not a command which can be used in a macro. Possible commands are {}.",
+                selectors.join(", ")
+            )),
+            None => unreachable!("Empty commands are skipped."),
+        }
+
+        command.iter().try_for_each(|value| match value {
+            RytmValue::Symbol(symbol) if symbol.contains(char::is_whitespace) => Err(format!(
+                "{symbol} can not be stored since it contains spaces."
+            )),
+            RytmValue::Symbol(symbol) if argument_number(symbol) == Some(0) => Err(format!(
+                "{symbol} is not an argument, arguments are counted from {ARGUMENT_PREFIX}1."
+            )),
+            _ => Ok(()),
+        })
+    }
+
+    /// The commands of a macro with the placeholders of its arguments replaced by the `arguments`.
+    pub fn expand(
+        &self,
+        name: &str,
+        arguments: &[RytmValue],
+    ) -> Result<Vec<RytmValueList>, RytmObjectError> {
+        let commands = self.0.get(name).ok_or_else(|| {
+            format!("Macro Error: There is no macro named {name}. Define it first with {DEFINE} {name} <command> {DELIMITER} <command> ...")
+        })?;
+
+        commands
+            .iter()
+            .map(|command| {
+                command
+                    .iter()
+                    .map(|value| match value {
+                        RytmValue::Symbol(symbol) => match argument_number(symbol) {
+                            Some(number) => arguments.get(number - 1).cloned().ok_or_else(|| {
+                                RytmObjectError::from(format!(
+                                    "Macro Error: {name} uses {symbol} but it is run with {} arguments.",
+                                    arguments.len()
+                                ))
+                            }),
+                            None => Ok(value.clone()),
+                        },
+                        _ => Ok(value.clone()),
+                    })
+                    .collect::<Result<Vec<_>, _>>()
+                    .map(RytmValueList::from)
+            })
+            .collect()
+    }
+
+    /// Every macro with its commands, ordered by name.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &[RytmValueList])> {
+        self.0
+            .iter()
+            .map(|(name, commands)| (name.as_str(), commands.as_slice()))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+/// The number of an argument placeholder like `%2`.
+fn argument_number(symbol: &str) -> Option<usize> {
+    symbol
+        .strip_prefix(ARGUMENT_PREFIX)
+        .and_then(|number| number.parse().ok())
+}
+
+/// Writes a value so it is read back as the same type, floats keep their decimal point.
+fn format_value(value: &RytmValue) -> String {
+    match value {
+        RytmValue::Float(float) => format!("{float:?}"),
+        RytmValue::Int(_) | RytmValue::Symbol(_) => value.to_string(),
+    }
+}
+
+fn parse_value(token: &str) -> RytmValue {
+    if let Ok(int) = token.parse::<isize>() {
+        return RytmValue::Int(int);
+    }
+    // Symbols like `inf` or `nan` would parse as floats too.
+    let looks_numeric = token.starts_with(|c: char| c.is_ascii_digit() || c == '-' || c == '.');
+    match token.parse::<f64>() {
+        Ok(float) if looks_numeric => RytmValue::Float(float),
+        _ => RytmValue::from(token),
+    }
+}
+
+impl std::fmt::Display for Macros {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "{MACROS_HEADER}")?;
+        for (name, commands) in self.iter() {
+            write!(f, "{name}")?;
+            for (number, command) in commands.iter().enumerate() {
+                if number > 0 {
+                    write!(f, " {DELIMITER}")?;
+                }
+                for value in command.iter() {
+                    write!(f, " {}", format_value(value))?;
+                }
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Macros {
+    type Err = RytmObjectError;
+
+    /// Parses macros, see the module docs for the format.
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let mut lines = text.lines().map(str::trim);
+        if lines.next() != Some(MACROS_HEADER) {
+            return Err(format!(
+                "Macro Error: Not a macros file. A macros file should start with {MACROS_HEADER}."
+            )
+            .into());
+        }
+
+        let mut macros = Self::default();
+        for line in lines.filter(|line| !line.is_empty() && !line.starts_with('#')) {
+            let mut tokens = line.split_whitespace();
+            let name = tokens.next().unwrap_or_default().to_owned();
+            let commands = tokens
+                .map(parse_value)
+                .collect::<Vec<_>>()
+                .split(
+                    |value| matches!(value, RytmValue::Symbol(delimiter) if delimiter == DELIMITER),
+                )
+                .filter(|command| !command.is_empty())
+                .map(|command| RytmValueList::from(command.to_vec()))
+                .collect::<Vec<_>>();
+            if commands.is_empty() {
+                return Err(format!("Macro Error: {name} has no commands.").into());
+            }
+            macros.0.insert(name, commands);
+        }
+        Ok(macros)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SELECTORS: [&str; 3] = ["copy", "send", "query"];
+
+    fn values(values: Vec<RytmValue>) -> RytmValueList {
+        RytmValueList::from(values)
+    }
+
+    fn stash() -> RytmValueList {
+        values(vec![
+            "stash".into(),
+            "copy".into(),
+            "pattern_wb".into(),
+            "%1".into(),
+            ",".into(),
+            "send".into(),
+            "pattern".into(),
+            "%1".into(),
+            ",".into(),
+            "query".into(),
+            "kit_wb".into(),
+        ])
+    }
+
+    #[test]
+    fn arguments_are_substituted() {
+        let mut macros = Macros::default();
+        macros.define(&stash(), &SELECTORS).unwrap();
+
+        let commands = macros.expand("stash", &[RytmValue::Int(5)]).unwrap();
+        assert_eq!(
+            commands,
+            vec![
+                values(vec!["copy".into(), "pattern_wb".into(), 5_isize.into()]),
+                values(vec!["send".into(), "pattern".into(), 5_isize.into()]),
+                values(vec!["query".into(), "kit_wb".into()]),
+            ]
+        );
+        assert!(macros.expand("stash", &[]).is_err());
+        assert!(macros.expand("unknown", &[]).is_err());
+    }
+
+    #[test]
+    fn macros_are_written_and_read_back() {
+        let mut macros = Macros::default();
+        macros.define(&stash(), &SELECTORS).unwrap();
+        macros
+            .define(
+                &values(vec![
+                    "fade".into(),
+                    "send".into(),
+                    "global".into(),
+                    1.0.into(),
+                ]),
+                &SELECTORS,
+            )
+            .unwrap();
+
+        let text = macros.to_string();
+        assert!(text.starts_with(MACROS_HEADER));
+        assert!(text.contains("stash copy pattern_wb %1 , send pattern %1 , query kit_wb"));
+        assert_eq!(text.parse::<Macros>().unwrap(), macros);
+
+        let path = std::env::temp_dir()
+            .join(format!("rytm-macros-{}", std::process::id()))
+            .join("macros.txt");
+        assert_eq!(Macros::read(&path).unwrap(), Macros::default());
+        macros.write(&path).unwrap();
+        assert_eq!(Macros::read(&path).unwrap(), macros);
+        assert!(!partial_path(&path).exists());
+        std::fs::remove_dir_all(path.parent().unwrap()).ok();
+    }
+
+    #[test]
+    fn invalid_definitions_are_rejected() {
+        let mut macros = Macros::default();
+        // Not a selector which can be used in a macro.
+        assert!(macros
+            .define(
+                &values(vec!["again".into(), "run".into(), "stash".into()]),
+                &SELECTORS
+            )
+            .is_err());
+        assert!(macros
+            .define(
+                &values(vec!["zero".into(), "send".into(), "%0".into()]),
+                &SELECTORS
+            )
+            .is_err());
+        // A batch would be split at its delimiter.
+        assert!(macros
+            .define(
+                &values(vec![
+                    "both".into(),
+                    "batch".into(),
+                    "get".into(),
+                    "kit_wb".into(),
+                    "tracklevel".into(),
+                    0_isize.into(),
+                    ",".into(),
+                    "get".into(),
+                    "kit_wb".into(),
+                    "tracklevel".into(),
+                    1_isize.into(),
+                ]),
+                &[SELECTORS.as_slice(), &["batch", "get"]].concat()
+            )
+            .is_err());
+        // Removing a macro which is not defined.
+        assert!(macros
+            .define(&values(vec!["stash".into()]), &SELECTORS)
+            .is_err());
+
+        macros.define(&stash(), &SELECTORS).unwrap();
+        macros
+            .define(&values(vec!["stash".into()]), &SELECTORS)
+            .unwrap();
+        assert!(macros.is_empty());
+    }
+}
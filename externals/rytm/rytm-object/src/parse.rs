@@ -2,15 +2,26 @@ pub mod types;
 
 use crate::{
     api,
-    api::object_type::*,
+    api::{
+        address::{LIST_END, LIST_START, MAX_EXPANDED_COMMANDS, WILDCARD},
+        batch::DELIMITER,
+        device_prefix::DEVICE,
+        dump::DUMP,
+        enum_query::VARIANTS,
+        object_type::*,
+    },
     error::ParseError,
     parse::types::ParseResult,
+    relative::RelativeOperation,
+    schema::ParameterScope,
+    snapshot::level_paths,
+    suggestion::suggestions,
     types::CommandType,
     value::{RytmValue, RytmValueList},
 };
 use error_logger_macro::log_errors;
 use lazy_static::lazy_static;
-use std::{collections::HashSet, str::FromStr};
+use std::{collections::HashSet, ops::RangeInclusive, str::FromStr};
 use tracing::{error, instrument};
 use types::{Number, ObjectTypeSelector, ParsedValue, PlockOperation};
 
@@ -240,6 +251,12 @@ where
     if iter.peek().is_some() {
         if element == SOUND {
             parse_sound(command_type, iter, result)
+        } else if command_type == CommandType::Set
+            && matches!(iter.peek(), Some(RytmValue::Int(_) | RytmValue::Float(_)))
+        {
+            // Numeric elements such as tracklevel are set with a value right after the index.
+            result.extend(parse_single_parameter(iter));
+            Ok(())
         } else {
             parse_identifier_or_enum(command_type, iter, result)
         }
@@ -324,6 +341,7 @@ where
 enum EnumParseResult {
     Complete(String, Option<String>),
     RequiresValue(String),
+    Query(String),
     Invalid(String),
 }
 
@@ -350,10 +368,12 @@ where
     }
 
     if !is_enum(symbol) {
-        return Err(ParseError::InvalidToken(format!(
-            "Unexpected symbol '{}'. Expected an identifier or enum.",
-            symbol
-        )));
+        return Err(ParseError::UnknownSymbol {
+            symbol: symbol.clone(),
+            suggestions: ParameterScope::of_level(result)
+                .map(|scope| suggestions(symbol, scope))
+                .unwrap_or_default(),
+        });
     }
 
     // Handle enum parsing
@@ -372,8 +392,18 @@ where
                 Ok(())
             }
         }
+        EnumParseResult::Query(name) => {
+            if command_type == CommandType::Set {
+                Err(ParseError::InvalidFormat(format!(
+                    "Enum '{name}:{VARIANTS}' lists the variants of an enum and can only be used with get. Try using '{name}:<your-value>' instead.",
+                )))
+            } else {
+                result.push(ParsedValue::EnumVariants(name));
+                Ok(())
+            }
+        }
         EnumParseResult::Invalid(s) => Err(ParseError::InvalidFormat(format!(
-            "Invalid enum format: '{}'. Enums may only have the format of <enum-type>: or <enum-type>:<variant>",
+            "Invalid enum format: '{}'. Enums may only have the format of <enum-type>:, <enum-type>:<variant> or <enum-type>:?",
             s
         ))),
     }
@@ -426,6 +456,7 @@ fn parse_enum_value(s: &str) -> EnumParseResult {
     };
 
     match parts.next() {
+        Some(VARIANTS) => EnumParseResult::Query(name),
         Some(value) if !value.is_empty() => {
             EnumParseResult::Complete(name, Some(value.to_string()))
         }
@@ -454,6 +485,597 @@ fn validate_index(index: &isize, min: isize, max: isize, name: &str) -> ParseRes
     }
 }
 
+/// Parses the selector of a bulk query or send such as `all` or `patterns 0..16`.
+///
+/// Returns `None` if the selector is not a bulk selector so it can be handled as a single object.
+#[instrument]
+#[log_errors]
+pub fn parse_bulk_selector(values: &RytmValueList) -> ParseResult<Option<Vec<ObjectTypeSelector>>> {
+    use api::bulk_object_type::*;
+
+    let Some(RytmValue::Symbol(selector)) = values.first() else {
+        return Ok(None);
+    };
+
+    if !BULK_OBJECT_TYPES.contains(&selector.as_str()) {
+        return Ok(None);
+    }
+
+    let range = values.get(1);
+    if values.len() > 2 || (selector == ALL && range.is_some()) {
+        return Err(ParseError::InvalidFormat(format!(
+            "Invalid bulk format. The right format should be, all or <bulk-selector> [<index-range>] where bulk selector is one of {}. Example: query patterns 0..16",
+            BULK_OBJECT_TYPES[1..].join(", ")
+        )));
+    }
+
+    let selectors = match selector.as_str() {
+        PATTERNS => expand_indices(range, 127, "Pattern index", ObjectTypeSelector::Pattern)?,
+        KITS => expand_indices(range, 127, "Kit index", ObjectTypeSelector::Kit)?,
+        SOUNDS => expand_indices(range, 127, "Sound index", ObjectTypeSelector::Sound)?,
+        GLOBALS => expand_indices(range, 3, "Global index", ObjectTypeSelector::Global)?,
+        // ALL
+        _ => {
+            let mut selectors = Vec::new();
+            selectors.extend((0..=127).map(ObjectTypeSelector::Pattern));
+            selectors.extend((0..=127).map(ObjectTypeSelector::Kit));
+            selectors.extend((0..=127).map(ObjectTypeSelector::Sound));
+            selectors.extend((0..=3).map(ObjectTypeSelector::Global));
+            selectors.push(ObjectTypeSelector::Settings);
+            selectors
+        }
+    };
+
+    Ok(Some(selectors))
+}
+
+fn expand_indices(
+    range: Option<&RytmValue>,
+    max: usize,
+    name: &str,
+    constructor: fn(usize) -> ObjectTypeSelector,
+) -> ParseResult<Vec<ObjectTypeSelector>> {
+    let range = match range {
+        Some(value) => parse_index_range(value, 0, max as isize, name)?,
+        None => 0..=max,
+    };
+
+    Ok(range.map(constructor).collect())
+}
+
+/// Parses an index or a range of indexes.
+///
+/// Accepts a single integer or a range like `0..15` which includes both of its ends, `0..=15` is the same range.
+#[instrument]
+#[log_errors]
+pub fn parse_index_range(
+    value: &RytmValue,
+    min: isize,
+    max: isize,
+    name: &str,
+) -> ParseResult<RangeInclusive<usize>> {
+    let (start, end) = match value {
+        RytmValue::Int(index) => (*index, *index),
+        RytmValue::Symbol(range) => {
+            let invalid = || {
+                ParseError::InvalidFormat(format!(
+                    "{range} is not a valid range for {}. Ranges are written as <start>..<end> and include both ends. Example: 0..15",
+                    name.to_lowercase()
+                ))
+            };
+
+            let (start, end) = range.split_once("..").ok_or_else(invalid)?;
+            let end = end.strip_prefix('=').unwrap_or(end);
+            let start = start.parse::<isize>().map_err(|_| invalid())?;
+            let end = end.parse::<isize>().map_err(|_| invalid())?;
+
+            (start, end)
+        }
+        RytmValue::Float(_) => return Err(ParseError::InvalidIndexType),
+    };
+
+    if start > end {
+        return Err(ParseError::InvalidFormat(format!(
+            "{value} is an empty range for {}.",
+            name.to_lowercase()
+        )));
+    }
+
+    validate_index(&start, min, max, name)?;
+    validate_index(&end, min, max, name)?;
+
+    Ok(start as usize..=end as usize)
+}
+
+/// Takes an `@device <device-id>` prefix off a command.
+///
+/// Returns `None` for the device id if the command does not have the prefix.
+#[instrument]
+#[log_errors]
+pub fn parse_device_prefix(mut values: RytmValueList) -> ParseResult<(Option<u8>, RytmValueList)> {
+    if !matches!(values.first(), Some(RytmValue::Symbol(prefix)) if prefix == DEVICE) {
+        return Ok((None, values));
+    }
+
+    let device_id = parse_device_id(values.get(1))?;
+    values.drain(..2);
+    Ok((Some(device_id), values))
+}
+
+/// Takes an `@device <device-id>` suffix off a command, used for the target device of `copy`.
+///
+/// Returns `None` for the device id if the command does not have the suffix.
+#[instrument]
+#[log_errors]
+pub fn parse_device_suffix(mut values: RytmValueList) -> ParseResult<(RytmValueList, Option<u8>)> {
+    let Some(position) = values
+        .iter()
+        .rposition(|value| matches!(value, RytmValue::Symbol(suffix) if suffix == DEVICE))
+    else {
+        return Ok((values, None));
+    };
+
+    if position + 2 != values.len() {
+        return Err(ParseError::InvalidFormat(format!(
+            "{DEVICE} <device-id> should either start or end the command."
+        )));
+    }
+
+    let device_id = parse_device_id(values.get(position + 1))?;
+    values.truncate(position);
+    Ok((values, Some(device_id)))
+}
+
+/// Reads the indexes of a command which are written from `base` as the 0-based indexes the parser expects.
+///
+/// Indexes of objects, tracks, trigs, sounds and kit elements are rebased in ranges and lists too, the values after the address are left as they are.
+/// Device style pattern names like `A01` are read in the place of a pattern index, `is_copy` reads the target of a copy as one too.
+#[instrument]
+#[log_errors]
+pub fn rebase_indices(
+    mut values: RytmValueList,
+    base: usize,
+    is_copy: bool,
+) -> ParseResult<RytmValueList> {
+    let Some(RytmValue::Symbol(object_type)) = values.first() else {
+        return Ok(values);
+    };
+    let is_pattern = object_type == PATTERN || object_type == api::bulk_object_type::PATTERNS;
+    // The pattern work buffer is copied to the pattern which follows it.
+    let is_pattern_wb_copy = is_copy && object_type == PATTERN_WORK_BUFFER;
+    let is_kit = object_type == KIT || object_type == KIT_WORK_BUFFER;
+
+    let mut place = 0;
+    let mut in_list = false;
+    let mut after_element = false;
+    for value in values.iter_mut().skip(1) {
+        if !in_list {
+            if is_kit && matches!(value, RytmValue::Symbol(element) if is_element(element)) {
+                after_element = true;
+                continue;
+            }
+            let is_pattern_name = (is_pattern || is_pattern_wb_copy)
+                && matches!(value, RytmValue::Symbol(name) if parse_pattern_name(name).is_some());
+            if !is_address(value) && !is_pattern_name {
+                break;
+            }
+        }
+
+        let (starts_list, ends_list) = match value {
+            RytmValue::Symbol(s) => (s.starts_with(LIST_START), s.ends_with(LIST_END)),
+            _ => (false, false),
+        };
+        let pattern_names = (is_pattern && (place == 0 || (is_copy && place == 1)))
+            || (is_pattern_wb_copy && place == 0);
+        *value = rebase_index(value, base, pattern_names)?;
+
+        in_list = (in_list || starts_list) && !ends_list;
+        if !in_list {
+            place += 1;
+            // Only the index of an element is left of the address.
+            if after_element {
+                break;
+            }
+        }
+    }
+
+    Ok(values)
+}
+
+/// Rebases an index, a range or an item of a list, the brackets of a list may be stuck to it.
+///
+/// Values which are not indexes are left for the parser to report.
+fn rebase_index(value: &RytmValue, base: usize, pattern_names: bool) -> ParseResult<RytmValue> {
+    let s = match value {
+        RytmValue::Int(index) => return Ok(RytmValue::Int(rebase_bound(*index, base)?)),
+        RytmValue::Symbol(s) => s,
+        RytmValue::Float(_) => return Ok(value.clone()),
+    };
+
+    let inner = s.trim_start_matches(LIST_START);
+    let prefix = &s[..s.len() - inner.len()];
+    let core = inner.trim_end_matches(LIST_END);
+    let suffix = &inner[core.len()..];
+    let bound = |bound: &str| -> ParseResult<Option<isize>> {
+        match bound.parse::<isize>() {
+            Ok(index) => rebase_bound(index, base).map(Some),
+            Err(_) if pattern_names => Ok(parse_pattern_name(bound).map(|index| index as isize)),
+            Err(_) => Ok(None),
+        }
+    };
+
+    let rebased = match core.split_once("..") {
+        Some((start, end)) => {
+            let (end, inclusive) = end.strip_prefix('=').map_or((end, ""), |end| (end, "="));
+            match (bound(start)?, bound(end)?) {
+                (Some(start), Some(end)) => format!("{start}..{inclusive}{end}"),
+                _ => return Ok(value.clone()),
+            }
+        }
+        None => match bound(core)? {
+            Some(index) if prefix.is_empty() && suffix.is_empty() => {
+                return Ok(RytmValue::Int(index))
+            }
+            Some(index) => index.to_string(),
+            None => return Ok(value.clone()),
+        },
+    };
+
+    Ok(RytmValue::Symbol(format!("{prefix}{rebased}{suffix}")))
+}
+
+fn rebase_bound(index: isize, base: usize) -> ParseResult<isize> {
+    let base = base as isize;
+    // Indexes below 0 are left for the parser to report.
+    if (0..base).contains(&index) {
+        return Err(ParseError::IndexOutOfRange(format!(
+            "Index {index} is below the index base. Indexes start at {base}."
+        )));
+    }
+    Ok(index - base)
+}
+
+/// Reads a pattern name as it is shown on the device, `A01` to `H16`, as a 0-based pattern index.
+pub fn parse_pattern_name(name: &str) -> Option<usize> {
+    let mut chars = name.chars();
+    let bank = chars.next()?.to_ascii_uppercase();
+    let number = chars.as_str();
+    if !('A'..='H').contains(&bank)
+        || number.len() != 2
+        || !number.bytes().all(|b| b.is_ascii_digit())
+    {
+        return None;
+    }
+    let number = number
+        .parse::<usize>()
+        .ok()
+        .filter(|number| (1..=16).contains(number))?;
+
+    Some((bank as usize - 'A' as usize) * 16 + number - 1)
+}
+
+/// Expands the ranges, wildcards and lists in the address of a `get` or `set` command into one command per address.
+///
+/// Indexes of objects, tracks, trigs and kit elements may be written as `0..15`, `*` or `[0 4 8 12]`, ranges include both ends.
+/// A command which addresses a single index is returned as it is.
+#[instrument]
+#[log_errors]
+pub fn expand_addresses(values: RytmValueList) -> ParseResult<Vec<RytmValueList>> {
+    let mut iter = values.iter().peekable();
+    // Every place of the command with the values it takes.
+    let mut places: Vec<Vec<RytmValue>> = Vec::new();
+
+    let Some(selector) = iter.next() else {
+        return Ok(vec![values]);
+    };
+    places.push(vec![selector.clone()]);
+
+    let object_type = selector.to_string();
+    let index_max = match object_type.as_str() {
+        PATTERN | KIT | SOUND => Some(127),
+        SOUND_WORK_BUFFER => Some(11),
+        GLOBAL => Some(3),
+        _ => None,
+    };
+    if let Some(max) = index_max {
+        if iter.peek().is_some_and(|value| is_address(value)) {
+            places.push(expand_index(&mut iter, max, "Object index")?);
+        }
+    }
+
+    match object_type.as_str() {
+        PATTERN | PATTERN_WORK_BUFFER => {
+            for (max, name) in [(12, "Track index"), (63, "Trig index")] {
+                if !iter.peek().is_some_and(|value| is_address(value)) {
+                    break;
+                }
+                places.push(expand_index(&mut iter, max, name)?);
+            }
+        }
+        KIT | KIT_WORK_BUFFER => {
+            if let Some(RytmValue::Symbol(element)) = iter.peek() {
+                if is_element(element) {
+                    let max = if element == api::kit_element_type::SOUND {
+                        11
+                    } else {
+                        12
+                    };
+                    places.push(vec![iter.next().unwrap().clone()]);
+                    if iter.peek().is_some_and(|value| is_address(value)) {
+                        places.push(expand_index(&mut iter, max, "Kit element index")?);
+                    }
+                }
+            }
+        }
+        _ => {}
+    }
+    places.extend(iter.map(|value| vec![value.clone()]));
+
+    let count = places
+        .iter()
+        .try_fold(1_usize, |count, place| count.checked_mul(place.len()))
+        .unwrap_or(usize::MAX);
+    if count > MAX_EXPANDED_COMMANDS {
+        return Err(ParseError::InvalidFormat(format!(
+            "The command addresses {count} places. A command may address at most {MAX_EXPANDED_COMMANDS}."
+        )));
+    }
+
+    let mut commands = vec![Vec::with_capacity(places.len())];
+    for place in places {
+        commands = commands
+            .into_iter()
+            .flat_map(|command| {
+                place.iter().map(move |value| {
+                    let mut command = command.clone();
+                    command.push(value.clone());
+                    command
+                })
+            })
+            .collect();
+    }
+
+    Ok(commands.into_iter().map(RytmValueList::from).collect())
+}
+
+/// Checks if a value may take the place of an index: an integer, a range, a wildcard or the start of a list.
+fn is_address(value: &RytmValue) -> bool {
+    match value {
+        RytmValue::Int(_) => true,
+        RytmValue::Symbol(s) => s == WILDCARD || s.starts_with(LIST_START) || s.contains(".."),
+        RytmValue::Float(_) => false,
+    }
+}
+
+/// Takes an index, a range, a wildcard or a list of them off a command and returns the indexes it stands for.
+fn expand_index<'a, I>(
+    iter: &mut std::iter::Peekable<I>,
+    max: isize,
+    name: &str,
+) -> ParseResult<Vec<RytmValue>>
+where
+    I: Iterator<Item = &'a RytmValue>,
+{
+    let to_values =
+        |range: RangeInclusive<usize>| range.map(|index| RytmValue::Int(index as isize));
+
+    let first = match iter.next() {
+        Some(RytmValue::Symbol(s)) if s == WILDCARD => {
+            return Ok(to_values(0..=max as usize).collect())
+        }
+        Some(RytmValue::Symbol(s)) if s.starts_with(LIST_START) => &s[LIST_START.len_utf8()..],
+        Some(value) => return Ok(to_values(parse_index_range(value, 0, max, name)?).collect()),
+        None => return Err(ParseError::UnexpectedEnd),
+    };
+
+    // Max may hand the brackets over stuck to the first and the last item or as separate symbols.
+    let mut items = vec![RytmValue::Symbol(first.to_owned())];
+    let mut closed = false;
+    while !items
+        .last()
+        .is_some_and(|item| matches!(item, RytmValue::Symbol(s) if s.ends_with(LIST_END)))
+    {
+        match iter.next() {
+            Some(item) => items.push(item.clone()),
+            None => break,
+        }
+    }
+    if let Some(RytmValue::Symbol(last)) = items.last_mut() {
+        if let Some(stripped) = last.strip_suffix(LIST_END) {
+            *last = stripped.to_owned();
+            closed = true;
+        }
+    }
+    if !closed {
+        return Err(ParseError::InvalidFormat(format!(
+            "A list of indexes for {} should end with {LIST_END}. Example: {LIST_START}0 4 8 12{LIST_END}",
+            name.to_lowercase()
+        )));
+    }
+
+    let mut indexes = Vec::new();
+    for item in items {
+        let item = match item {
+            RytmValue::Symbol(s) if s.is_empty() => continue,
+            RytmValue::Symbol(s) => s
+                .parse::<isize>()
+                .map_or(RytmValue::Symbol(s), RytmValue::Int),
+            other => other,
+        };
+        indexes.extend(to_values(parse_index_range(&item, 0, max, name)?));
+    }
+    if indexes.is_empty() {
+        return Err(ParseError::InvalidFormat(format!(
+            "The list of indexes for {} is empty.",
+            name.to_lowercase()
+        )));
+    }
+
+    Ok(indexes)
+}
+
+/// Takes a relative operation such as `inc 5` off the end of a `set` command.
+///
+/// Returns `None` for the operation if the command sets an absolute value.
+#[instrument]
+#[log_errors]
+pub fn parse_relative_operation(
+    mut values: RytmValueList,
+) -> ParseResult<(RytmValueList, Option<RelativeOperation>)> {
+    use api::relative_operation::*;
+
+    let Some(position) = values.iter().rposition(
+        |value| matches!(value, RytmValue::Symbol(operation) if RELATIVE_OPERATIONS.contains(&operation.as_str())),
+    ) else {
+        return Ok((values, None));
+    };
+    match values.get(position.wrapping_sub(1)) {
+        // The operation is the new name of a sound.
+        Some(RytmValue::Symbol(name)) if name == api::sound_action_type::NAME => {
+            return Ok((values, None))
+        }
+        Some(RytmValue::Symbol(variant)) if variant.contains(':') => {
+            return Err(ParseError::InvalidFormat(format!(
+                "{} can not be used with the enum {variant}. Relative operations only change numeric parameters.",
+                values[position]
+            )))
+        }
+        Some(_) if position > 1 => {}
+        _ => {
+            return Err(ParseError::InvalidFormat(format!(
+                "{} should follow the identifier of the parameter it changes. Example: set sound_wb 0 filtcutoff inc 5",
+                values[position]
+            )))
+        }
+    }
+
+    let RytmValue::Symbol(operation) = values[position].clone() else {
+        unreachable!("The position is of a symbol.");
+    };
+    let arguments = values[position + 1..]
+        .iter()
+        .map(|value| match value {
+            RytmValue::Int(value) => Some(*value as f64),
+            RytmValue::Float(value) => Some(*value),
+            RytmValue::Symbol(_) => None,
+        })
+        .collect::<Option<Vec<_>>>();
+    let invalid = || {
+        ParseError::InvalidFormat(format!(
+            "Invalid arguments for {operation}. The accepted formats are {INCREMENT} [<amount>], {DECREMENT} [<amount>], {SCALE} <factor>, {INVERT} and {RANDOMIZE} [<min> <max>]."
+        ))
+    };
+
+    let operation = match (
+        operation.as_str(),
+        arguments.ok_or_else(invalid)?.as_slice(),
+    ) {
+        (INCREMENT, []) => RelativeOperation::Increment(1.0),
+        (INCREMENT, [amount]) => RelativeOperation::Increment(*amount),
+        (DECREMENT, []) => RelativeOperation::Decrement(1.0),
+        (DECREMENT, [amount]) => RelativeOperation::Decrement(*amount),
+        (SCALE, [factor]) => RelativeOperation::Scale(*factor),
+        (INVERT, []) => RelativeOperation::Invert,
+        (RANDOMIZE, []) => RelativeOperation::Randomize(None),
+        (RANDOMIZE, [min, max]) => RelativeOperation::Randomize(Some((*min, *max))),
+        _ => return Err(invalid()),
+    };
+
+    values.truncate(position);
+    Ok((values, Some(operation)))
+}
+
+/// Takes `dump` off the end of a `get` command, returns whether the command is a dump.
+pub fn parse_dump(mut values: RytmValueList) -> (RytmValueList, bool) {
+    let dump = matches!(values.last(), Some(RytmValue::Symbol(last)) if last == DUMP);
+    if dump {
+        values.pop();
+    }
+    (values, dump)
+}
+
+/// Turns the address of a `dump` into a `get` command for every identifier and enum of the level it addresses.
+///
+/// The address is an object, a track or a trig of a pattern or a sound of a kit, e.g. `pattern 0 3` for a track.
+#[instrument]
+#[log_errors]
+pub fn expand_dump(values: &RytmValueList) -> ParseResult<Vec<RytmValueList>> {
+    let mut iter = values.iter();
+    let selector = iter.next().ok_or(ParseError::QuerySelectorMissing)?;
+    let index = if ObjectTypeSelector::is_object_type_indexable(selector) {
+        match iter.next() {
+            Some(index @ RytmValue::Int(_)) => Some(index),
+            _ => return Err(ParseError::QuerySelectorIndexMissingOrInvalid),
+        }
+    } else {
+        None
+    };
+    let object = ObjectTypeSelector::try_from((selector, index))?;
+    let prefix = &values[..values.len() - iter.len()];
+
+    let paths = level_paths(object, iter.as_slice()).ok_or_else(|| {
+        ParseError::InvalidFormat(format!(
+            "{DUMP} should follow an object, a track or a trig of a pattern or a sound of a kit. Example: get pattern 0 3 {DUMP}"
+        ))
+    })?;
+
+    Ok(paths
+        .into_iter()
+        .map(|path| {
+            let mut command = prefix.to_vec();
+            command.extend(path);
+            RytmValueList::from(command)
+        })
+        .collect())
+}
+
+/// Splits a batch into its commands at the `,` delimiter.
+///
+/// Every command starts with `get` or `set`, empty commands such as the one after a trailing delimiter are skipped.
+#[instrument]
+#[log_errors]
+pub fn parse_batch(values: &RytmValueList) -> ParseResult<Vec<(CommandType, RytmValueList)>> {
+    let commands = values
+        .split(|value| matches!(value, RytmValue::Symbol(delimiter) if delimiter == DELIMITER))
+        .filter(|command| !command.is_empty())
+        .enumerate()
+        .map(|(number, command)| {
+            let command_type = match command.first() {
+                Some(RytmValue::Symbol(selector)) => selector.parse::<CommandType>()?,
+                Some(other) => return Err(ParseError::InvalidCommandType(other.to_string())),
+                None => unreachable!("Empty commands are skipped."),
+            };
+            if command_type == CommandType::Copy {
+                return Err(ParseError::InvalidFormat(format!(
+                    "Command {} of the batch is a copy. Only get and set commands can be batched.",
+                    number + 1
+                )));
+            }
+            Ok((command_type, RytmValueList::from(command[1..].to_vec())))
+        })
+        .collect::<ParseResult<Vec<_>>>()?;
+
+    if commands.is_empty() {
+        return Err(ParseError::InvalidFormat(format!(
+            "The batch is empty. Separate get or set commands with {DELIMITER}. Example: batch set pattern 0 0 0 enable 1 {DELIMITER} set pattern 0 0 1 enable 1"
+        )));
+    }
+
+    Ok(commands)
+}
+
+fn parse_device_id(value: Option<&RytmValue>) -> ParseResult<u8> {
+    match value {
+        Some(RytmValue::Int(device_id)) => {
+            validate_index(device_id, 0, 127, "Device id")?;
+            Ok(*device_id as u8)
+        }
+        _ => Err(ParseError::InvalidFormat(format!(
+            "{DEVICE} should be followed by a device id. Device id should be an integer between 0 and 127."
+        ))),
+    }
+}
+
 /// Checks if a string is a valid identifier
 #[instrument]
 fn is_identifier(s: &str) -> bool {
@@ -750,6 +1372,36 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_valid_kit_element_set() {
+        // set kit_wb tracklevel 3 100
+        let values = vec![
+            RytmValue::Symbol("kit_wb".to_string()),
+            RytmValue::Symbol("tracklevel".to_string()),
+            RytmValue::Int(3),
+            RytmValue::Int(100),
+        ];
+        let result = parse_command(&values.into(), CommandType::Set).unwrap();
+        assert_eq!(
+            result,
+            vec![
+                ParsedValue::ObjectType(ObjectTypeSelector::KitWorkBuffer),
+                ParsedValue::Element("tracklevel".to_string()),
+                ParsedValue::ElementIndex(3),
+                ParsedValue::Parameter(Number::Int(100)),
+            ]
+        );
+
+        // A get does not take a value.
+        let values = vec![
+            RytmValue::Symbol("kit_wb".to_string()),
+            RytmValue::Symbol("tracklevel".to_string()),
+            RytmValue::Int(3),
+            RytmValue::Int(100),
+        ];
+        assert!(parse_command(&values.into(), CommandType::Get).is_err());
+    }
+
     #[test]
     fn test_valid_kit_sound_get() {
         // get kit 10 sound 2 amplev
@@ -828,13 +1480,49 @@ mod tests {
         ];
         let result = parse(values.into());
         assert!(result.is_err());
-        if let Err(ParseError::InvalidToken(_)) = result {
+        if let Err(ParseError::UnknownSymbol { .. }) = result {
             // Expected error
         } else {
-            panic!("Expected InvalidToken error");
+            panic!("Expected UnknownSymbol error");
         }
     }
 
+    #[test]
+    fn test_unknown_symbol_suggestions() {
+        // get sound 0 filtcutof
+        let values = vec![
+            RytmValue::Symbol("sound".to_string()),
+            RytmValue::Int(0),
+            RytmValue::Symbol("filtcutof".to_string()),
+        ];
+        let err = parse(values.into()).unwrap_err();
+        let (symbol, suggestions) = err.suggestions().unwrap();
+        assert_eq!(symbol, "filtcutof");
+        assert_eq!(suggestions[0], "filtcutoff");
+        assert!(err.to_string().contains("Did you mean"));
+
+        // get pattern 1 0 rotnote:c
+        let values = vec![
+            RytmValue::Symbol("pattern".to_string()),
+            RytmValue::Int(1),
+            RytmValue::Int(0),
+            RytmValue::Symbol("rotnote:c".to_string()),
+        ];
+        let err = parse(values.into()).unwrap_err();
+        assert_eq!(
+            err.suggestions(),
+            Some(("rotnote:c", &["rootnote:c".to_string()][..]))
+        );
+
+        // get pattern 1 qwertyuiop
+        let values = vec![
+            RytmValue::Symbol("pattern".to_string()),
+            RytmValue::Int(1),
+            RytmValue::Symbol("qwertyuiop".to_string()),
+        ];
+        assert!(parse(values.into()).unwrap_err().suggestions().is_none());
+    }
+
     #[test]
     fn test_valid_settings_get_enum() {
         // get settings sequencermode:
@@ -1067,6 +1755,37 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_enum_variants_query() {
+        // get pattern 1 0 2 trigcondition:?
+        let values = vec![
+            RytmValue::Symbol("pattern".to_string()),
+            RytmValue::Int(1),
+            RytmValue::Int(0),
+            RytmValue::Int(2),
+            RytmValue::Symbol("trigcondition:?".to_string()),
+        ];
+        let result = parse(values.into()).unwrap();
+        assert_eq!(
+            result,
+            vec![
+                ParsedValue::ObjectType(ObjectTypeSelector::Pattern(1)),
+                ParsedValue::TrackIndex(0),
+                ParsedValue::TrigIndex(2),
+                ParsedValue::EnumVariants("trigcondition".to_string()),
+            ]
+        );
+
+        // set pattern 1 speed:?
+        let values: RytmValueList = vec![
+            RytmValue::Symbol("pattern".to_string()),
+            RytmValue::Int(1),
+            RytmValue::Symbol("speed:?".to_string()),
+        ]
+        .into();
+        assert!(parse_command(&values, CommandType::Set).is_err());
+    }
+
     #[test]
     fn test_invalid_command() {
         // foo bar baz
@@ -1237,4 +1956,471 @@ mod tests {
             ]
         );
     }
+
+    #[test]
+    fn test_index_range() {
+        let parse = |value: RytmValue| parse_index_range(&value, 0, 127, "Pattern index");
+
+        assert_eq!(parse(RytmValue::Int(4)).unwrap(), 4..=4);
+        assert_eq!(parse(RytmValue::Symbol("0..15".into())).unwrap(), 0..=15);
+        assert_eq!(parse(RytmValue::Symbol("0..=15".into())).unwrap(), 0..=15);
+        assert_eq!(parse(RytmValue::Symbol("4..4".into())).unwrap(), 4..=4);
+        assert_eq!(parse(RytmValue::Symbol("0..127".into())).unwrap(), 0..=127);
+        assert!(parse(RytmValue::Symbol("0..128".into())).is_err());
+        assert!(parse(RytmValue::Symbol("0..=128".into())).is_err());
+        assert!(parse(RytmValue::Symbol("5..4".into())).is_err());
+        assert!(parse(RytmValue::Symbol("a..b".into())).is_err());
+        assert!(parse(RytmValue::Float(1.0)).is_err());
+    }
+
+    #[test]
+    fn test_device_prefix_and_suffix() {
+        let list = |values: Vec<RytmValue>| RytmValueList::from(values);
+        let copy = vec![
+            RytmValue::Symbol("@device".into()),
+            RytmValue::Int(1),
+            RytmValue::Symbol("pattern".into()),
+            RytmValue::Int(3),
+            RytmValue::Int(5),
+            RytmValue::Symbol("@device".into()),
+            RytmValue::Int(2),
+        ];
+
+        let (source, values) = parse_device_prefix(list(copy)).unwrap();
+        assert_eq!(source, Some(1));
+        let (values, target) = parse_device_suffix(values).unwrap();
+        assert_eq!(target, Some(2));
+        assert_eq!(
+            values.as_slice(),
+            &[
+                RytmValue::Symbol("pattern".into()),
+                RytmValue::Int(3),
+                RytmValue::Int(5)
+            ]
+        );
+
+        let (device_id, values) =
+            parse_device_prefix(list(vec![RytmValue::Symbol("settings".into())])).unwrap();
+        assert_eq!(device_id, None);
+        assert_eq!(values.len(), 1);
+
+        assert!(parse_device_prefix(list(vec![
+            RytmValue::Symbol("@device".into()),
+            RytmValue::Int(128),
+        ]))
+        .is_err());
+        assert!(parse_device_prefix(list(vec![RytmValue::Symbol("@device".into())])).is_err());
+        assert!(parse_device_suffix(list(vec![
+            RytmValue::Symbol("pattern".into()),
+            RytmValue::Symbol("@device".into()),
+            RytmValue::Int(1),
+            RytmValue::Int(3),
+        ]))
+        .is_err());
+    }
+
+    #[test]
+    fn test_expand_addresses() {
+        let symbol = |s: &str| RytmValue::Symbol(s.into());
+        let expand = |values: Vec<RytmValue>| expand_addresses(values.into());
+
+        let single = expand(vec![
+            symbol("pattern"),
+            RytmValue::Int(3),
+            RytmValue::Int(0),
+            RytmValue::Int(1),
+            symbol("vel"),
+            RytmValue::Int(100),
+        ])
+        .unwrap();
+        assert_eq!(single.len(), 1);
+        assert_eq!(single[0].len(), 6);
+
+        let rows = expand(vec![
+            symbol("pattern"),
+            RytmValue::Int(3),
+            symbol("0..1"),
+            symbol("*"),
+            symbol("enable"),
+            RytmValue::Int(1),
+        ])
+        .unwrap();
+        assert_eq!(rows.len(), 2 * 64);
+        assert_eq!(
+            rows[65].as_slice(),
+            &[
+                symbol("pattern"),
+                RytmValue::Int(3),
+                RytmValue::Int(1),
+                RytmValue::Int(1),
+                symbol("enable"),
+                RytmValue::Int(1),
+            ]
+        );
+
+        // Both ends of a range are addressed, every track and the first 16 trigs.
+        let block = expand(vec![
+            symbol("pattern"),
+            RytmValue::Int(3),
+            symbol("0..11"),
+            symbol("0..15"),
+            symbol("enable"),
+            RytmValue::Int(1),
+        ])
+        .unwrap();
+        assert_eq!(block.len(), 12 * 16);
+        assert_eq!(block.last().unwrap()[2], RytmValue::Int(11));
+        assert_eq!(block.last().unwrap()[3], RytmValue::Int(15));
+
+        let trigs = |list: Vec<RytmValue>| {
+            let mut values = vec![symbol("pattern_wb"), RytmValue::Int(0)];
+            values.extend(list);
+            values.push(symbol("enable"));
+            expand(values).map(|commands| {
+                commands
+                    .iter()
+                    .map(|command| command[2].clone())
+                    .collect::<Vec<_>>()
+            })
+        };
+        let expected = vec![
+            RytmValue::Int(0),
+            RytmValue::Int(4),
+            RytmValue::Int(8),
+            RytmValue::Int(12),
+        ];
+        assert_eq!(
+            trigs(vec![
+                symbol("[0"),
+                RytmValue::Int(4),
+                RytmValue::Int(8),
+                symbol("12]")
+            ])
+            .unwrap(),
+            expected
+        );
+        assert_eq!(
+            trigs(vec![
+                symbol("["),
+                RytmValue::Int(0),
+                RytmValue::Int(4),
+                symbol("8..=12"),
+                symbol("]")
+            ])
+            .unwrap()
+            .len(),
+            7
+        );
+        assert!(trigs(vec![symbol("[0"), RytmValue::Int(4)]).is_err());
+        assert!(trigs(vec![symbol("[64]")]).is_err());
+
+        let kit = expand(vec![
+            symbol("kit_wb"),
+            symbol("tracklevel"),
+            symbol("*"),
+            RytmValue::Int(100),
+        ])
+        .unwrap();
+        assert_eq!(kit.len(), 13);
+
+        // Parameters are never expanded.
+        let levels = expand(vec![
+            symbol("kit"),
+            RytmValue::Int(0),
+            symbol("sound"),
+            RytmValue::Int(0),
+            symbol("lfodepth"),
+            symbol("*"),
+        ])
+        .unwrap();
+        assert_eq!(levels.len(), 1);
+
+        assert!(expand(vec![
+            symbol("pattern"),
+            symbol("*"),
+            symbol("*"),
+            symbol("*"),
+            symbol("enable"),
+        ])
+        .is_err());
+    }
+
+    #[test]
+    fn test_rebase_indices() {
+        let symbol = |s: &str| RytmValue::Symbol(s.into());
+        let rebase = |values: Vec<RytmValue>, is_copy: bool| {
+            rebase_indices(values.into(), 1, is_copy).map(|values| values.to_vec())
+        };
+
+        assert_eq!(
+            rebase(
+                vec![
+                    symbol("pattern"),
+                    RytmValue::Int(1),
+                    symbol("1..=4"),
+                    symbol("[1"),
+                    RytmValue::Int(5),
+                    symbol("9]"),
+                    symbol("vel"),
+                    RytmValue::Int(100),
+                ],
+                false
+            )
+            .unwrap(),
+            vec![
+                symbol("pattern"),
+                RytmValue::Int(0),
+                symbol("0..=3"),
+                symbol("[0"),
+                RytmValue::Int(4),
+                symbol("8]"),
+                symbol("vel"),
+                RytmValue::Int(100),
+            ]
+        );
+        // The value of a kit element follows its index.
+        assert_eq!(
+            rebase(
+                vec![
+                    symbol("kit_wb"),
+                    symbol("tracklevel"),
+                    RytmValue::Int(3),
+                    RytmValue::Int(100),
+                ],
+                false
+            )
+            .unwrap(),
+            vec![
+                symbol("kit_wb"),
+                symbol("tracklevel"),
+                RytmValue::Int(2),
+                RytmValue::Int(100),
+            ]
+        );
+        assert_eq!(
+            rebase(vec![symbol("pattern"), symbol("B02"), symbol("h16")], true).unwrap(),
+            vec![symbol("pattern"), RytmValue::Int(17), RytmValue::Int(127)]
+        );
+        assert_eq!(
+            rebase(vec![symbol("pattern_wb"), symbol("A03")], true).unwrap(),
+            vec![symbol("pattern_wb"), RytmValue::Int(2)]
+        );
+        assert_eq!(
+            rebase(vec![symbol("patterns"), symbol("A01..=A16")], false).unwrap(),
+            vec![symbol("patterns"), symbol("0..=15")]
+        );
+        // A pattern name is not a track index.
+        assert_eq!(
+            rebase(
+                vec![symbol("pattern"), RytmValue::Int(2), symbol("A01")],
+                false
+            )
+            .unwrap(),
+            vec![symbol("pattern"), RytmValue::Int(1), symbol("A01")]
+        );
+        assert!(rebase(vec![symbol("sound"), RytmValue::Int(0)], false).is_err());
+
+        assert_eq!(parse_pattern_name("A01"), Some(0));
+        assert_eq!(parse_pattern_name("e07"), Some(70));
+        assert_eq!(parse_pattern_name("A17"), None);
+        assert_eq!(parse_pattern_name("I01"), None);
+        assert_eq!(parse_pattern_name("A+1"), None);
+    }
+
+    #[test]
+    fn test_relative_operation() {
+        let symbol = |s: &str| RytmValue::Symbol(s.into());
+        let parse = |values: Vec<RytmValue>| parse_relative_operation(values.into());
+        let cutoff = |operation: Vec<RytmValue>| {
+            let mut values = vec![symbol("sound_wb"), RytmValue::Int(0), symbol("filtcutoff")];
+            values.extend(operation);
+            parse(values)
+        };
+
+        let (values, operation) = cutoff(vec![symbol("inc"), RytmValue::Int(5)]).unwrap();
+        assert_eq!(values.len(), 3);
+        assert_eq!(operation, Some(RelativeOperation::Increment(5.0)));
+        assert_eq!(
+            cutoff(vec![symbol("dec")]).unwrap().1,
+            Some(RelativeOperation::Decrement(1.0))
+        );
+        assert_eq!(
+            cutoff(vec![symbol("scale"), RytmValue::Float(0.5)])
+                .unwrap()
+                .1,
+            Some(RelativeOperation::Scale(0.5))
+        );
+        assert_eq!(
+            cutoff(vec![
+                symbol("randomize"),
+                RytmValue::Int(10),
+                RytmValue::Int(20)
+            ])
+            .unwrap()
+            .1,
+            Some(RelativeOperation::Randomize(Some((10.0, 20.0))))
+        );
+        assert_eq!(cutoff(vec![RytmValue::Int(5)]).unwrap().1, None);
+        assert!(cutoff(vec![symbol("scale")]).is_err());
+        assert!(cutoff(vec![symbol("invert"), RytmValue::Int(1)]).is_err());
+        assert!(cutoff(vec![symbol("randomize"), RytmValue::Int(1)]).is_err());
+
+        // A sound may be named after an operation.
+        let (values, operation) = parse(vec![
+            symbol("sound_wb"),
+            RytmValue::Int(0),
+            symbol("name"),
+            symbol("invert"),
+        ])
+        .unwrap();
+        assert_eq!((values.len(), operation), (4, None));
+        assert!(parse(vec![
+            symbol("pattern_wb"),
+            symbol("speed:1x"),
+            symbol("inc")
+        ])
+        .is_err());
+    }
+
+    #[test]
+    fn test_dump() {
+        let symbol = |s: &str| RytmValue::Symbol(s.into());
+
+        let (values, dump) = parse_dump(
+            vec![
+                symbol("pattern"),
+                RytmValue::Int(0),
+                RytmValue::Int(3),
+                symbol("dump"),
+            ]
+            .into(),
+        );
+        assert!(dump);
+        let commands = expand_dump(&values).unwrap();
+        assert!(commands.iter().all(|command| command.len() == 4
+            && command[..3] == [symbol("pattern"), RytmValue::Int(0), RytmValue::Int(3)]));
+        for command in &commands {
+            parse_command(command, CommandType::Get).unwrap();
+        }
+
+        let (values, dump) = parse_dump(vec![symbol("settings"), symbol("bpm")].into());
+        assert!(!dump);
+        assert_eq!(values.len(), 2);
+
+        let commands = expand_dump(&vec![symbol("settings")].into()).unwrap();
+        assert!(commands
+            .iter()
+            .any(|command| command.as_slice() == [symbol("settings"), symbol("projectbpm")]));
+        let commands =
+            expand_dump(&vec![symbol("kit_wb"), symbol("sound"), RytmValue::Int(2)].into())
+                .unwrap();
+        assert!(commands.iter().all(|command| command.len() >= 4
+            && command[..3] == [symbol("kit_wb"), symbol("sound"), RytmValue::Int(2)]));
+        assert!(commands.iter().any(|command| command.as_slice()
+            == [
+                symbol("kit_wb"),
+                symbol("sound"),
+                RytmValue::Int(2),
+                symbol("velmodamt"),
+                RytmValue::Int(3)
+            ]));
+        for command in &commands {
+            parse_command(command, CommandType::Get).unwrap();
+        }
+
+        assert!(expand_dump(&vec![symbol("sound")].into()).is_err());
+        assert!(
+            expand_dump(&vec![symbol("sound"), RytmValue::Int(0), RytmValue::Int(1)].into())
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_batch() {
+        let symbol = |s: &str| RytmValue::Symbol(s.into());
+        let batch = vec![
+            symbol("set"),
+            symbol("pattern"),
+            RytmValue::Int(0),
+            RytmValue::Int(0),
+            RytmValue::Int(0),
+            symbol("enable"),
+            RytmValue::Int(1),
+            symbol(","),
+            symbol("get"),
+            symbol("settings"),
+            symbol("bpm"),
+            symbol(","),
+        ];
+
+        let commands = parse_batch(&batch.into()).unwrap();
+        assert_eq!(commands.len(), 2);
+        assert_eq!(commands[0].0, CommandType::Set);
+        assert_eq!(commands[0].1.len(), 6);
+        assert_eq!(commands[1].0, CommandType::Get);
+        assert_eq!(
+            commands[1].1.as_slice(),
+            &[symbol("settings"), symbol("bpm")]
+        );
+
+        assert!(parse_batch(&vec![symbol(",")].into()).is_err());
+        assert!(parse_batch(&vec![symbol("query"), symbol("settings")].into()).is_err());
+        assert!(parse_batch(
+            &vec![
+                symbol("get"),
+                symbol("settings"),
+                symbol("bpm"),
+                symbol(","),
+                symbol("copy"),
+                symbol("pattern"),
+                RytmValue::Int(0),
+                RytmValue::Int(1),
+            ]
+            .into()
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_bulk_selector() {
+        let parse = |values: Vec<RytmValue>| parse_bulk_selector(&values.into());
+
+        let all = parse(vec![RytmValue::Symbol("all".into())])
+            .unwrap()
+            .unwrap();
+        assert_eq!(all.len(), 128 * 3 + 4 + 1);
+        assert_eq!(all.first(), Some(&ObjectTypeSelector::Pattern(0)));
+        assert_eq!(all.last(), Some(&ObjectTypeSelector::Settings));
+
+        let patterns = parse(vec![
+            RytmValue::Symbol("patterns".into()),
+            RytmValue::Symbol("0..15".into()),
+        ])
+        .unwrap()
+        .unwrap();
+        assert_eq!(patterns.len(), 16);
+        assert_eq!(patterns.last(), Some(&ObjectTypeSelector::Pattern(15)));
+
+        let globals = parse(vec![RytmValue::Symbol("globals".into())])
+            .unwrap()
+            .unwrap();
+        assert_eq!(globals.len(), 4);
+
+        assert!(parse(vec![
+            RytmValue::Symbol("all".into()),
+            RytmValue::Symbol("0..2".into())
+        ])
+        .is_err());
+        assert!(parse(vec![
+            RytmValue::Symbol("globals".into()),
+            RytmValue::Symbol("0..5".into())
+        ])
+        .is_err());
+
+        // Single objects are not bulk selectors.
+        assert_eq!(
+            parse(vec![RytmValue::Symbol("pattern".into()), RytmValue::Int(1)]).unwrap(),
+            None
+        );
+    }
 }
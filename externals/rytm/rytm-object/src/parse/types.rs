@@ -55,6 +55,8 @@ pub enum ParsedValue {
     ParameterString(String),
     /// An enum with optional value (e.g., "speed:1x" or "speed:")
     Enum(String, Option<String>),
+    /// A query for the variants of an enum (e.g., "speed:?")
+    EnumVariants(String),
     /// The track index
     TrackIndex(usize),
     /// The trig index
@@ -83,6 +85,7 @@ impl std::fmt::Display for ParsedValue {
             ParsedValue::ParameterString(s) => write!(f, "{}", s),
             ParsedValue::Enum(s, Some(value)) => write!(f, "{}:{}", s, value),
             ParsedValue::Enum(s, None) => write!(f, "{}:", s),
+            ParsedValue::EnumVariants(s) => write!(f, "{}:?", s),
             ParsedValue::TrackIndex(i) => write!(f, "{}", i),
             ParsedValue::TrigIndex(i) => write!(f, "{}", i),
             ParsedValue::SoundIndex(i) => write!(f, "{}", i),
@@ -185,7 +188,7 @@ impl From<bool> for Number {
     }
 }
 
-#[derive(Debug, Copy, Clone, PartialEq)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum ObjectTypeSelector {
     Pattern(usize),
     PatternWorkBuffer,
@@ -226,6 +229,61 @@ impl ObjectTypeSelector {
         )
     }
 
+    pub const fn is_work_buffer(&self) -> bool {
+        matches!(
+            self,
+            Self::PatternWorkBuffer
+                | Self::KitWorkBuffer
+                | Self::SoundWorkBuffer(_)
+                | Self::GlobalWorkBuffer
+        )
+    }
+
+    /// The object type symbol without the work buffer suffix, the index and the work buffer flag.
+    ///
+    /// Objects which do not have an index report 0.
+    pub const fn object_parts(&self) -> (&'static str, usize, bool) {
+        match self {
+            Self::Pattern(index) => (PATTERN, *index, false),
+            Self::PatternWorkBuffer => (PATTERN, 0, true),
+            Self::Kit(index) => (KIT, *index, false),
+            Self::KitWorkBuffer => (KIT, 0, true),
+            Self::Sound(index) => (SOUND, *index, false),
+            Self::SoundWorkBuffer(index) => (SOUND, *index, true),
+            Self::Global(index) => (GLOBAL, *index, false),
+            Self::GlobalWorkBuffer => (GLOBAL, 0, true),
+            Self::Settings => (SETTINGS, 0, false),
+        }
+    }
+
+    /// The object type symbol and the index of the selector as it is written in commands.
+    pub const fn object_type_and_index(&self) -> (&'static str, Option<usize>) {
+        match self {
+            Self::Pattern(index) => (PATTERN, Some(*index)),
+            Self::PatternWorkBuffer => (PATTERN_WORK_BUFFER, None),
+            Self::Kit(index) => (KIT, Some(*index)),
+            Self::KitWorkBuffer => (KIT_WORK_BUFFER, None),
+            Self::Sound(index) => (SOUND, Some(*index)),
+            Self::SoundWorkBuffer(index) => (SOUND_WORK_BUFFER, Some(*index)),
+            Self::Global(index) => (GLOBAL, Some(*index)),
+            Self::GlobalWorkBuffer => (GLOBAL_WORK_BUFFER, None),
+            Self::Settings => (SETTINGS, None),
+        }
+    }
+
+    /// The object which is changed when this object is copied to the given index or to the work buffer.
+    ///
+    /// Returns `None` for objects which can not be copied.
+    pub const fn copy_target(&self, target_index: Option<usize>) -> Option<Self> {
+        match (self, target_index) {
+            (Self::Pattern(_) | Self::PatternWorkBuffer, Some(index)) => Some(Self::Pattern(index)),
+            (Self::Pattern(_) | Self::PatternWorkBuffer, None) => Some(Self::PatternWorkBuffer),
+            (Self::Kit(_) | Self::KitWorkBuffer, Some(index)) => Some(Self::Kit(index)),
+            (Self::Kit(_) | Self::KitWorkBuffer, None) => Some(Self::KitWorkBuffer),
+            _ => None,
+        }
+    }
+
     pub fn is_object_type_indexable(object_type: &RytmValue) -> bool {
         let object_type = object_type.to_string();
 
@@ -250,7 +308,7 @@ impl TryFrom<(&RytmValue, Option<&RytmValue>)> for ObjectTypeSelector {
             PATTERN_WORK_BUFFER => Ok(Self::PatternWorkBuffer),
             KIT => parse_indexed(index, 0..=127, Self::Kit),
             KIT_WORK_BUFFER => Ok(Self::KitWorkBuffer),
-            SOUND => parse_indexed(index, 0..=11, Self::Sound),
+            SOUND => parse_indexed(index, 0..=127, Self::Sound),
             SOUND_WORK_BUFFER => parse_indexed(index, 0..=11, Self::SoundWorkBuffer),
             GLOBAL => parse_indexed(index, 0..=3, Self::Global),
             GLOBAL_WORK_BUFFER => Ok(Self::GlobalWorkBuffer),
@@ -260,6 +318,25 @@ impl TryFrom<(&RytmValue, Option<&RytmValue>)> for ObjectTypeSelector {
     }
 }
 
+/// An object of a specific device.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct DeviceObject {
+    pub device_id: u8,
+    pub object: ObjectTypeSelector,
+}
+
+impl DeviceObject {
+    pub const fn new(device_id: u8, object: ObjectTypeSelector) -> Self {
+        Self { device_id, object }
+    }
+}
+
+impl std::fmt::Display for DeviceObject {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} of device {}", self.object, self.device_id)
+    }
+}
+
 fn parse_indexed<T>(
     index: Option<&RytmValue>,
     range: std::ops::RangeInclusive<usize>,
@@ -0,0 +1,439 @@
+//! Progress of long-running operations and cancelling them.
+//!
+//! Loading and saving projects run on a worker thread and bulk transfers leave the scheduler over many seconds.
+//! They report the phase they are in and how far they have come to a [`ProgressTracker`],
+//! which collects the events for the Max thread and holds the cancel tokens of the running operations.
+
+use crate::error::RytmObjectError;
+use parking_lot::Mutex;
+use std::{
+    collections::VecDeque,
+    fs::File,
+    io::{Read, Write},
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
+
+/// Files are read and written in pieces of this size to report progress and to check for cancellation in between.
+pub const FILE_CHUNK_SIZE: usize = 256 * 1024;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Operation {
+    Load,
+    Save,
+    /// A bulk query or send.
+    Transfer,
+}
+
+impl std::fmt::Display for Operation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Load => write!(f, "load"),
+            Self::Save => write!(f, "save"),
+            Self::Transfer => write!(f, "transfer"),
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Phase {
+    Reading,
+    Parsing,
+    Applying,
+    Serializing,
+    Writing,
+    Transferring,
+}
+
+impl std::fmt::Display for Phase {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Reading => write!(f, "reading"),
+            Self::Parsing => write!(f, "parsing"),
+            Self::Applying => write!(f, "applying"),
+            Self::Serializing => write!(f, "serializing"),
+            Self::Writing => write!(f, "writing"),
+            Self::Transferring => write!(f, "transferring"),
+        }
+    }
+}
+
+/// Shared flag which asks a running operation to stop at its next step.
+#[derive(Debug, Clone, Default)]
+pub struct CancelToken(Arc<AtomicBool>);
+
+impl CancelToken {
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::SeqCst)
+    }
+}
+
+#[derive(Debug)]
+pub enum ProgressEvent {
+    /// The first operation started or the last one ended.
+    Busy(bool),
+    Advanced {
+        operation: Operation,
+        phase: Phase,
+        percent: u8,
+    },
+    Finished {
+        operation: Operation,
+        result: Result<(), RytmObjectError>,
+    },
+    /// The operation stopped because it was cancelled, nothing is changed by it.
+    Cancelled(Operation),
+}
+
+struct Running {
+    operation: Operation,
+    token: CancelToken,
+    last: Option<(Phase, u8)>,
+}
+
+/// Keeps track of the running operations and collects their events until they are taken.
+#[derive(Default)]
+pub struct ProgressTracker {
+    running: Vec<Running>,
+    events: VecDeque<ProgressEvent>,
+}
+
+impl ProgressTracker {
+    pub fn is_busy(&self) -> bool {
+        !self.running.is_empty()
+    }
+
+    pub fn is_running(&self, operation: Operation) -> bool {
+        self.running
+            .iter()
+            .any(|running| running.operation == operation)
+    }
+
+    /// Starts tracking an operation and returns its cancel token.
+    ///
+    /// A load or a save can not start while another load or save is running.
+    /// A transfer which is already running is extended instead, its token is returned.
+    pub fn begin(&mut self, operation: Operation) -> Result<CancelToken, RytmObjectError> {
+        if let Some(running) = self.running.iter().find(|running| {
+            running.operation != Operation::Transfer && operation != Operation::Transfer
+        }) {
+            return Err(format!(
+                "Busy Error: Can not start a {operation} while a {} is running. Wait for it to finish or send cancel.",
+                running.operation
+            )
+            .into());
+        }
+        if let Some(running) = self
+            .running
+            .iter()
+            .find(|running| running.operation == operation)
+        {
+            return Ok(running.token.clone());
+        }
+
+        if self.running.is_empty() {
+            self.events.push_back(ProgressEvent::Busy(true));
+        }
+        let token = CancelToken::default();
+        self.running.push(Running {
+            operation,
+            token: token.clone(),
+            last: None,
+        });
+        Ok(token)
+    }
+
+    /// Records how far a running operation has come.
+    ///
+    /// An event is only collected when the phase or the whole percentage changes.
+    pub fn advance(&mut self, operation: Operation, phase: Phase, done: usize, total: usize) {
+        let Some(running) = self
+            .running
+            .iter_mut()
+            .find(|running| running.operation == operation)
+        else {
+            return;
+        };
+
+        let percent = percent(done, total);
+        if running.last == Some((phase, percent)) {
+            return;
+        }
+        running.last = Some((phase, percent));
+        self.events.push_back(ProgressEvent::Advanced {
+            operation,
+            phase,
+            percent,
+        });
+    }
+
+    /// Stops tracking an operation.
+    ///
+    /// An operation which fails after its token is cancelled is reported as cancelled,
+    /// one which succeeds is reported as finished since it was too late to stop it.
+    pub fn finish(&mut self, operation: Operation, result: Result<(), RytmObjectError>) {
+        let Some(position) = self
+            .running
+            .iter()
+            .position(|running| running.operation == operation)
+        else {
+            return;
+        };
+
+        let running = self.running.remove(position);
+        self.events.push_back(match result {
+            Err(_) if running.token.is_cancelled() => ProgressEvent::Cancelled(operation),
+            result => ProgressEvent::Finished { operation, result },
+        });
+        if self.running.is_empty() {
+            self.events.push_back(ProgressEvent::Busy(false));
+        }
+    }
+
+    /// Cancels every running operation, they stop at their next step.
+    ///
+    /// Returns the cancelled operations.
+    pub fn cancel(&mut self) -> Vec<Operation> {
+        self.running
+            .iter()
+            .map(|running| {
+                running.token.cancel();
+                running.operation
+            })
+            .collect()
+    }
+
+    /// Takes the events collected since the last call, in order.
+    pub fn take_events(&mut self) -> Vec<ProgressEvent> {
+        self.events.drain(..).collect()
+    }
+}
+
+/// Reports the progress of an operation which runs on another thread.
+pub struct Reporter {
+    operation: Operation,
+    token: CancelToken,
+    tracker: Arc<Mutex<ProgressTracker>>,
+    notify: Box<dyn Fn() + Send>,
+}
+
+impl Reporter {
+    /// Starts tracking the operation, `notify` is called every time there are new events to take.
+    pub fn begin(
+        tracker: &Arc<Mutex<ProgressTracker>>,
+        operation: Operation,
+        notify: Box<dyn Fn() + Send>,
+    ) -> Result<Self, RytmObjectError> {
+        let token = tracker.lock().begin(operation)?;
+        notify();
+        Ok(Self {
+            operation,
+            token,
+            tracker: Arc::clone(tracker),
+            notify,
+        })
+    }
+
+    /// Records how far the operation has come.
+    ///
+    /// Fails if the operation is cancelled, so it can stop at this step with `?`.
+    pub fn advance(&self, phase: Phase, done: usize, total: usize) -> Result<(), RytmObjectError> {
+        self.check_cancelled()?;
+        self.tracker
+            .lock()
+            .advance(self.operation, phase, done, total);
+        (self.notify)();
+        Ok(())
+    }
+
+    /// The token which cancels the operation.
+    pub fn token(&self) -> CancelToken {
+        self.token.clone()
+    }
+
+    /// Fails if the operation is cancelled.
+    pub fn check_cancelled(&self) -> Result<(), RytmObjectError> {
+        if self.token.is_cancelled() {
+            return Err(format!("Cancel Error: The {} is cancelled.", self.operation).into());
+        }
+        Ok(())
+    }
+
+    pub fn finish(self, result: Result<(), RytmObjectError>) {
+        self.tracker.lock().finish(self.operation, result);
+        (self.notify)();
+    }
+}
+
+/// Reads a file reporting the bytes read in the reading phase.
+pub fn read_file(path: &Path, reporter: &Reporter) -> Result<Vec<u8>, RytmObjectError> {
+    let mut file = File::open(path)
+        .map_err(|err| format!("Load Error: Failed to open {}: {err}", path.display()))?;
+    let total = file
+        .metadata()
+        .map_or(0, |metadata| metadata.len() as usize);
+
+    let mut bytes = Vec::with_capacity(total);
+    let mut chunk = vec![0; FILE_CHUNK_SIZE];
+    reporter.advance(Phase::Reading, 0, total)?;
+    loop {
+        let read = file
+            .read(&mut chunk)
+            .map_err(|err| format!("Load Error: Failed to read {}: {err}", path.display()))?;
+        if read == 0 {
+            break;
+        }
+        bytes.extend_from_slice(&chunk[..read]);
+        reporter.advance(Phase::Reading, bytes.len(), total.max(bytes.len()))?;
+    }
+    Ok(bytes)
+}
+
+/// Writes a file reporting the bytes written in the writing phase.
+///
+/// The bytes are written next to the file first and moved in place when they are complete,
+/// so a failed or cancelled write leaves an existing file as it was.
+pub fn write_file(path: &Path, bytes: &[u8], reporter: &Reporter) -> Result<(), RytmObjectError> {
+    let partial = partial_path(path);
+    let result = write_chunks(&partial, bytes, reporter).and_then(|()| {
+        std::fs::rename(&partial, path)
+            .map_err(|err| format!("Save Error: Failed to write {}: {err}", path.display()).into())
+    });
+    if result.is_err() {
+        std::fs::remove_file(&partial).ok();
+    }
+    result
+}
+
+fn write_chunks(path: &Path, bytes: &[u8], reporter: &Reporter) -> Result<(), RytmObjectError> {
+    let mut file = File::create(path)
+        .map_err(|err| format!("Save Error: Failed to create {}: {err}", path.display()))?;
+    let mut written = 0;
+    reporter.advance(Phase::Writing, 0, bytes.len())?;
+    for chunk in bytes.chunks(FILE_CHUNK_SIZE) {
+        file.write_all(chunk)
+            .map_err(|err| format!("Save Error: Failed to write {}: {err}", path.display()))?;
+        written += chunk.len();
+        reporter.advance(Phase::Writing, written, bytes.len())?;
+    }
+    file.sync_all()
+        .map_err(|err| format!("Save Error: Failed to write {}: {err}", path.display()).into())
+}
+
+/// Where a file is written before it is moved over the one it replaces.
+pub(crate) fn partial_path(path: &Path) -> PathBuf {
+    let mut file_name = path.file_name().unwrap_or_default().to_os_string();
+    file_name.push(".part");
+    path.with_file_name(file_name)
+}
+
+/// Whole percentage of the work which is done, nothing to do counts as complete.
+pub fn percent(done: usize, total: usize) -> u8 {
+    if total == 0 {
+        return 100;
+    }
+    (done.min(total) * 100 / total) as u8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn begin_reporter(operation: Operation) -> (Arc<Mutex<ProgressTracker>>, Reporter) {
+        let tracker = Arc::new(Mutex::new(ProgressTracker::default()));
+        let reporter = Reporter::begin(&tracker, operation, Box::new(|| {})).unwrap();
+        (tracker, reporter)
+    }
+
+    #[test]
+    fn progress_is_reported_once_per_percent() {
+        let mut tracker = ProgressTracker::default();
+        tracker.begin(Operation::Transfer).unwrap();
+        for done in 0..=400 {
+            tracker.advance(Operation::Transfer, Phase::Transferring, done, 400);
+        }
+        tracker.finish(Operation::Transfer, Ok(()));
+
+        let events = tracker.take_events();
+        assert!(matches!(events.first(), Some(ProgressEvent::Busy(true))));
+        assert!(matches!(events.last(), Some(ProgressEvent::Busy(false))));
+        assert!(matches!(
+            events[events.len() - 2],
+            ProgressEvent::Finished {
+                operation: Operation::Transfer,
+                result: Ok(())
+            }
+        ));
+        let percents = events
+            .iter()
+            .filter_map(|event| match event {
+                ProgressEvent::Advanced { percent, .. } => Some(*percent),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(percents, (0..=100).collect::<Vec<_>>());
+        assert!(!tracker.is_busy());
+    }
+
+    #[test]
+    fn loads_and_saves_do_not_overlap() {
+        let mut tracker = ProgressTracker::default();
+        tracker.begin(Operation::Load).unwrap();
+        assert!(tracker.begin(Operation::Save).is_err());
+        assert!(tracker.begin(Operation::Load).is_err());
+        let transfer = tracker.begin(Operation::Transfer).unwrap();
+        assert!(!transfer.is_cancelled());
+
+        assert_eq!(tracker.cancel(), vec![Operation::Load, Operation::Transfer]);
+        assert!(transfer.is_cancelled());
+        tracker.finish(
+            Operation::Load,
+            Err("Cancel Error: The load is cancelled.".into()),
+        );
+        assert!(tracker.is_running(Operation::Transfer));
+        assert!(!tracker.is_running(Operation::Load));
+        assert!(tracker
+            .take_events()
+            .iter()
+            .any(|event| matches!(event, ProgressEvent::Cancelled(Operation::Load))));
+    }
+
+    #[test]
+    fn cancelled_write_leaves_the_file_as_it_was() {
+        let path = std::env::temp_dir().join(format!("rytm-progress-{}.rytm", std::process::id()));
+        std::fs::write(&path, b"previous").unwrap();
+        let bytes = vec![1; FILE_CHUNK_SIZE * 3];
+
+        let (tracker, reporter) = begin_reporter(Operation::Save);
+        tracker.lock().cancel();
+        let result = write_file(&path, &bytes, &reporter);
+        assert!(result.is_err());
+        reporter.finish(result);
+        assert_eq!(std::fs::read(&path).unwrap(), b"previous");
+        assert!(!partial_path(&path).exists());
+
+        let (tracker, reporter) = begin_reporter(Operation::Save);
+        write_file(&path, &bytes, &reporter).unwrap();
+        assert_eq!(read_file(&path, &reporter).unwrap(), bytes);
+        reporter.finish(Ok(()));
+        std::fs::remove_file(&path).ok();
+
+        let last_percent = tracker
+            .lock()
+            .take_events()
+            .iter()
+            .filter_map(|event| match event {
+                ProgressEvent::Advanced { phase, percent, .. } => Some((*phase, *percent)),
+                _ => None,
+            })
+            .next_back();
+        assert_eq!(last_percent, Some((Phase::Reading, 100)));
+    }
+}
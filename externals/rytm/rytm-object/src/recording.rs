@@ -0,0 +1,259 @@
+//! Recording of the sysex traffic between `rytm` and the device.
+//!
+//! Recordings are plain text. The first line is [`RECORDING_HEADER`] and every following line is a complete sysex message:
+//!
+//! `<microseconds since the start> <in | out> <bytes in hex separated by spaces>`
+//!
+//! `out` messages are sent to the device and `in` messages are received from it, e.g.
+//!
+//! ```text
+//! # rytm sysex recording v1
+//! 0 out F0 7E 00 06 01 F7
+//! 5120 in F0 7E 00 06 02 00 20 3C 07 00 01 00 01 46 00 00 F7
+//! ```
+//!
+//! Empty lines and lines starting with `#` are ignored, so recordings can be annotated by hand and used as test fixtures.
+
+use crate::{
+    error::RytmObjectError,
+    scheduler::{Clock, SystemClock},
+};
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+    path::Path,
+    str::FromStr,
+    time::Duration,
+};
+
+pub const RECORDING_HEADER: &str = "# rytm sysex recording v1";
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Direction {
+    /// Received from the device.
+    In,
+    /// Sent to the device.
+    Out,
+}
+
+impl std::fmt::Display for Direction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::In => write!(f, "in"),
+            Self::Out => write!(f, "out"),
+        }
+    }
+}
+
+impl FromStr for Direction {
+    type Err = RytmObjectError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "in" => Ok(Self::In),
+            "out" => Ok(Self::Out),
+            other => Err(format!(
+                "Recording Error: Invalid direction {other}. Direction should be either in or out."
+            )
+            .into()),
+        }
+    }
+}
+
+/// A line of a recording.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecordedMessage {
+    pub at: Duration,
+    pub direction: Direction,
+    pub bytes: Vec<u8>,
+}
+
+impl std::fmt::Display for RecordedMessage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}", self.at.as_micros(), self.direction)?;
+        for byte in &self.bytes {
+            write!(f, " {byte:02X}")?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for RecordedMessage {
+    type Err = RytmObjectError;
+
+    fn from_str(line: &str) -> Result<Self, Self::Err> {
+        let mut parts = line.split_whitespace();
+        let (Some(at), Some(direction)) = (parts.next(), parts.next()) else {
+            return Err(format!(
+                "Recording Error: Invalid line {line}. The right format should be, <microseconds> <in | out> <bytes>."
+            )
+            .into());
+        };
+
+        let at = at.parse::<u64>().map_err(|_| {
+            format!("Recording Error: Invalid time {at}. Time should be in microseconds.")
+        })?;
+        let bytes = parts
+            .map(|byte| {
+                u8::from_str_radix(byte, 16).map_err(|_| {
+                    format!("Recording Error: Invalid byte {byte}. Bytes should be written in hex.")
+                })
+            })
+            .collect::<Result<Vec<u8>, String>>()?;
+
+        Ok(Self {
+            at: Duration::from_micros(at),
+            direction: direction.parse()?,
+            bytes,
+        })
+    }
+}
+
+/// Parses a recording, see the module docs for the format.
+pub fn parse_recording(text: &str) -> Result<Vec<RecordedMessage>, RytmObjectError> {
+    let mut lines = text.lines().map(str::trim);
+    if lines.next() != Some(RECORDING_HEADER) {
+        return Err(format!(
+            "Recording Error: Not a recording. A recording should start with {RECORDING_HEADER}."
+        )
+        .into());
+    }
+
+    lines
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(str::parse)
+        .collect()
+}
+
+/// Reads and parses a recording from the file system.
+pub fn read_recording(path: impl AsRef<Path>) -> Result<Vec<RecordedMessage>, RytmObjectError> {
+    let path = path.as_ref();
+    let text = std::fs::read_to_string(path).map_err(|err| {
+        format!(
+            "Recording Error: Failed to read recording {}: {err}",
+            path.display()
+        )
+    })?;
+    parse_recording(&text)
+}
+
+/// Writes sysex messages to a recording as they pass.
+pub struct Recorder {
+    writer: Box<dyn Write + Send>,
+    clock: Box<dyn Clock>,
+    started_at: Duration,
+}
+
+impl Recorder {
+    /// Starts a recording by writing the header.
+    pub fn new(mut writer: Box<dyn Write + Send>, clock: Box<dyn Clock>) -> std::io::Result<Self> {
+        writeln!(writer, "{RECORDING_HEADER}")?;
+        let started_at = clock.now();
+        Ok(Self {
+            writer,
+            clock,
+            started_at,
+        })
+    }
+
+    /// Starts a recording to a file, the file is replaced if it exists.
+    pub fn create(path: impl AsRef<Path>) -> std::io::Result<Self> {
+        Self::new(
+            Box::new(BufWriter::new(File::create(path)?)),
+            Box::<SystemClock>::default(),
+        )
+    }
+
+    pub fn record(&mut self, direction: Direction, bytes: &[u8]) -> std::io::Result<()> {
+        let message = RecordedMessage {
+            at: self.clock.now().saturating_sub(self.started_at),
+            direction,
+            bytes: bytes.to_vec(),
+        };
+        writeln!(self.writer, "{message}")
+    }
+
+    /// Writes what is left to the underlying writer.
+    pub fn finish(mut self) -> std::io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scheduler::ManualClock;
+    use parking_lot::Mutex;
+    use std::sync::Arc;
+
+    /// A writer which keeps what is written to it readable after it is handed away.
+    #[derive(Clone, Default)]
+    struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+    impl SharedBuffer {
+        fn contents(&self) -> Vec<u8> {
+            self.0.lock().clone()
+        }
+    }
+
+    impl Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn recording_round_trip() {
+        let buffer = SharedBuffer::default();
+        let clock = ManualClock::default();
+        clock.advance(Duration::from_secs(10));
+
+        let mut recorder =
+            Recorder::new(Box::new(buffer.clone()), Box::new(clock.clone())).unwrap();
+        recorder
+            .record(Direction::Out, &[0xF0, 0x7E, 0x00, 0x06, 0x01, 0xF7])
+            .unwrap();
+        clock.advance(Duration::from_micros(5120));
+        recorder.record(Direction::In, &[0xF0, 0x01, 0xF7]).unwrap();
+        recorder.finish().unwrap();
+
+        let text = String::from_utf8(buffer.contents()).unwrap();
+        assert_eq!(
+            text,
+            "# rytm sysex recording v1\n0 out F0 7E 00 06 01 F7\n5120 in F0 01 F7\n"
+        );
+
+        let messages = parse_recording(&text).unwrap();
+        assert_eq!(
+            messages,
+            vec![
+                RecordedMessage {
+                    at: Duration::ZERO,
+                    direction: Direction::Out,
+                    bytes: vec![0xF0, 0x7E, 0x00, 0x06, 0x01, 0xF7],
+                },
+                RecordedMessage {
+                    at: Duration::from_micros(5120),
+                    direction: Direction::In,
+                    bytes: vec![0xF0, 0x01, 0xF7],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn parses_annotated_recordings() {
+        let text = "# rytm sysex recording v1\n\n# query kit 5\n0 out f0 00 f7\n";
+        assert_eq!(parse_recording(text).unwrap().len(), 1);
+
+        assert!(parse_recording("0 out F0 F7").is_err());
+        assert!(parse_recording("# rytm sysex recording v1\n0 sideways F0 F7").is_err());
+        assert!(parse_recording("# rytm sysex recording v1\nsoon in F0 F7").is_err());
+        assert!(parse_recording("# rytm sysex recording v1\n0 in F0 GG F7").is_err());
+    }
+}
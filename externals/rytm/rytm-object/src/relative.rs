@@ -0,0 +1,223 @@
+//! Relative changes of parameters, e.g. `set sound_wb 0 filtcutoff inc 5`.
+//!
+//! The current value is read with the getter of the parameter, the new one is clamped to the range of the parameter and written with its setter.
+
+use crate::{
+    api::relative_operation::{DECREMENT, INCREMENT, INVERT, RANDOMIZE, SCALE},
+    error::SetError,
+    parse::types::Number,
+    schema::ParameterRange,
+    value::RytmValue,
+};
+
+/// A change which is made relative to the current value of a parameter.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum RelativeOperation {
+    Increment(f64),
+    Decrement(f64),
+    Scale(f64),
+    /// Mirrors the value in the range of the parameter.
+    Invert,
+    /// Picks a value in the given bounds or in the range of the parameter.
+    Randomize(Option<(f64, f64)>),
+}
+
+impl std::fmt::Display for RelativeOperation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Increment(amount) => write!(f, "{INCREMENT} {amount}"),
+            Self::Decrement(amount) => write!(f, "{DECREMENT} {amount}"),
+            Self::Scale(factor) => write!(f, "{SCALE} {factor}"),
+            Self::Invert => write!(f, "{INVERT}"),
+            Self::Randomize(Some((min, max))) => write!(f, "{RANDOMIZE} {min} {max}"),
+            Self::Randomize(None) => write!(f, "{RANDOMIZE}"),
+        }
+    }
+}
+
+impl RelativeOperation {
+    /// Checks if the operation needs the current value of the parameter.
+    pub const fn reads_current_value(&self) -> bool {
+        !matches!(self, Self::Randomize(_))
+    }
+
+    /// Applies the operation to the current value and clamps the result to the range of the parameter.
+    pub fn apply(
+        &self,
+        identifier: &str,
+        current: Option<&RytmValue>,
+        range: ParameterRange,
+    ) -> Result<Number, SetError> {
+        let current = match (self.reads_current_value(), current) {
+            (false, _) => 0.0,
+            (true, Some(RytmValue::Int(value))) => *value as f64,
+            (true, Some(RytmValue::Float(value))) => *value,
+            (true, _) => {
+                return Err(SetError::InvalidFormat(format!(
+                    "{identifier} does not have a numeric value to change with {self}."
+                )))
+            }
+        };
+        let (min, max) = (range.min.get_float(), range.max.get_float());
+
+        let value = match *self {
+            Self::Increment(amount) => current + amount,
+            Self::Decrement(amount) => current - amount,
+            Self::Scale(factor) => current * factor,
+            Self::Invert => min + max - current,
+            Self::Randomize(bounds) => {
+                let (min, max) = bounds.unwrap_or((min, max));
+                let (min, max) = if min <= max { (min, max) } else { (max, min) };
+                min + fastrand::f64() * (max - min)
+            }
+        };
+        Ok(range.clamp(value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{schema::ParameterScope, types::CommandType, value::RytmValueList, RytmObject};
+    use rytm_rs::RytmProject;
+
+    #[test]
+    fn results_are_clamped_to_the_range() {
+        let cutoff = ParameterScope::Sound.range("filtcutoff").unwrap();
+        let apply = |operation: RelativeOperation, current: isize| {
+            operation.apply("filtcutoff", Some(&RytmValue::Int(current)), cutoff)
+        };
+
+        assert_eq!(
+            apply(RelativeOperation::Increment(5.0), 100).unwrap(),
+            Number::Int(105)
+        );
+        assert_eq!(
+            apply(RelativeOperation::Increment(50.0), 100).unwrap(),
+            Number::Int(127)
+        );
+        assert_eq!(
+            apply(RelativeOperation::Decrement(5.0), 2).unwrap(),
+            Number::Int(0)
+        );
+        assert_eq!(
+            apply(RelativeOperation::Scale(0.5), 101).unwrap(),
+            Number::Int(51)
+        );
+        assert_eq!(
+            apply(RelativeOperation::Invert, 27).unwrap(),
+            Number::Int(100)
+        );
+        for _ in 0..32 {
+            let Number::Int(value) =
+                apply(RelativeOperation::Randomize(Some((10.0, 20.0))), 0).unwrap()
+            else {
+                panic!("An integer parameter should stay an integer.");
+            };
+            assert!((10..=20).contains(&value));
+        }
+
+        let bpm = ParameterScope::Settings.range("projectbpm").unwrap();
+        assert_eq!(
+            RelativeOperation::Scale(2.0)
+                .apply("projectbpm", Some(&RytmValue::Float(120.0)), bpm)
+                .unwrap(),
+            Number::Float(240.0)
+        );
+    }
+
+    #[test]
+    fn values_which_can_not_be_changed() {
+        let cutoff = ParameterScope::Sound.range("filtcutoff").unwrap();
+        // A parameter lock which is not set.
+        assert!(RelativeOperation::Increment(1.0)
+            .apply("filtcutoff", Some(&RytmValue::from("unset")), cutoff)
+            .is_err());
+        // Randomizing does not read the current value.
+        assert!(RelativeOperation::Randomize(None)
+            .apply("filtcutoff", None, cutoff)
+            .is_ok());
+    }
+
+    #[test]
+    fn relative_sets_are_clamped_to_the_range() {
+        let rytm = RytmObject::new(RytmProject::try_default().unwrap());
+        let cutoff = |operation: Vec<RytmValue>| {
+            let mut command = vec!["sound_wb".into(), 0_isize.into(), "filtcutoff".into()];
+            command.extend(operation);
+            rytm.command(CommandType::Set, RytmValueList::from(command))
+                .unwrap();
+            rytm.command(
+                CommandType::Get,
+                RytmValueList::from(vec!["sound_wb".into(), 0_isize.into(), "filtcutoff".into()]),
+            )
+            .unwrap()
+            .into_value()
+            .unwrap()
+        };
+
+        assert_eq!(cutoff(vec![100_isize.into()]), RytmValue::Int(100));
+        assert_eq!(
+            cutoff(vec!["inc".into(), 5_isize.into()]),
+            RytmValue::Int(105)
+        );
+        assert_eq!(
+            cutoff(vec!["inc".into(), 50_isize.into()]),
+            RytmValue::Int(127)
+        );
+        assert_eq!(cutoff(vec!["scale".into(), 0.5.into()]), RytmValue::Int(64));
+        assert_eq!(cutoff(vec!["invert".into()]), RytmValue::Int(63));
+        assert_eq!(cutoff(vec!["dec".into()]), RytmValue::Int(62));
+
+        rytm.command(
+            CommandType::Set,
+            RytmValueList::from(vec![
+                "kit_wb".into(),
+                "tracklevel".into(),
+                "*".into(),
+                "randomize".into(),
+                10_isize.into(),
+                20_isize.into(),
+            ]),
+        )
+        .unwrap();
+        for track in 0..13 {
+            let level = rytm
+                .project
+                .lock()
+                .work_buffer()
+                .kit()
+                .track_level(track)
+                .unwrap();
+            assert!((10..=20).contains(&level));
+        }
+
+        // The index of a sound does not have a range to clamp to.
+        assert!(rytm
+            .command(
+                CommandType::Set,
+                RytmValueList::from(vec![
+                    "sound_wb".into(),
+                    0_isize.into(),
+                    "index".into(),
+                    "inc".into(),
+                ]),
+            )
+            .is_err());
+
+        // A parameter lock which is not set has nothing to change.
+        assert!(rytm
+            .command(
+                CommandType::Set,
+                RytmValueList::from(vec![
+                    "pattern_wb".into(),
+                    0_isize.into(),
+                    0_isize.into(),
+                    "plockset".into(),
+                    "filtcutoff".into(),
+                    "inc".into(),
+                ]),
+            )
+            .is_err());
+    }
+}
@@ -0,0 +1,894 @@
+use crate::parse::types::DeviceObject;
+use parking_lot::Mutex;
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+/// Source of time for the scheduler.
+///
+/// The returned duration is measured from an arbitrary but fixed origin, only the differences matter.
+pub trait Clock: Send + Sync {
+    fn now(&self) -> Duration;
+}
+
+/// Monotonic clock backed by [`Instant`].
+pub struct SystemClock {
+    origin: Instant,
+}
+
+impl Default for SystemClock {
+    fn default() -> Self {
+        Self {
+            origin: Instant::now(),
+        }
+    }
+}
+
+impl Clock for SystemClock {
+    fn now(&self) -> Duration {
+        self.origin.elapsed()
+    }
+}
+
+/// A clock which only moves when it is told to, for driving the scheduler in tests and simulations.
+///
+/// Clones share the same time.
+#[derive(Debug, Default, Clone)]
+pub struct ManualClock(Arc<Mutex<Duration>>);
+
+impl ManualClock {
+    pub fn advance(&self, by: Duration) {
+        *self.0.lock() += by;
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> Duration {
+        *self.0.lock()
+    }
+}
+
+/// An outgoing message waiting for its turn.
+///
+/// Send requests are rendered to sysex when they leave the queue so they always carry the latest state of the object.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Request {
+    Query(DeviceObject),
+    Send(DeviceObject),
+    /// A universal device inquiry to a device id.
+    Identify(u8),
+    /// The query which warms the connection up, it is never merged with a query of the same object.
+    WarmUp(DeviceObject),
+}
+
+impl Request {
+    /// The object a query or send is about, a device inquiry is not about an object.
+    pub const fn object(&self) -> Option<DeviceObject> {
+        match self {
+            Self::Query(object) | Self::Send(object) | Self::WarmUp(object) => Some(*object),
+            Self::Identify(_) => None,
+        }
+    }
+}
+
+impl std::fmt::Display for Request {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Query(object) => write!(f, "query {object}"),
+            Self::Send(object) => write!(f, "send {object}"),
+            Self::Identify(device_id) => write!(f, "identify {device_id}"),
+            Self::WarmUp(object) => write!(f, "query {object} to warm up"),
+        }
+    }
+}
+
+/// How far a bulk transfer has come.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Progress {
+    pub done: usize,
+    pub total: usize,
+}
+
+impl Progress {
+    pub const fn is_complete(&self) -> bool {
+        self.done >= self.total
+    }
+}
+
+/// A request which left the queue.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Scheduled {
+    pub request: Request,
+    /// Present if the request is a part of a bulk transfer.
+    pub progress: Option<Progress>,
+}
+
+/// A query or a warm-up which is sent and waiting for its response.
+struct InFlight {
+    /// `None` while a retry is waiting in the queue.
+    sent_at: Option<Duration>,
+    retries: usize,
+}
+
+struct BulkTransfer {
+    members: HashSet<Request>,
+    /// Members which were also queued on their own, they stay in the queue when the transfer is cancelled.
+    queued_alone: HashSet<Request>,
+    progress: Progress,
+}
+
+/// Spaces outgoing requests to the device by a configurable interval.
+///
+/// The device processes sysex in a low priority thread so bursts of requests get lost or queued on the device.
+/// Duplicate pending requests are merged into one.
+pub struct RequestScheduler {
+    clock: Box<dyn Clock>,
+    interval: Duration,
+    queue: VecDeque<Request>,
+    /// When the last request left the queue and if it was a device inquiry.
+    last_sent: Option<(Duration, bool)>,
+    bulk: Option<BulkTransfer>,
+    timeout: Duration,
+    max_retries: usize,
+    in_flight: HashMap<Request, InFlight>,
+    /// When the last device inquiry left the queue, replies are waited for until it times out.
+    inquiry_sent_at: Option<Duration>,
+    debounce: Duration,
+    /// Requests which join the queue when they are not renewed for the debounce window, by the time they are due.
+    debounced: HashMap<Request, Duration>,
+}
+
+impl Default for RequestScheduler {
+    fn default() -> Self {
+        Self::new(Box::<SystemClock>::default())
+    }
+}
+
+impl RequestScheduler {
+    pub const DEFAULT_INTERVAL: Duration = Duration::from_millis(800);
+    pub const DEFAULT_TIMEOUT: Duration = Duration::from_millis(3000);
+    pub const DEFAULT_MAX_RETRIES: usize = 2;
+    pub const DEFAULT_DEBOUNCE: Duration = Duration::from_millis(200);
+    /// The interval after a device inquiry if the interval is longer, an inquiry is small and only the addressed device answers it.
+    pub const INQUIRY_INTERVAL: Duration = Duration::from_millis(50);
+
+    pub fn new(clock: Box<dyn Clock>) -> Self {
+        Self {
+            clock,
+            interval: Self::DEFAULT_INTERVAL,
+            queue: VecDeque::new(),
+            last_sent: None,
+            bulk: None,
+            timeout: Self::DEFAULT_TIMEOUT,
+            max_retries: Self::DEFAULT_MAX_RETRIES,
+            in_flight: HashMap::new(),
+            inquiry_sent_at: None,
+            debounce: Self::DEFAULT_DEBOUNCE,
+            debounced: HashMap::new(),
+        }
+    }
+
+    pub const fn interval(&self) -> Duration {
+        self.interval
+    }
+
+    pub fn set_interval(&mut self, interval: Duration) {
+        self.interval = interval;
+    }
+
+    pub const fn timeout(&self) -> Duration {
+        self.timeout
+    }
+
+    /// Sets how long to wait for the response of a query before retrying it.
+    ///
+    /// A zero timeout disables tracking the responses.
+    pub fn set_timeout(&mut self, timeout: Duration) {
+        self.timeout = timeout;
+        if timeout.is_zero() {
+            self.in_flight.clear();
+            self.inquiry_sent_at = None;
+        }
+    }
+
+    pub const fn max_retries(&self) -> usize {
+        self.max_retries
+    }
+
+    pub fn set_max_retries(&mut self, max_retries: usize) {
+        self.max_retries = max_retries;
+    }
+
+    pub const fn debounce(&self) -> Duration {
+        self.debounce
+    }
+
+    /// Sets how long a debounced request waits for more changes before it joins the queue.
+    pub fn set_debounce(&mut self, debounce: Duration) {
+        self.debounce = debounce;
+    }
+
+    /// Adds a request to the end of the queue once it is not renewed for the debounce window.
+    ///
+    /// Renewing a waiting request starts its window again, so a burst of changes leaves as one request.
+    /// Returns `false` if an identical request was already waiting or pending and the new one is merged into it.
+    pub fn enqueue_debounced(&mut self, request: Request) -> bool {
+        if self.queue.contains(&request) {
+            self.keep_on_cancel(request);
+            return false;
+        }
+        let due = self.clock.now() + self.debounce;
+        self.debounced.insert(request, due).is_none()
+    }
+
+    /// Adds a request to the end of the queue.
+    ///
+    /// Returns `false` if an identical request was already pending and the new one is merged into it.
+    pub fn enqueue(&mut self, request: Request) -> bool {
+        if self.queue.contains(&request) {
+            self.keep_on_cancel(request);
+            return false;
+        }
+        self.queue.push_back(request);
+        true
+    }
+
+    /// Adds a request to the front of the queue, an identical pending request is moved there.
+    pub fn enqueue_front(&mut self, request: Request) {
+        self.keep_on_cancel(request);
+        self.queue.retain(|pending| *pending != request);
+        self.queue.push_front(request);
+    }
+
+    /// Keeps a pending member of the bulk transfer in the queue when the transfer is cancelled.
+    fn keep_on_cancel(&mut self, request: Request) {
+        if let Some(bulk) = &mut self.bulk {
+            if bulk.members.contains(&request) {
+                bulk.queued_alone.insert(request);
+            }
+        }
+    }
+
+    /// Adds many requests to the end of the queue and tracks their progress as they leave it.
+    ///
+    /// If a bulk transfer is already running the new requests are appended to it.
+    pub fn enqueue_bulk(&mut self, requests: impl IntoIterator<Item = Request>) -> Progress {
+        let bulk = self.bulk.get_or_insert_with(|| BulkTransfer {
+            members: HashSet::new(),
+            queued_alone: HashSet::new(),
+            progress: Progress { done: 0, total: 0 },
+        });
+
+        for request in requests {
+            if self.queue.contains(&request) {
+                if !bulk.members.contains(&request) {
+                    bulk.queued_alone.insert(request);
+                }
+            } else {
+                self.queue.push_back(request);
+            }
+            if bulk.members.insert(request) {
+                bulk.progress.total += 1;
+            }
+        }
+
+        bulk.progress
+    }
+
+    /// Progress of the running bulk transfer if there is one.
+    pub fn progress(&self) -> Option<Progress> {
+        self.bulk.as_ref().map(|bulk| bulk.progress)
+    }
+
+    pub fn pending(&self) -> usize {
+        self.queue.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.queue.is_empty()
+    }
+
+    /// The request which leaves the queue next.
+    pub fn peek(&self) -> Option<Request> {
+        self.queue.front().copied()
+    }
+
+    pub fn clear(&mut self) {
+        self.queue.clear();
+        self.debounced.clear();
+        self.bulk = None;
+        self.in_flight.clear();
+        self.inquiry_sent_at = None;
+    }
+
+    /// Drops the requests of the running bulk transfer which did not leave the queue yet.
+    ///
+    /// Requests which were also queued on their own stay in the queue.
+    /// Returns how many requests are dropped, requests which are already sent are still waited for.
+    pub fn cancel_bulk(&mut self) -> usize {
+        let Some(bulk) = self.bulk.take() else {
+            return 0;
+        };
+        let pending = self.queue.len();
+        self.queue.retain(|request| {
+            !bulk.members.contains(request) || bulk.queued_alone.contains(request)
+        });
+        pending - self.queue.len()
+    }
+
+    /// Drops the device inquiries which did not leave the queue yet and stops waiting for replies to the sent ones.
+    ///
+    /// Returns how many inquiries are dropped.
+    pub fn cancel_inquiries(&mut self) -> usize {
+        let pending = self.queue.len();
+        self.queue
+            .retain(|request| !matches!(request, Request::Identify(_)));
+        self.inquiry_sent_at = None;
+        pending - self.queue.len()
+    }
+
+    /// Stops waiting for replies to device inquiries once every inquiry left the queue and the last one timed out.
+    ///
+    /// Returns `true` when the inquiries time out, inquiries are not retried.
+    pub fn expire_inquiries(&mut self) -> bool {
+        let Some(sent_at) = self.inquiry_sent_at else {
+            return false;
+        };
+        let pending = self
+            .queue
+            .iter()
+            .any(|request| matches!(request, Request::Identify(_)));
+        if pending || self.clock.now() < sent_at + self.timeout {
+            return false;
+        }
+        self.inquiry_sent_at = None;
+        true
+    }
+
+    /// Marks a query or a warm-up as answered.
+    ///
+    /// Returns `false` if the request was not waiting for a response.
+    pub fn resolve(&mut self, request: Request) -> bool {
+        self.in_flight.remove(&request).is_some()
+    }
+
+    /// Queries and warm-ups which are sent and not answered yet.
+    pub fn awaiting_response(&self) -> impl Iterator<Item = &Request> {
+        self.in_flight.keys()
+    }
+
+    /// Queues the queries and warm-ups which did not get a response in time again.
+    ///
+    /// Returns the requests which ran out of retries, they are not tracked anymore.
+    pub fn expire(&mut self) -> Vec<Request> {
+        if self.timeout.is_zero() {
+            return Vec::new();
+        }
+
+        let now = self.clock.now();
+        let mut retry = Vec::new();
+        let mut timed_out = Vec::new();
+
+        for (request, in_flight) in &mut self.in_flight {
+            match in_flight.sent_at {
+                Some(sent_at) if now >= sent_at + self.timeout => {
+                    if in_flight.retries < self.max_retries {
+                        in_flight.retries += 1;
+                        in_flight.sent_at = None;
+                        retry.push(*request);
+                    } else {
+                        timed_out.push(*request);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        for request in &timed_out {
+            self.in_flight.remove(request);
+        }
+
+        for request in retry {
+            self.enqueue(request);
+        }
+
+        timed_out
+    }
+
+    /// Time left until the earliest query waiting for a response expires.
+    pub fn time_until_next_timeout(&self) -> Option<Duration> {
+        if self.timeout.is_zero() {
+            return None;
+        }
+
+        let now = self.clock.now();
+        self.in_flight
+            .values()
+            .filter_map(|in_flight| in_flight.sent_at)
+            .chain(self.inquiry_sent_at)
+            .map(|sent_at| (sent_at + self.timeout).saturating_sub(now))
+            .min()
+    }
+
+    /// Time left until the next request may leave the queue or a debounced request joins it.
+    ///
+    /// Returns `None` when there is nothing pending.
+    pub fn time_until_next(&self) -> Option<Duration> {
+        let now = self.clock.now();
+        let debounced = self
+            .debounced
+            .values()
+            .map(|due| due.saturating_sub(now))
+            .min();
+
+        match (self.time_until_next_in_queue(), debounced) {
+            (Some(queued), Some(debounced)) => Some(queued.min(debounced)),
+            (queued, debounced) => queued.or(debounced),
+        }
+    }
+
+    fn time_until_next_in_queue(&self) -> Option<Duration> {
+        if self.queue.is_empty() {
+            return None;
+        }
+
+        Some(
+            self.last_sent
+                .map_or(Duration::ZERO, |(last_sent, inquiry)| {
+                    let interval = if inquiry {
+                        self.interval.min(Self::INQUIRY_INTERVAL)
+                    } else {
+                        self.interval
+                    };
+                    (last_sent + interval).saturating_sub(self.clock.now())
+                }),
+        )
+    }
+
+    /// Moves the debounced requests which are due to the end of the queue, the earliest first.
+    ///
+    /// Requests which are due at the same time join in the order of their objects.
+    fn release_debounced(&mut self) {
+        let now = self.clock.now();
+        let mut due = self
+            .debounced
+            .iter()
+            .filter(|(_, due)| **due <= now)
+            .map(|(request, due)| (*due, *request))
+            .collect::<Vec<_>>();
+        due.sort_unstable();
+
+        for (_, request) in due {
+            self.debounced.remove(&request);
+            self.enqueue(request);
+        }
+    }
+
+    /// Takes the next request out of the queue if the interval since the last one has passed.
+    pub fn poll(&mut self) -> Option<Scheduled> {
+        self.release_debounced();
+        if self.time_until_next_in_queue()? > Duration::ZERO {
+            return None;
+        }
+
+        let request = self.queue.pop_front()?;
+        let now = self.clock.now();
+        self.last_sent = Some((now, matches!(request, Request::Identify(_))));
+
+        match (request, self.timeout.is_zero()) {
+            (Request::Query(_) | Request::WarmUp(_), false) => {
+                self.in_flight
+                    .entry(request)
+                    .or_insert(InFlight {
+                        sent_at: None,
+                        retries: 0,
+                    })
+                    .sent_at = Some(now);
+            }
+            (Request::Identify(_), false) => self.inquiry_sent_at = Some(now),
+            _ => {}
+        }
+
+        let progress = self.bulk.as_mut().and_then(|bulk| {
+            bulk.queued_alone.remove(&request);
+            bulk.members.remove(&request).then(|| {
+                bulk.progress.done += 1;
+                bulk.progress
+            })
+        });
+
+        if progress.is_some_and(|progress| progress.is_complete()) {
+            self.bulk = None;
+        }
+
+        Some(Scheduled { request, progress })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse::types::ObjectTypeSelector;
+
+    const fn object(object: ObjectTypeSelector) -> DeviceObject {
+        DeviceObject::new(0, object)
+    }
+
+    const fn query(object: ObjectTypeSelector) -> Request {
+        Request::Query(DeviceObject::new(0, object))
+    }
+
+    const fn send(object: ObjectTypeSelector) -> Request {
+        Request::Send(DeviceObject::new(0, object))
+    }
+
+    fn scheduler() -> (RequestScheduler, ManualClock) {
+        let clock = ManualClock::default();
+        (RequestScheduler::new(Box::new(clock.clone())), clock)
+    }
+
+    #[test]
+    fn first_request_leaves_immediately() {
+        let (mut scheduler, _) = scheduler();
+        assert_eq!(scheduler.time_until_next(), None);
+        assert!(scheduler.enqueue(query(ObjectTypeSelector::Kit(5))));
+        assert_eq!(scheduler.time_until_next(), Some(Duration::ZERO));
+        assert_eq!(
+            scheduler.poll().map(|scheduled| scheduled.request),
+            Some(query(ObjectTypeSelector::Kit(5)))
+        );
+        assert_eq!(scheduler.poll(), None);
+    }
+
+    #[test]
+    fn requests_are_spaced_by_interval() {
+        let (mut scheduler, clock) = scheduler();
+        scheduler.set_interval(Duration::from_millis(100));
+        scheduler.enqueue(query(ObjectTypeSelector::Pattern(0)));
+        scheduler.enqueue(query(ObjectTypeSelector::Pattern(1)));
+
+        assert!(scheduler.poll().is_some());
+        assert_eq!(scheduler.poll(), None);
+        assert_eq!(
+            scheduler.time_until_next(),
+            Some(Duration::from_millis(100))
+        );
+
+        clock.advance(Duration::from_millis(60));
+        assert_eq!(scheduler.poll(), None);
+        assert_eq!(scheduler.time_until_next(), Some(Duration::from_millis(40)));
+
+        clock.advance(Duration::from_millis(40));
+        assert_eq!(
+            scheduler.poll().map(|scheduled| scheduled.request),
+            Some(query(ObjectTypeSelector::Pattern(1)))
+        );
+        assert!(scheduler.is_empty());
+    }
+
+    #[test]
+    fn duplicate_requests_are_merged() {
+        let (mut scheduler, _) = scheduler();
+        assert!(scheduler.enqueue(send(ObjectTypeSelector::KitWorkBuffer)));
+        assert!(!scheduler.enqueue(send(ObjectTypeSelector::KitWorkBuffer)));
+        assert!(scheduler.enqueue(query(ObjectTypeSelector::KitWorkBuffer)));
+        assert!(scheduler.enqueue(send(ObjectTypeSelector::SoundWorkBuffer(1))));
+        assert!(!scheduler.enqueue(send(ObjectTypeSelector::SoundWorkBuffer(1))));
+        assert_eq!(scheduler.pending(), 3);
+    }
+
+    #[test]
+    fn request_after_idle_period_is_not_delayed() {
+        let (mut scheduler, clock) = scheduler();
+        scheduler.enqueue(query(ObjectTypeSelector::Settings));
+        assert!(scheduler.poll().is_some());
+
+        clock.advance(Duration::from_millis(5000));
+        scheduler.enqueue(query(ObjectTypeSelector::Settings));
+        assert_eq!(scheduler.time_until_next(), Some(Duration::ZERO));
+    }
+
+    #[test]
+    fn bulk_transfer_reports_progress() {
+        let (mut scheduler, _) = scheduler();
+        scheduler.set_interval(Duration::ZERO);
+        scheduler.enqueue(query(ObjectTypeSelector::Settings));
+
+        let progress = scheduler.enqueue_bulk(
+            [
+                ObjectTypeSelector::Global(0),
+                ObjectTypeSelector::Global(1),
+                ObjectTypeSelector::Settings,
+            ]
+            .map(query),
+        );
+        assert_eq!(progress, Progress { done: 0, total: 3 });
+        // Settings query was already pending so it is merged.
+        assert_eq!(scheduler.pending(), 3);
+
+        let first = scheduler.poll().unwrap();
+        assert_eq!(first.request, query(ObjectTypeSelector::Settings));
+        assert_eq!(first.progress, Some(Progress { done: 1, total: 3 }));
+
+        assert_eq!(
+            scheduler.poll().unwrap().progress,
+            Some(Progress { done: 2, total: 3 })
+        );
+        assert_eq!(
+            scheduler.poll().unwrap().progress,
+            Some(Progress { done: 3, total: 3 })
+        );
+        assert_eq!(scheduler.progress(), None);
+    }
+
+    #[test]
+    fn requests_outside_of_bulk_have_no_progress() {
+        let (mut scheduler, _) = scheduler();
+        scheduler.set_interval(Duration::ZERO);
+        scheduler.enqueue_bulk([send(ObjectTypeSelector::Kit(0))]);
+        scheduler.enqueue(send(ObjectTypeSelector::Kit(1)));
+
+        assert!(scheduler.poll().unwrap().progress.is_some());
+        assert_eq!(scheduler.poll().unwrap().progress, None);
+    }
+
+    #[test]
+    fn cancelled_bulk_transfer_keeps_other_requests() {
+        let (mut scheduler, _) = scheduler();
+        scheduler.set_interval(Duration::ZERO);
+        scheduler.enqueue_bulk([0, 1, 2].map(|index| send(ObjectTypeSelector::Kit(index))));
+        scheduler.enqueue(send(ObjectTypeSelector::Settings));
+        scheduler.poll();
+
+        assert_eq!(scheduler.cancel_bulk(), 2);
+        assert_eq!(scheduler.progress(), None);
+        assert_eq!(
+            scheduler.poll().unwrap().request,
+            send(ObjectTypeSelector::Settings)
+        );
+        assert!(scheduler.poll().is_none());
+        assert_eq!(scheduler.cancel_bulk(), 0);
+    }
+
+    #[test]
+    fn cancelled_bulk_transfer_keeps_requests_queued_on_their_own() {
+        let (mut scheduler, _) = scheduler();
+        scheduler.set_interval(Duration::ZERO);
+        scheduler.enqueue(query(ObjectTypeSelector::Kit(0)));
+        scheduler.enqueue_bulk([0, 1, 2].map(|index| query(ObjectTypeSelector::Kit(index))));
+        assert!(!scheduler.enqueue(query(ObjectTypeSelector::Kit(2))));
+
+        assert_eq!(scheduler.cancel_bulk(), 1);
+        assert_eq!(
+            scheduler.poll().unwrap().request,
+            query(ObjectTypeSelector::Kit(0))
+        );
+        assert_eq!(
+            scheduler.poll().unwrap().request,
+            query(ObjectTypeSelector::Kit(2))
+        );
+        assert!(scheduler.poll().is_none());
+    }
+
+    #[test]
+    fn answered_queries_are_not_retried() {
+        let (mut scheduler, clock) = scheduler();
+        scheduler.set_timeout(Duration::from_millis(1000));
+        scheduler.enqueue(query(ObjectTypeSelector::Kit(5)));
+        scheduler.enqueue(send(ObjectTypeSelector::Kit(6)));
+        scheduler.poll();
+
+        assert_eq!(
+            scheduler.time_until_next_timeout(),
+            Some(Duration::from_millis(1000))
+        );
+        assert!(scheduler.resolve(query(ObjectTypeSelector::Kit(5))));
+        assert!(!scheduler.resolve(query(ObjectTypeSelector::Kit(5))));
+
+        clock.advance(Duration::from_millis(1000));
+        assert!(scheduler.expire().is_empty());
+        assert_eq!(scheduler.pending(), 1);
+        // Sends do not wait for a response.
+        scheduler.poll();
+        assert_eq!(scheduler.time_until_next_timeout(), None);
+    }
+
+    #[test]
+    fn unanswered_queries_are_retried_then_time_out() {
+        let (mut scheduler, clock) = scheduler();
+        scheduler.set_interval(Duration::ZERO);
+        scheduler.set_timeout(Duration::from_millis(1000));
+        scheduler.set_max_retries(1);
+        scheduler.enqueue(query(ObjectTypeSelector::PatternWorkBuffer));
+        scheduler.poll();
+
+        clock.advance(Duration::from_millis(999));
+        assert!(scheduler.expire().is_empty());
+        assert!(scheduler.is_empty());
+
+        clock.advance(Duration::from_millis(1));
+        assert!(scheduler.expire().is_empty());
+        assert_eq!(
+            scheduler.poll().map(|scheduled| scheduled.request),
+            Some(query(ObjectTypeSelector::PatternWorkBuffer))
+        );
+
+        clock.advance(Duration::from_millis(1000));
+        assert_eq!(
+            scheduler.expire(),
+            vec![query(ObjectTypeSelector::PatternWorkBuffer)]
+        );
+        assert!(scheduler.is_empty());
+        assert_eq!(scheduler.awaiting_response().count(), 0);
+    }
+
+    #[test]
+    fn inquiries_are_paced_and_time_out_after_the_last_one() {
+        let (mut scheduler, clock) = scheduler();
+        scheduler.set_interval(Duration::from_millis(100));
+        scheduler.set_timeout(Duration::from_millis(1000));
+        for device_id in 0..3 {
+            scheduler.enqueue(Request::Identify(device_id));
+        }
+
+        assert_eq!(
+            scheduler.poll().map(|scheduled| scheduled.request),
+            Some(Request::Identify(0))
+        );
+        assert_eq!(scheduler.poll(), None);
+        clock.advance(RequestScheduler::INQUIRY_INTERVAL);
+        scheduler.poll();
+        clock.advance(Duration::from_millis(1000));
+        // An inquiry is still waiting in the queue.
+        assert!(!scheduler.expire_inquiries());
+
+        scheduler.poll();
+        assert_eq!(
+            scheduler.time_until_next_timeout(),
+            Some(Duration::from_millis(1000))
+        );
+        clock.advance(Duration::from_millis(1000));
+        assert!(scheduler.expire_inquiries());
+        assert!(!scheduler.expire_inquiries());
+        assert_eq!(scheduler.time_until_next_timeout(), None);
+    }
+
+    #[test]
+    fn requests_after_an_inquiry_wait_for_the_inquiry_interval() {
+        let (mut scheduler, clock) = scheduler();
+        scheduler.enqueue(query(ObjectTypeSelector::Settings));
+        scheduler.enqueue(Request::Identify(0));
+        scheduler.enqueue(query(ObjectTypeSelector::Kit(0)));
+
+        scheduler.poll();
+        assert_eq!(scheduler.time_until_next(), Some(scheduler.interval()));
+        clock.advance(scheduler.interval());
+        assert_eq!(
+            scheduler.poll().map(|scheduled| scheduled.request),
+            Some(Request::Identify(0))
+        );
+        assert_eq!(
+            scheduler.time_until_next(),
+            Some(RequestScheduler::INQUIRY_INTERVAL)
+        );
+        clock.advance(RequestScheduler::INQUIRY_INTERVAL);
+        assert_eq!(
+            scheduler.poll().map(|scheduled| scheduled.request),
+            Some(query(ObjectTypeSelector::Kit(0)))
+        );
+    }
+
+    #[test]
+    fn cancelled_inquiries_leave_other_requests_queued() {
+        let (mut scheduler, _) = scheduler();
+        scheduler.enqueue(Request::Identify(0));
+        scheduler.enqueue(query(ObjectTypeSelector::Settings));
+        scheduler.enqueue(Request::Identify(1));
+        scheduler.poll();
+
+        assert_eq!(scheduler.cancel_inquiries(), 1);
+        assert_eq!(scheduler.peek(), Some(query(ObjectTypeSelector::Settings)));
+        assert!(!scheduler.expire_inquiries());
+        assert_eq!(scheduler.time_until_next_timeout(), None);
+    }
+
+    #[test]
+    fn warm_up_is_not_merged_with_a_query() {
+        let (mut scheduler, _) = scheduler();
+        scheduler.set_interval(Duration::ZERO);
+        let warm_up = Request::WarmUp(object(ObjectTypeSelector::Settings));
+        scheduler.enqueue(query(ObjectTypeSelector::Settings));
+        scheduler.enqueue_front(warm_up);
+        assert!(!scheduler.enqueue(query(ObjectTypeSelector::Settings)));
+
+        assert_eq!(
+            scheduler.poll().map(|scheduled| scheduled.request),
+            Some(warm_up)
+        );
+        assert_eq!(
+            scheduler.poll().map(|scheduled| scheduled.request),
+            Some(query(ObjectTypeSelector::Settings))
+        );
+
+        // Answering the warm-up leaves the query waiting for its own response.
+        assert!(scheduler.resolve(warm_up));
+        assert_eq!(
+            scheduler.awaiting_response().collect::<Vec<_>>(),
+            [&query(ObjectTypeSelector::Settings)]
+        );
+    }
+
+    #[test]
+    fn zero_timeout_disables_tracking() {
+        let (mut scheduler, clock) = scheduler();
+        scheduler.set_timeout(Duration::ZERO);
+        scheduler.enqueue(query(ObjectTypeSelector::Settings));
+        scheduler.poll();
+
+        clock.advance(Duration::from_millis(100_000));
+        assert!(scheduler.expire().is_empty());
+        assert_eq!(scheduler.awaiting_response().count(), 0);
+    }
+
+    #[test]
+    fn debounced_requests_coalesce_until_the_window_passes() {
+        let (mut scheduler, clock) = scheduler();
+        scheduler.set_interval(Duration::ZERO);
+        scheduler.set_debounce(Duration::from_millis(100));
+
+        assert!(scheduler.enqueue_debounced(send(ObjectTypeSelector::SoundWorkBuffer(0))));
+        assert_eq!(
+            scheduler.time_until_next(),
+            Some(Duration::from_millis(100))
+        );
+
+        clock.advance(Duration::from_millis(60));
+        assert_eq!(scheduler.poll(), None);
+        // Another change restarts the window.
+        assert!(!scheduler.enqueue_debounced(send(ObjectTypeSelector::SoundWorkBuffer(0))));
+        assert!(scheduler.enqueue_debounced(send(ObjectTypeSelector::SoundWorkBuffer(1))));
+
+        clock.advance(Duration::from_millis(60));
+        assert_eq!(scheduler.poll(), None);
+        assert_eq!(scheduler.time_until_next(), Some(Duration::from_millis(40)));
+
+        clock.advance(Duration::from_millis(40));
+        assert_eq!(
+            std::iter::from_fn(|| scheduler.poll())
+                .map(|scheduled| scheduled.request)
+                .collect::<Vec<_>>(),
+            vec![
+                send(ObjectTypeSelector::SoundWorkBuffer(0)),
+                send(ObjectTypeSelector::SoundWorkBuffer(1)),
+            ]
+        );
+        assert_eq!(scheduler.time_until_next(), None);
+
+        // A request which is already queued is not debounced again.
+        scheduler.set_interval(Duration::from_millis(800));
+        scheduler.enqueue(send(ObjectTypeSelector::KitWorkBuffer));
+        assert!(!scheduler.enqueue_debounced(send(ObjectTypeSelector::KitWorkBuffer)));
+    }
+
+    #[test]
+    fn request_can_jump_the_queue() {
+        let (mut scheduler, _) = scheduler();
+        scheduler.set_interval(Duration::ZERO);
+        scheduler.enqueue(query(ObjectTypeSelector::Kit(0)));
+        scheduler.enqueue(query(ObjectTypeSelector::Settings));
+        scheduler.enqueue_front(query(ObjectTypeSelector::Settings));
+
+        assert_eq!(scheduler.pending(), 2);
+        assert_eq!(
+            std::iter::from_fn(|| scheduler.poll())
+                .map(|scheduled| scheduled.request)
+                .collect::<Vec<_>>(),
+            vec![
+                query(ObjectTypeSelector::Settings),
+                query(ObjectTypeSelector::Kit(0)),
+            ]
+        );
+    }
+}
@@ -0,0 +1,748 @@
+//! A description of the parameters of every object, for building interfaces from, e.g. `describe sound filtcutoff`.
+//!
+//! Ranges are the ones listed in the api docs, defaults are read from a default project.
+
+use crate::{
+    api::{
+        global_action_type::GLOBAL_ACTION_TYPES,
+        global_enum_type::GLOBAL_ENUM_TYPES,
+        kit_action_type::KIT_ACTION_TYPES,
+        kit_element_type::{KIT_ELEMENTS_ACTION, KIT_ELEMENTS_ENUM},
+        kit_enum_type::KIT_ENUM_TYPES,
+        object_type::*,
+        pattern_action_type::PATTERN_ACTION_TYPES,
+        pattern_enum_type::PATTERN_ENUM_TYPES,
+        settings_action_type::SETTINGS_ACTION_TYPES,
+        settings_enum_type::SETTINGS_ENUM_TYPES,
+        sound_action_type::SOUND_ACTION_TYPES,
+        sound_enum_type::SOUND_ENUM_TYPES,
+        track_action_type::TRACK_ACTION_TYPES,
+        track_enum_type::TRACK_ENUM_TYPES,
+        trig_action_type::TRIG_ACTION_TYPES,
+        trig_enum_type::TRIG_ENUM_TYPES,
+    },
+    parse::types::{Number, ObjectTypeSelector, ParsedValue},
+    snapshot::getter_path,
+    value::RytmValue,
+};
+
+/// The type of the values a parameter takes.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ValueType {
+    Int,
+    Float,
+    /// One of the variants of an enum, written after the `:` of the enum type.
+    Enum,
+    Symbol,
+}
+
+impl std::fmt::Display for ValueType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Int => write!(f, "int"),
+            Self::Float => write!(f, "float"),
+            Self::Enum => write!(f, "enum"),
+            Self::Symbol => write!(f, "symbol"),
+        }
+    }
+}
+
+/// Describes a parameter with what an interface needs to offer a control for it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParameterSchema {
+    pub scope: ParameterScope,
+    /// The identifier as it is written in a command, enum types end with `:`.
+    pub identifier: String,
+    /// `None` if the type is not known, e.g. for a parameter which can not be read from a default object.
+    pub value_type: Option<ValueType>,
+    pub range: Option<ParameterRange>,
+    /// The value of the parameter in a default object.
+    pub default: Option<RytmValue>,
+    /// Whether the parameter may be locked on a trig with `plockset`.
+    pub plockable: bool,
+}
+
+impl ParameterSchema {
+    fn new(scope: ParameterScope, identifier: &str, is_enum: bool) -> Self {
+        let range = scope.range(identifier);
+        let plockable = is_plockable(scope, identifier);
+        let value_type = if is_enum {
+            Some(ValueType::Enum)
+        } else {
+            range.map(|range| match range.min {
+                Number::Int(_) => ValueType::Int,
+                Number::Float(_) => ValueType::Float,
+            })
+        };
+        // Kit elements are written without the `:` even if they are enums.
+        let identifier = if is_enum && scope != ParameterScope::KitElement {
+            format!("{identifier}:")
+        } else {
+            identifier.to_owned()
+        };
+
+        Self {
+            scope,
+            identifier,
+            value_type,
+            range,
+            default: None,
+            plockable,
+        }
+    }
+
+    /// Sets the default value, the type of a parameter without a range is taken from it.
+    pub fn set_default(&mut self, default: RytmValue) {
+        self.value_type = self.value_type.or(Some(match default {
+            RytmValue::Int(_) => ValueType::Int,
+            RytmValue::Float(_) => ValueType::Float,
+            RytmValue::Symbol(_) => ValueType::Symbol,
+        }));
+        self.default = Some(default);
+    }
+
+    /// The `get` command which reads the parameter from the first of its kind in the work buffer, without the selector.
+    ///
+    /// Returns `None` for a parameter which has no getter.
+    pub fn default_getter(&self) -> Option<Vec<RytmValue>> {
+        let path = getter_path(self.scope, &self.identifier)?;
+        let first = RytmValue::Int(0);
+        let mut getter = match self.scope {
+            ParameterScope::Pattern => vec![PATTERN_WORK_BUFFER.into()],
+            ParameterScope::Track => vec![PATTERN_WORK_BUFFER.into(), first],
+            ParameterScope::Trig | ParameterScope::ParameterLock => {
+                vec![PATTERN_WORK_BUFFER.into(), first.clone(), first]
+            }
+            ParameterScope::Kit => vec![KIT_WORK_BUFFER.into()],
+            ParameterScope::KitElement => {
+                return Some(vec![
+                    KIT_WORK_BUFFER.into(),
+                    self.identifier.as_str().into(),
+                    first,
+                ])
+            }
+            ParameterScope::Sound => vec![SOUND_WORK_BUFFER.into(), first],
+            ParameterScope::Global => vec![GLOBAL_WORK_BUFFER.into()],
+            ParameterScope::Settings => vec![SETTINGS.into()],
+        };
+        getter.extend(path);
+        Some(getter)
+    }
+
+    /// Checks if the parameter is the one an identifier names, the `:` of enum types may be left out.
+    pub fn is_named(&self, identifier: &str) -> bool {
+        identifier_name(&self.identifier) == identifier_name(identifier)
+    }
+}
+
+/// Describes every parameter of an object type without its defaults, which are read from a default object.
+///
+/// Work buffers have the parameters of the objects they hold. Tracks and trigs are described with patterns and kit elements with kits.
+/// Returns `None` for an unknown object type.
+pub fn parameters(object_type: &str) -> Option<Vec<ParameterSchema>> {
+    let scopes: &[ParameterScope] = match object_type {
+        PATTERN | PATTERN_WORK_BUFFER => &[
+            ParameterScope::Pattern,
+            ParameterScope::Track,
+            ParameterScope::Trig,
+        ],
+        KIT | KIT_WORK_BUFFER => &[ParameterScope::Kit, ParameterScope::KitElement],
+        SOUND | SOUND_WORK_BUFFER => &[ParameterScope::Sound],
+        GLOBAL | GLOBAL_WORK_BUFFER => &[ParameterScope::Global],
+        SETTINGS => &[ParameterScope::Settings],
+        _ => return None,
+    };
+
+    Some(
+        scopes
+            .iter()
+            .flat_map(|scope| {
+                let (action_types, enum_types) = scope.identifiers();
+                action_types
+                    .iter()
+                    .map(|action_type| ParameterSchema::new(*scope, action_type, false))
+                    .chain(
+                        enum_types
+                            .iter()
+                            .map(|enum_type| ParameterSchema::new(*scope, enum_type, true)),
+                    )
+            })
+            .collect(),
+    )
+}
+
+/// The legal values of a parameter, the bounds are floats for float parameters.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct ParameterRange {
+    pub min: Number,
+    pub max: Number,
+}
+
+impl ParameterRange {
+    const fn int(min: isize, max: isize) -> Self {
+        Self {
+            min: Number::Int(min),
+            max: Number::Int(max),
+        }
+    }
+
+    const fn float(min: f64, max: f64) -> Self {
+        Self {
+            min: Number::Float(min),
+            max: Number::Float(max),
+        }
+    }
+
+    /// Clamps a value into the range, integer parameters are rounded.
+    pub fn clamp(&self, value: f64) -> Number {
+        let value = value.clamp(self.min.get_float(), self.max.get_float());
+        match self.min {
+            Number::Int(_) => Number::Int(value.round() as isize),
+            Number::Float(_) => Number::Float(value),
+        }
+    }
+}
+
+/// Where a parameter lives, the same identifier may mean different parameters in different places.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ParameterScope {
+    Pattern,
+    Track,
+    Trig,
+    /// A parameter lock of a trig, which locks a sound or a kit parameter.
+    ParameterLock,
+    Kit,
+    KitElement,
+    Sound,
+    Global,
+    Settings,
+}
+
+impl std::fmt::Display for ParameterScope {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Pattern => write!(f, "pattern"),
+            Self::Track => write!(f, "track"),
+            Self::Trig => write!(f, "trig"),
+            Self::ParameterLock => write!(f, "plock"),
+            Self::Kit => write!(f, "kit"),
+            Self::KitElement => write!(f, "kitelement"),
+            Self::Sound => write!(f, "sound"),
+            Self::Global => write!(f, "global"),
+            Self::Settings => write!(f, "settings"),
+        }
+    }
+}
+
+impl ParameterScope {
+    /// The scope and the identifier of the parameter which the tokens of a command address.
+    pub fn of(tokens: &[ParsedValue]) -> Option<(Self, &str)> {
+        let scope = Self::of_level(tokens)?;
+        if scope == Self::KitElement {
+            // Kit elements are addressed by the element, they do not have an identifier.
+            let Some(ParsedValue::Element(element)) = tokens.get(1) else {
+                return None;
+            };
+            return Some((scope, element.as_str()));
+        }
+
+        tokens
+            .iter()
+            .rev()
+            .find_map(|token| match token {
+                ParsedValue::Identifier(identifier) => Some(identifier.as_str()),
+                _ => None,
+            })
+            .map(|identifier| (scope, identifier))
+    }
+
+    /// The scope of the level of an object which the tokens of a command address, they may end before the parameter.
+    pub fn of_level(tokens: &[ParsedValue]) -> Option<Self> {
+        let Some(ParsedValue::ObjectType(object)) = tokens.first() else {
+            return None;
+        };
+        let has = |matches: fn(&ParsedValue) -> bool| tokens.iter().any(matches);
+
+        Some(match object {
+            ObjectTypeSelector::Pattern(_) | ObjectTypeSelector::PatternWorkBuffer => {
+                if has(|token| matches!(token, ParsedValue::PlockOperation(_))) {
+                    Self::ParameterLock
+                } else if has(|token| matches!(token, ParsedValue::TrigIndex(_))) {
+                    Self::Trig
+                } else if has(|token| matches!(token, ParsedValue::TrackIndex(_))) {
+                    Self::Track
+                } else {
+                    Self::Pattern
+                }
+            }
+            ObjectTypeSelector::Kit(_) | ObjectTypeSelector::KitWorkBuffer => {
+                if has(|token| matches!(token, ParsedValue::SoundIndex(_))) {
+                    Self::Sound
+                } else if let Some(ParsedValue::Element(_)) = tokens.get(1) {
+                    Self::KitElement
+                } else {
+                    Self::Kit
+                }
+            }
+            ObjectTypeSelector::Sound(_) | ObjectTypeSelector::SoundWorkBuffer(_) => Self::Sound,
+            ObjectTypeSelector::Global(_) | ObjectTypeSelector::GlobalWorkBuffer => Self::Global,
+            ObjectTypeSelector::Settings => Self::Settings,
+        })
+    }
+
+    /// The identifiers and the enum types of the parameters in the scope.
+    ///
+    /// Parameter locks are described in the scopes of the sound and kit parameters they lock.
+    pub const fn identifiers(&self) -> (&'static [&'static str], &'static [&'static str]) {
+        match self {
+            Self::Pattern => (PATTERN_ACTION_TYPES, PATTERN_ENUM_TYPES),
+            Self::Track => (TRACK_ACTION_TYPES, TRACK_ENUM_TYPES),
+            Self::Trig => (TRIG_ACTION_TYPES, TRIG_ENUM_TYPES),
+            Self::ParameterLock => (&[], &[]),
+            Self::Kit => (KIT_ACTION_TYPES, KIT_ENUM_TYPES),
+            Self::KitElement => (KIT_ELEMENTS_ACTION, KIT_ELEMENTS_ENUM),
+            Self::Sound => (SOUND_ACTION_TYPES, SOUND_ENUM_TYPES),
+            Self::Global => (GLOBAL_ACTION_TYPES, GLOBAL_ENUM_TYPES),
+            Self::Settings => (SETTINGS_ACTION_TYPES, SETTINGS_ENUM_TYPES),
+        }
+    }
+
+    /// The range of a settable numeric parameter, `None` if the parameter is not known to have one.
+    pub fn range(&self, identifier: &str) -> Option<ParameterRange> {
+        match self {
+            Self::Pattern => pattern_range(identifier),
+            Self::Track => track_range(identifier),
+            Self::Trig => trig_range(identifier),
+            Self::ParameterLock => sound_range(identifier).or_else(|| kit_range(identifier)),
+            Self::Kit => kit_range(identifier),
+            Self::KitElement => kit_element_range(identifier),
+            Self::Sound => sound_range(identifier),
+            Self::Global => global_range(identifier),
+            Self::Settings => settings_range(identifier),
+        }
+    }
+}
+
+fn pattern_range(identifier: &str) -> Option<ParameterRange> {
+    use crate::api::pattern_action_type::*;
+    Some(match identifier {
+        MASTER_LENGTH | MASTER_CHANGE => ParameterRange::int(1, 1024),
+        KIT_NUMBER | GLOBAL_QUANTIZE => ParameterRange::int(0, 127),
+        SWING_AMOUNT => ParameterRange::int(50, 80),
+        BPM => ParameterRange::float(30.0, 300.0),
+        _ => return None,
+    })
+}
+
+fn track_range(identifier: &str) -> Option<ParameterRange> {
+    use crate::api::track_action_type::*;
+    Some(match identifier {
+        DEF_TRIG_NOTE | DEF_TRIG_VELOCITY | QUANTIZE_AMOUNT => ParameterRange::int(0, 127),
+        DEF_TRIG_PROB => ParameterRange::int(0, 100),
+        NUMBER_OF_STEPS => ParameterRange::int(1, 64),
+        SENDS_MIDI | EUCLIDEAN_MODE => ParameterRange::int(0, 1),
+        EUCLIDEAN_PL1 | EUCLIDEAN_PL2 | EUCLIDEAN_RO1 | EUCLIDEAN_RO2 | EUCLIDEAN_TRO => {
+            ParameterRange::int(0, 63)
+        }
+        _ => return None,
+    })
+}
+
+fn trig_range(identifier: &str) -> Option<ParameterRange> {
+    use crate::api::trig_action_type::*;
+    Some(match identifier {
+        ENABLE | RETRIG | MUTE | ACCENT | SWING | SLIDE => ParameterRange::int(0, 1),
+        NOTE => ParameterRange::int(36, 84),
+        VELOCITY => ParameterRange::int(1, 127),
+        RETRIG_VELOCITY_OFFSET => ParameterRange::int(-128, 127),
+        SOUND_LOCK => ParameterRange::int(0, 127),
+        _ => return None,
+    })
+}
+
+fn kit_range(identifier: &str) -> Option<ParameterRange> {
+    use crate::api::kit_action_type::*;
+    Some(match identifier {
+        CONTROL_IN_1_MOD_AMT | CONTROL_IN_2_MOD_AMT => ParameterRange::int(-128, 127),
+        FX_DELAY_TIME
+        | FX_DELAY_HPF
+        | FX_DELAY_LPF
+        | FX_DELAY_REVERB_SEND
+        | FX_DELAY_VOLUME
+        | FX_REVERB_PRE_DELAY
+        | FX_REVERB_DECAY
+        | FX_REVERB_FREQ
+        | FX_REVERB_GAIN
+        | FX_REVERB_HPF
+        | FX_REVERB_LPF
+        | FX_REVERB_VOLUME
+        | FX_COMP_THRESHOLD
+        | FX_COMP_GAIN
+        | FX_COMP_MIX
+        | FX_COMP_VOLUME
+        | FX_LFO_START_PHASE_OR_SLEW
+        | FX_DISTORTION_DELAY_OVERDRIVE
+        | FX_DISTORTION_AMOUNT => ParameterRange::int(0, 127),
+        FX_DELAY_PING_PONG | FX_DISTORTION_DELAY_POST | FX_DISTORTION_REVERB_POST => {
+            ParameterRange::int(0, 1)
+        }
+        FX_DELAY_STEREO_WIDTH | FX_LFO_SPEED | FX_LFO_FADE | FX_DISTORTION_SYMMETRY => {
+            ParameterRange::int(-64, 63)
+        }
+        FX_DELAY_FEEDBACK => ParameterRange::int(0, 198),
+        FX_LFO_DEPTH => ParameterRange::float(-128.0, 127.99),
+        _ => return None,
+    })
+}
+
+fn kit_element_range(element: &str) -> Option<ParameterRange> {
+    use crate::api::kit_element_type::*;
+    Some(match element {
+        TRACK_LEVEL => ParameterRange::int(0, 127),
+        TRACK_RETRIG_VEL_OFFSET => ParameterRange::int(-128, 127),
+        TRACK_RETRIG_ALWAYS_ON => ParameterRange::int(0, 1),
+        _ => return None,
+    })
+}
+
+fn sound_range(identifier: &str) -> Option<ParameterRange> {
+    use crate::api::sound_action_type::*;
+    Some(match identifier {
+        KIT_NUMBER
+        | ACCENT_LEVEL
+        | AMP_ATTACK
+        | AMP_HOLD
+        | AMP_DECAY
+        | AMP_OVERDRIVE
+        | AMP_DELAY_SEND
+        | AMP_REVERB_SEND
+        | AMP_VOLUME
+        | FILT_ATTACK
+        | FILT_HOLD
+        | FILT_DECAY
+        | FILT_RELEASE
+        | FILT_CUTOFF
+        | FILT_RESONANCE
+        | LFO_START_PHASE_OR_SLEW
+        | SAMP_NUMBER
+        | SAMP_BIT_REDUCTION
+        | SAMP_VOLUME => ParameterRange::int(0, 127),
+        AMP_PAN | FILT_ENVELOPE_AMOUNT | LFO_SPEED | LFO_FADE | SAMP_FINE_TUNE => {
+            ParameterRange::int(-64, 63)
+        }
+        LFO_DEPTH => ParameterRange::float(-128.0, 127.99),
+        SAMP_TUNE => ParameterRange::int(-24, 24),
+        SAMP_START | SAMP_END => ParameterRange::float(0.0, 120.0),
+        SAMP_LOOP_FLAG | ENV_RESET_FILTER | VELOCITY_TO_VOLUME | LEGACY_FX_SEND => {
+            ParameterRange::int(0, 1)
+        }
+        VEL_MOD_AMT | AT_MOD_AMT => ParameterRange::int(-127, 128),
+        _ => return None,
+    })
+}
+
+fn global_range(identifier: &str) -> Option<ParameterRange> {
+    use crate::api::global_action_type::*;
+    Some(match identifier {
+        KIT_RELOAD_ON_CHANGE
+        | QUANTIZE_LIVE_REC
+        | AUTO_TRACK_SWITCH
+        | CLOCK_RECEIVE
+        | CLOCK_SEND
+        | TRANSPORT_RECEIVE
+        | TRANSPORT_SEND
+        | PROGRAM_CHANGE_RECEIVE
+        | PROGRAM_CHANGE_SEND
+        | RECEIVE_NOTES
+        | RECEIVE_CC_NRPN
+        | METRONOME_ACTIVE => ParameterRange::int(0, 1),
+        ROUTE_TO_MAIN | SEND_TO_FX => ParameterRange::int(0, 11),
+        METRONOME_PRE_ROLL_BARS => ParameterRange::int(0, 16),
+        METRONOME_VOLUME => ParameterRange::int(0, 127),
+        _ => return None,
+    })
+}
+
+fn settings_range(identifier: &str) -> Option<ParameterRange> {
+    use crate::api::settings_action_type::*;
+    Some(match identifier {
+        BPM_PROJECT => ParameterRange::float(30.0, 300.0),
+        SELECTED_TRACK | MUTE | UNMUTE => ParameterRange::int(0, 11),
+        SELECTED_PAGE => ParameterRange::int(0, 3),
+        FIXED_VELOCITY_ENABLE | SAMPLE_RECORDER_MONITOR_ENABLE => ParameterRange::int(0, 1),
+        FIXED_VELOCITY_AMOUNT | SAMPLE_RECORDER_THR => ParameterRange::int(0, 127),
+        _ => return None,
+    })
+}
+
+/// Checks if a parameter can be locked on a trig, these are the ones `plockset` handles.
+fn is_plockable(scope: ParameterScope, identifier: &str) -> bool {
+    match scope {
+        ParameterScope::Kit => {
+            use crate::api::{kit_action_type::*, kit_enum_type::*};
+            matches!(
+                identifier,
+                FX_DELAY_TIME
+                    | FX_DELAY_PING_PONG
+                    | FX_DELAY_STEREO_WIDTH
+                    | FX_DELAY_FEEDBACK
+                    | FX_DELAY_HPF
+                    | FX_DELAY_LPF
+                    | FX_DELAY_REVERB_SEND
+                    | FX_DELAY_VOLUME
+                    | FX_REVERB_PRE_DELAY
+                    | FX_REVERB_DECAY
+                    | FX_REVERB_FREQ
+                    | FX_REVERB_GAIN
+                    | FX_REVERB_HPF
+                    | FX_REVERB_LPF
+                    | FX_REVERB_VOLUME
+                    | FX_COMP_THRESHOLD
+                    | FX_COMP_GAIN
+                    | FX_COMP_MIX
+                    | FX_COMP_VOLUME
+                    | FX_LFO_SPEED
+                    | FX_LFO_FADE
+                    | FX_LFO_START_PHASE_OR_SLEW
+                    | FX_LFO_DEPTH
+                    | FX_COMP_ATTACK
+                    | FX_COMP_RELEASE
+                    | FX_COMP_RATIO
+                    | FX_COMP_SIDE_CHAIN_EQ
+                    | FX_LFO_DESTINATION
+            )
+        }
+        ParameterScope::Sound => {
+            use crate::api::{sound_action_type::*, sound_enum_type::*};
+            matches!(
+                identifier,
+                AMP_ATTACK
+                    | AMP_HOLD
+                    | AMP_DECAY
+                    | AMP_OVERDRIVE
+                    | AMP_DELAY_SEND
+                    | AMP_REVERB_SEND
+                    | AMP_PAN
+                    | AMP_VOLUME
+                    | FILT_ATTACK
+                    | FILT_HOLD
+                    | FILT_DECAY
+                    | FILT_RELEASE
+                    | FILT_CUTOFF
+                    | FILT_RESONANCE
+                    | FILT_ENVELOPE_AMOUNT
+                    | LFO_SPEED
+                    | LFO_FADE
+                    | LFO_START_PHASE_OR_SLEW
+                    | LFO_DEPTH
+                    | SAMP_TUNE
+                    | SAMP_FINE_TUNE
+                    | SAMP_NUMBER
+                    | SAMP_BIT_REDUCTION
+                    | SAMP_START
+                    | SAMP_END
+                    | SAMP_LOOP_FLAG
+                    | SAMP_VOLUME
+                    | LFO_DESTINATION
+                    | FILTER_TYPE
+                    | LFO_MULTIPLIER
+                    | LFO_WAVEFORM
+                    | LFO_MODE
+            )
+        }
+        _ => false,
+    }
+}
+
+/// The name of an identifier without the `:` of enum types.
+fn identifier_name(identifier: &str) -> &str {
+    identifier.strip_suffix(':').unwrap_or(identifier)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{types::CommandType, value::RytmValueList, RytmObject};
+    use rytm_rs::RytmProject;
+
+    #[test]
+    fn scope_of_the_addressed_parameter() {
+        let tokens = [
+            ParsedValue::ObjectType(ObjectTypeSelector::Pattern(0)),
+            ParsedValue::TrackIndex(1),
+            ParsedValue::TrigIndex(2),
+            ParsedValue::Identifier("vel".into()),
+        ];
+        assert_eq!(
+            ParameterScope::of(&tokens),
+            Some((ParameterScope::Trig, "vel"))
+        );
+
+        let tokens = [
+            ParsedValue::ObjectType(ObjectTypeSelector::KitWorkBuffer),
+            ParsedValue::Element("tracklevel".into()),
+            ParsedValue::ElementIndex(3),
+        ];
+        assert_eq!(
+            ParameterScope::of(&tokens),
+            Some((ParameterScope::KitElement, "tracklevel"))
+        );
+
+        let tokens = [
+            ParsedValue::ObjectType(ObjectTypeSelector::Kit(0)),
+            ParsedValue::Element("sound".into()),
+            ParsedValue::SoundIndex(3),
+            ParsedValue::Identifier("filtcutoff".into()),
+        ];
+        assert_eq!(
+            ParameterScope::of(&tokens),
+            Some((ParameterScope::Sound, "filtcutoff"))
+        );
+    }
+
+    #[test]
+    fn parameters_of_an_object_type() {
+        let sound = parameters("sound_wb").unwrap();
+        let cutoff = sound
+            .iter()
+            .find(|parameter| parameter.is_named("filtcutoff"))
+            .unwrap();
+        assert_eq!(cutoff.value_type, Some(ValueType::Int));
+        assert_eq!(cutoff.range, Some(ParameterRange::int(0, 127)));
+        assert!(cutoff.plockable);
+        let filter_type = sound
+            .iter()
+            .find(|parameter| parameter.is_named("filtertype"))
+            .unwrap();
+        assert_eq!(filter_type.identifier, "filtertype:");
+        assert_eq!(filter_type.value_type, Some(ValueType::Enum));
+        assert!(filter_type.plockable);
+
+        let pattern = parameters("pattern").unwrap();
+        assert!(pattern
+            .iter()
+            .any(|parameter| parameter.scope == ParameterScope::Trig && parameter.is_named("vel")));
+        let kit = parameters("kit").unwrap();
+        let retrig_rate = kit
+            .iter()
+            .find(|parameter| {
+                parameter.scope == ParameterScope::KitElement
+                    && parameter.is_named("trackretrigrate")
+            })
+            .unwrap();
+        assert_eq!(retrig_rate.identifier, "trackretrigrate");
+        assert_eq!(
+            retrig_rate.default_getter(),
+            Some(vec![
+                "kit_wb".into(),
+                "trackretrigrate".into(),
+                RytmValue::Int(0)
+            ])
+        );
+        let velocity_target = sound
+            .iter()
+            .find(|parameter| parameter.is_named("velmodtarget"))
+            .unwrap();
+        assert_eq!(
+            velocity_target.default_getter(),
+            Some(vec![
+                "sound_wb".into(),
+                RytmValue::Int(0),
+                "velmodtarget:0".into()
+            ])
+        );
+        assert!(parameters("project").is_none());
+
+        let mut name = parameters("settings")
+            .unwrap()
+            .into_iter()
+            .find(|parameter| parameter.is_named("version"))
+            .unwrap();
+        assert_eq!(name.value_type, None);
+        name.set_default(RytmValue::Int(1));
+        assert_eq!(name.value_type, Some(ValueType::Int));
+    }
+
+    #[test]
+    fn describe_reads_defaults_from_a_default_object() {
+        let rytm = RytmObject::new(RytmProject::try_default().unwrap());
+        // The twin is not where the defaults come from.
+        rytm.command(
+            CommandType::Set,
+            RytmValueList::from(vec![
+                "sound_wb".into(),
+                0_isize.into(),
+                "amppan".into(),
+                10_isize.into(),
+            ]),
+        )
+        .unwrap();
+
+        let parameters = rytm
+            .describe(RytmValueList::from(vec!["sound".into(), "amppan".into()]))
+            .unwrap();
+        let [pan] = parameters.as_slice() else {
+            panic!("A single parameter should be described.");
+        };
+        assert_eq!(pan.scope, ParameterScope::Sound);
+        assert_eq!(pan.value_type, Some(ValueType::Int));
+        assert_eq!(
+            (pan.range.unwrap().min, pan.range.unwrap().max),
+            (Number::Int(-64), Number::Int(63))
+        );
+        assert!(pan.default.is_some());
+        assert_ne!(pan.default, Some(RytmValue::Int(10)));
+        assert!(pan.plockable);
+
+        let parameters = rytm
+            .describe(RytmValueList::from(vec!["pattern_wb".into()]))
+            .unwrap();
+        assert!(parameters
+            .iter()
+            .any(|parameter| parameter.scope == ParameterScope::Trig));
+        let root_note = parameters
+            .iter()
+            .find(|parameter| {
+                parameter.scope == ParameterScope::Track && parameter.is_named("rootnote")
+            })
+            .unwrap();
+        assert_eq!(root_note.identifier, "rootnote:");
+        assert_eq!(root_note.value_type, Some(ValueType::Enum));
+        assert!(root_note.default.is_some());
+
+        // Every parameter with a getter has a default, the first value of a getter which reads many.
+        let parameters = rytm
+            .describe(RytmValueList::from(vec!["sound".into()]))
+            .unwrap();
+        let described = |identifier: &str| {
+            parameters
+                .iter()
+                .find(|parameter| parameter.is_named(identifier))
+                .unwrap()
+        };
+        assert!(described("velmodamt").default.is_some());
+        assert!(described("atmodtarget").default.is_some());
+        assert_eq!(described("iswb").default, None);
+        for parameter in ["kit", "global", "settings"]
+            .into_iter()
+            .flat_map(|object_type| {
+                rytm.describe(RytmValueList::from(vec![object_type.into()]))
+                    .unwrap()
+            })
+        {
+            assert_eq!(
+                parameter.default.is_some(),
+                parameter.default_getter().is_some(),
+                "{} should have a default only if it has a getter.",
+                parameter.identifier
+            );
+        }
+
+        assert!(rytm
+            .describe(RytmValueList::from(vec!["sound".into(), "cutoff".into()]))
+            .is_err());
+        assert!(rytm
+            .describe(RytmValueList::from(vec!["project".into()]))
+            .is_err());
+    }
+}
@@ -0,0 +1,596 @@
+//! Snapshots of objects made of the values of their getters, for comparing objects parameter by parameter.
+
+use crate::{
+    api::{
+        global_action_type::{self, GLOBAL_ACTION_TYPES},
+        global_enum_type::{self, GLOBAL_ENUM_TYPES},
+        kit_action_type::{self, KIT_ACTION_TYPES},
+        kit_element_type::{KIT_ELEMENTS_ACTION, KIT_ELEMENTS_ENUM, SOUND},
+        kit_enum_type::{self, KIT_ENUM_TYPES},
+        pattern_action_type::PATTERN_ACTION_TYPES,
+        pattern_enum_type::PATTERN_ENUM_TYPES,
+        settings_action_type::{self, SETTINGS_ACTION_TYPES},
+        settings_enum_type::SETTINGS_ENUM_TYPES,
+        sound_action_type::{self, SOUND_ACTION_TYPES},
+        sound_enum_type::{self, SOUND_ENUM_TYPES},
+        track_action_type::{self, TRACK_ACTION_TYPES},
+        track_enum_type::TRACK_ENUM_TYPES,
+        trig_action_type::TRIG_ACTION_TYPES,
+        trig_enum_type::TRIG_ENUM_TYPES,
+    },
+    parse::types::ObjectTypeSelector,
+    schema::ParameterScope,
+    value::RytmValue,
+};
+use std::collections::HashMap;
+
+const TRACK_COUNT: usize = 13;
+const TRIG_COUNT: usize = 64;
+const KIT_SOUND_COUNT: usize = 12;
+const MOD_SLOT_COUNT: usize = 4;
+
+/// The getters of a level of an object.
+struct Getters {
+    action_types: &'static [&'static str],
+    enum_types: &'static [&'static str],
+    /// Identifiers and enums which are listed for the level but have no getter.
+    unreadable: &'static [&'static str],
+    /// Getters which read one of many values and the number of values they read.
+    ///
+    /// An identifier is followed by the index of the value, e.g. `velmodamt 2`, an enum takes it as its value, e.g. `velmodtarget:2`.
+    indexed: &'static [(&'static str, usize)],
+}
+
+const PATTERN_GETTERS: Getters = Getters {
+    action_types: PATTERN_ACTION_TYPES,
+    enum_types: PATTERN_ENUM_TYPES,
+    unreadable: &[],
+    indexed: &[],
+};
+
+const TRACK_GETTERS: Getters = Getters {
+    action_types: TRACK_ACTION_TYPES,
+    enum_types: TRACK_ENUM_TYPES,
+    unreadable: &[track_action_type::IS_WORK_BUFFER],
+    indexed: &[],
+};
+
+const TRIG_GETTERS: Getters = Getters {
+    action_types: TRIG_ACTION_TYPES,
+    enum_types: TRIG_ENUM_TYPES,
+    unreadable: &[],
+    indexed: &[],
+};
+
+const KIT_GETTERS: Getters = Getters {
+    action_types: KIT_ACTION_TYPES,
+    enum_types: KIT_ENUM_TYPES,
+    // Only a setter, the value is read with the delay time.
+    unreadable: &[kit_enum_type::FX_DELAY_TIME_ON_THE_GRID],
+    indexed: &[
+        (kit_action_type::CONTROL_IN_1_MOD_AMT, MOD_SLOT_COUNT),
+        (kit_action_type::CONTROL_IN_2_MOD_AMT, MOD_SLOT_COUNT),
+        (kit_enum_type::CONTROL_IN_1_MOD_TARGET, MOD_SLOT_COUNT),
+        (kit_enum_type::CONTROL_IN_2_MOD_TARGET, MOD_SLOT_COUNT),
+    ],
+};
+
+const SOUND_GETTERS: Getters = Getters {
+    action_types: SOUND_ACTION_TYPES,
+    enum_types: SOUND_ENUM_TYPES,
+    unreadable: &[
+        sound_action_type::VERSION,
+        sound_action_type::INDEX,
+        sound_action_type::IS_POOL,
+        sound_action_type::IS_KIT,
+        sound_action_type::IS_WORK_BUFFER,
+        sound_action_type::KIT_NUMBER,
+        sound_action_type::SOUND_TYPE,
+        sound_enum_type::MACHINE_PARAMETERS,
+    ],
+    indexed: &[
+        (sound_action_type::VEL_MOD_AMT, MOD_SLOT_COUNT),
+        (sound_action_type::AT_MOD_AMT, MOD_SLOT_COUNT),
+        (sound_enum_type::VELOCITY_MOD_TARGET, MOD_SLOT_COUNT),
+        (sound_enum_type::AFTER_TOUCH_MOD_TARGET, MOD_SLOT_COUNT),
+    ],
+};
+
+const GLOBAL_GETTERS: Getters = Getters {
+    action_types: GLOBAL_ACTION_TYPES,
+    enum_types: GLOBAL_ENUM_TYPES,
+    unreadable: &[],
+    indexed: &[
+        (global_action_type::ROUTE_TO_MAIN, KIT_SOUND_COUNT),
+        (global_action_type::SEND_TO_FX, KIT_SOUND_COUNT),
+        (global_enum_type::TRACK_CHANNELS, KIT_SOUND_COUNT),
+    ],
+};
+
+const SETTINGS_GETTERS: Getters = Getters {
+    action_types: SETTINGS_ACTION_TYPES,
+    enum_types: SETTINGS_ENUM_TYPES,
+    unreadable: &[settings_action_type::VERSION, settings_action_type::UNMUTE],
+    indexed: &[(settings_action_type::MUTE, KIT_SOUND_COUNT)],
+};
+
+/// A parameter of an object and its value.
+///
+/// The path is what follows the object type and the index in a `get` command, e.g. `3 12 velocity` for a trig of a pattern.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Parameter {
+    pub path: Vec<RytmValue>,
+    pub value: RytmValue,
+}
+
+/// A parameter which has different values in two snapshots of the same object.
+///
+/// The value is `None` on the side where the parameter could not be read.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Mismatch {
+    pub path: Vec<RytmValue>,
+    pub local: Option<RytmValue>,
+    pub device: Option<RytmValue>,
+}
+
+/// Paths of every getter of an object type, in the order they are compared.
+///
+/// Getters which read one of many values are listed once for every value, e.g. `velmodamt 0` to `velmodamt 3`.
+pub fn parameter_paths(object: ObjectTypeSelector) -> Vec<Vec<RytmValue>> {
+    let mut paths = Vec::new();
+    match object {
+        ObjectTypeSelector::Pattern(_) | ObjectTypeSelector::PatternWorkBuffer => {
+            push_getters(&mut paths, &[], &PATTERN_GETTERS);
+            for track in 0..TRACK_COUNT {
+                let track_prefix = [index_value(track)];
+                push_getters(&mut paths, &track_prefix, &TRACK_GETTERS);
+                for trig in 0..TRIG_COUNT {
+                    let trig_prefix = [index_value(track), index_value(trig)];
+                    push_getters(&mut paths, &trig_prefix, &TRIG_GETTERS);
+                }
+            }
+        }
+        ObjectTypeSelector::Kit(_) | ObjectTypeSelector::KitWorkBuffer => {
+            push_kit_getters(&mut paths);
+            for sound in 0..KIT_SOUND_COUNT {
+                let sound_prefix = [SOUND.into(), index_value(sound)];
+                push_getters(&mut paths, &sound_prefix, &SOUND_GETTERS);
+            }
+        }
+        ObjectTypeSelector::Sound(_) | ObjectTypeSelector::SoundWorkBuffer(_) => {
+            push_getters(&mut paths, &[], &SOUND_GETTERS);
+        }
+        ObjectTypeSelector::Global(_) | ObjectTypeSelector::GlobalWorkBuffer => {
+            push_getters(&mut paths, &[], &GLOBAL_GETTERS);
+        }
+        ObjectTypeSelector::Settings => {
+            push_getters(&mut paths, &[], &SETTINGS_GETTERS);
+        }
+    }
+    paths
+}
+
+/// Paths of the getters of a single level of an object, the ones a `dump` reads.
+///
+/// The scope is the part of the path which names the level, e.g. `3` for a track of a pattern, `3 12` for a trig or `sound 3` for a sound of a kit.
+/// The getters of the object itself are returned for an empty scope, without the levels nested in it except the kit elements of a kit.
+/// Returns `None` if the scope does not name a level of the object.
+pub fn level_paths(object: ObjectTypeSelector, scope: &[RytmValue]) -> Option<Vec<Vec<RytmValue>>> {
+    let mut paths = Vec::new();
+    match (object, scope) {
+        (ObjectTypeSelector::Pattern(_) | ObjectTypeSelector::PatternWorkBuffer, []) => {
+            push_getters(&mut paths, &[], &PATTERN_GETTERS);
+        }
+        (
+            ObjectTypeSelector::Pattern(_) | ObjectTypeSelector::PatternWorkBuffer,
+            [RytmValue::Int(_)],
+        ) => {
+            push_getters(&mut paths, scope, &TRACK_GETTERS);
+        }
+        (
+            ObjectTypeSelector::Pattern(_) | ObjectTypeSelector::PatternWorkBuffer,
+            [RytmValue::Int(_), RytmValue::Int(_)],
+        ) => {
+            push_getters(&mut paths, scope, &TRIG_GETTERS);
+        }
+        (ObjectTypeSelector::Kit(_) | ObjectTypeSelector::KitWorkBuffer, []) => {
+            push_kit_getters(&mut paths);
+        }
+        (
+            ObjectTypeSelector::Kit(_) | ObjectTypeSelector::KitWorkBuffer,
+            [RytmValue::Symbol(element), RytmValue::Int(_)],
+        ) if element == SOUND => {
+            push_getters(&mut paths, scope, &SOUND_GETTERS);
+        }
+        (ObjectTypeSelector::Sound(_) | ObjectTypeSelector::SoundWorkBuffer(_), []) => {
+            push_getters(&mut paths, &[], &SOUND_GETTERS);
+        }
+        (ObjectTypeSelector::Global(_) | ObjectTypeSelector::GlobalWorkBuffer, []) => {
+            push_getters(&mut paths, &[], &GLOBAL_GETTERS);
+        }
+        (ObjectTypeSelector::Settings, []) => {
+            push_getters(&mut paths, &[], &SETTINGS_GETTERS);
+        }
+        _ => return None,
+    }
+    Some(paths)
+}
+
+/// The path which reads a parameter of a level, the identifier is written as in a command with enum types ending with `:`.
+///
+/// Getters which read one of many values read the first one, e.g. `velmodamt 0` or `velmodtarget:0`.
+/// Returns `None` if the parameter has no getter.
+pub fn getter_path(scope: ParameterScope, identifier: &str) -> Option<Vec<RytmValue>> {
+    let getters = match scope {
+        ParameterScope::Pattern => &PATTERN_GETTERS,
+        ParameterScope::Track => &TRACK_GETTERS,
+        ParameterScope::Trig => &TRIG_GETTERS,
+        ParameterScope::Kit => &KIT_GETTERS,
+        ParameterScope::Sound => &SOUND_GETTERS,
+        ParameterScope::Global => &GLOBAL_GETTERS,
+        ParameterScope::Settings => &SETTINGS_GETTERS,
+        ParameterScope::KitElement | ParameterScope::ParameterLock => {
+            return Some(vec![identifier.into()])
+        }
+    };
+    let name = identifier.strip_suffix(':').unwrap_or(identifier);
+    if getters.unreadable.contains(&name) {
+        return None;
+    }
+    if !getters.indexed.iter().any(|(indexed, _)| *indexed == name) {
+        return Some(vec![identifier.into()]);
+    }
+    Some(if name == identifier {
+        vec![identifier.into(), index_value(0)]
+    } else {
+        vec![format!("{identifier}0").into()]
+    })
+}
+
+/// Compares two snapshots of the same object, returns the parameters which differ in the order of the local snapshot.
+pub fn compare(local: &[Parameter], device: &[Parameter]) -> Vec<Mismatch> {
+    let mut device_values = device
+        .iter()
+        .map(|parameter| (path_key(&parameter.path), parameter))
+        .collect::<HashMap<_, _>>();
+
+    let mut mismatches = local
+        .iter()
+        .filter_map(|parameter| {
+            let device_value = device_values
+                .remove(&path_key(&parameter.path))
+                .map(|device| device.value.clone());
+            (device_value.as_ref() != Some(&parameter.value)).then(|| Mismatch {
+                path: parameter.path.clone(),
+                local: Some(parameter.value.clone()),
+                device: device_value,
+            })
+        })
+        .collect::<Vec<_>>();
+
+    // Parameters which could only be read on the device.
+    mismatches.extend(
+        device
+            .iter()
+            .filter(|parameter| device_values.contains_key(&path_key(&parameter.path)))
+            .map(|parameter| Mismatch {
+                path: parameter.path.clone(),
+                local: None,
+                device: Some(parameter.value.clone()),
+            }),
+    );
+    mismatches
+}
+
+/// Pushes the getters of a kit and of its kit elements on every track.
+fn push_kit_getters(paths: &mut Vec<Vec<RytmValue>>) {
+    push_getters(paths, &[], &KIT_GETTERS);
+    for element in KIT_ELEMENTS_ACTION.iter().chain(KIT_ELEMENTS_ENUM) {
+        for track in 0..TRACK_COUNT {
+            paths.push(vec![(*element).into(), index_value(track)]);
+        }
+    }
+}
+
+fn push_getters(paths: &mut Vec<Vec<RytmValue>>, prefix: &[RytmValue], getters: &Getters) {
+    let readable = |name: &&&str| !getters.unreadable.contains(*name);
+    let count = |name: &str| {
+        getters
+            .indexed
+            .iter()
+            .find(|(indexed, _)| *indexed == name)
+            .map(|(_, count)| *count)
+    };
+
+    for action_type in getters.action_types.iter().filter(readable) {
+        let mut path = prefix.to_vec();
+        path.push((*action_type).into());
+        match count(action_type) {
+            Some(count) => paths.extend((0..count).map(|index| {
+                let mut path = path.clone();
+                path.push(index_value(index));
+                path
+            })),
+            None => paths.push(path),
+        }
+    }
+    for enum_type in getters.enum_types.iter().filter(readable) {
+        let values = match count(enum_type) {
+            Some(count) => (0..count).map(|index| index.to_string()).collect(),
+            None => vec![String::new()],
+        };
+        for value in values {
+            let mut path = prefix.to_vec();
+            path.push(format!("{enum_type}:{value}").into());
+            paths.push(path);
+        }
+    }
+}
+
+const fn index_value(index: usize) -> RytmValue {
+    RytmValue::Int(index as isize)
+}
+
+fn path_key(path: &[RytmValue]) -> String {
+    path.iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        api::Response, parse::types::DeviceObject, types::CommandType, value::RytmValueList,
+        RytmObject,
+    };
+    use rytm_rs::RytmProject;
+
+    fn parameter(path: &[&str], value: RytmValue) -> Parameter {
+        Parameter {
+            path: path.iter().map(|part| (*part).into()).collect(),
+            value,
+        }
+    }
+
+    #[test]
+    fn parameter_paths_cover_nested_elements() {
+        let paths = parameter_paths(ObjectTypeSelector::Pattern(0));
+        assert!(paths.contains(&vec!["speed:".into()]));
+        assert!(paths.contains(&vec![RytmValue::Int(12), "rootnote:".into()]));
+        assert!(paths.contains(&vec![
+            RytmValue::Int(12),
+            RytmValue::Int(63),
+            "enable".into()
+        ]));
+
+        let paths = parameter_paths(ObjectTypeSelector::KitWorkBuffer);
+        assert!(paths.contains(&vec!["tracklevel".into(), RytmValue::Int(12)]));
+        assert!(paths.contains(&vec![
+            "sound".into(),
+            RytmValue::Int(11),
+            "filtcutoff".into()
+        ]));
+        assert!(!paths.contains(&vec!["fxdeltimeonthegrid:".into()]));
+        assert!(paths.contains(&vec!["ctrlinmod2target:3".into()]));
+    }
+
+    #[test]
+    fn parameter_paths_are_readable() {
+        let paths = parameter_paths(ObjectTypeSelector::Sound(0));
+        assert!(paths.contains(&vec!["velmodamt".into(), RytmValue::Int(3)]));
+        assert!(paths.contains(&vec!["atmodtarget:0".into()]));
+        assert!(!paths.contains(&vec!["velmodamt".into()]));
+        assert!(!paths.contains(&vec!["atmodtarget:".into()]));
+        assert!(!paths.contains(&vec!["iswb".into()]));
+
+        let paths = parameter_paths(ObjectTypeSelector::Settings);
+        assert!(paths.contains(&vec!["mute".into(), RytmValue::Int(11)]));
+        assert!(!paths.contains(&vec!["unmute".into()]));
+
+        let paths = parameter_paths(ObjectTypeSelector::GlobalWorkBuffer);
+        assert!(paths.contains(&vec!["routetomain".into(), RytmValue::Int(0)]));
+        assert!(paths.contains(&vec!["trackchannels:11".into()]));
+        assert!(paths.contains(&vec!["version".into()]));
+    }
+
+    #[test]
+    fn getter_paths_of_parameters() {
+        assert_eq!(
+            getter_path(ParameterScope::Sound, "filtcutoff"),
+            Some(vec!["filtcutoff".into()])
+        );
+        assert_eq!(
+            getter_path(ParameterScope::Sound, "velmodamt"),
+            Some(vec!["velmodamt".into(), RytmValue::Int(0)])
+        );
+        assert_eq!(
+            getter_path(ParameterScope::Sound, "velmodtarget:"),
+            Some(vec!["velmodtarget:0".into()])
+        );
+        assert_eq!(getter_path(ParameterScope::Sound, "iswb"), None);
+        assert_eq!(getter_path(ParameterScope::Settings, "unmute"), None);
+    }
+
+    #[test]
+    fn level_paths_stay_in_their_level() {
+        let paths = level_paths(ObjectTypeSelector::Pattern(0), &[]).unwrap();
+        assert_eq!(
+            paths.len(),
+            PATTERN_ACTION_TYPES.len() + PATTERN_ENUM_TYPES.len()
+        );
+
+        let track = [RytmValue::Int(3)];
+        let paths = level_paths(ObjectTypeSelector::PatternWorkBuffer, &track).unwrap();
+        assert!(paths.contains(&vec![RytmValue::Int(3), "rootnote:".into()]));
+        assert!(paths.iter().all(|path| path.len() == 2));
+
+        let trig = [RytmValue::Int(3), RytmValue::Int(12)];
+        let paths = level_paths(ObjectTypeSelector::Pattern(1), &trig).unwrap();
+        assert!(paths.contains(&vec![
+            RytmValue::Int(3),
+            RytmValue::Int(12),
+            "enable".into()
+        ]));
+
+        let paths = level_paths(ObjectTypeSelector::Kit(0), &[]).unwrap();
+        assert!(paths.contains(&vec!["tracklevel".into(), RytmValue::Int(12)]));
+        assert!(!paths.iter().any(|path| path[0] == RytmValue::from("sound")));
+
+        let sound = ["sound".into(), RytmValue::Int(11)];
+        let paths = level_paths(ObjectTypeSelector::KitWorkBuffer, &sound).unwrap();
+        assert!(paths.contains(&vec![
+            "sound".into(),
+            RytmValue::Int(11),
+            "filtcutoff".into()
+        ]));
+
+        assert!(level_paths(ObjectTypeSelector::Settings, &track).is_none());
+        assert!(level_paths(ObjectTypeSelector::Pattern(0), &["sound".into()]).is_none());
+    }
+
+    #[test]
+    fn compare_reports_differing_and_missing_parameters() {
+        let local = vec![
+            parameter(&["name"], "KIT".into()),
+            parameter(&["fxdelfeedback"], RytmValue::Int(40)),
+            parameter(&["fxlfodest:"], "fxdeltime".into()),
+        ];
+        let device = vec![
+            parameter(&["fxlfodest:"], "fxdeltime".into()),
+            parameter(&["name"], "KIT".into()),
+            parameter(&["fxdelfeedback"], RytmValue::Int(41)),
+            parameter(&["fxcompgain"], RytmValue::Int(2)),
+        ];
+
+        assert!(compare(&local, &local).is_empty());
+        assert_eq!(
+            compare(&local, &device),
+            vec![
+                Mismatch {
+                    path: vec!["fxdelfeedback".into()],
+                    local: Some(RytmValue::Int(40)),
+                    device: Some(RytmValue::Int(41)),
+                },
+                Mismatch {
+                    path: vec!["fxcompgain".into()],
+                    local: None,
+                    device: Some(RytmValue::Int(2)),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn dump_reads_every_parameter_of_a_level() {
+        let rytm = RytmObject::new(RytmProject::try_default().unwrap());
+        rytm.command(
+            CommandType::Set,
+            RytmValueList::from(vec![
+                "sound_wb".into(),
+                0_isize.into(),
+                "filtcutoff".into(),
+                100_isize.into(),
+            ]),
+        )
+        .unwrap();
+
+        let Response::Many(responses) = rytm
+            .command(
+                CommandType::Get,
+                RytmValueList::from(vec!["sound_wb".into(), 0_isize.into(), "dump".into()]),
+            )
+            .unwrap()
+        else {
+            panic!("A dump should respond with every parameter.");
+        };
+        assert!(responses.len() > 30);
+        assert!(responses.iter().any(|response| matches!(
+            response,
+            Response::Common { key, value, .. }
+                if *key == RytmValue::from("filtcutoff") && *value == RytmValue::Int(100)
+        )));
+
+        let Response::Many(responses) = rytm
+            .command(
+                CommandType::Get,
+                RytmValueList::from(vec![
+                    "pattern_wb".into(),
+                    2_isize.into(),
+                    5_isize.into(),
+                    "dump".into(),
+                ]),
+            )
+            .unwrap()
+        else {
+            panic!("A dump should respond with every parameter.");
+        };
+        assert!(responses.iter().all(|response| matches!(
+            response,
+            Response::Trig {
+                track_index: 2,
+                trig_index: 5,
+                ..
+            }
+        )));
+        assert_eq!(
+            rytm.dirty_objects(),
+            vec![DeviceObject::new(0, ObjectTypeSelector::SoundWorkBuffer(0))]
+        );
+
+        assert!(rytm
+            .command(
+                CommandType::Get,
+                RytmValueList::from(vec!["settings".into(), 0_isize.into(), "dump".into()]),
+            )
+            .is_err());
+    }
+
+    #[test]
+    fn every_getter_of_a_level_is_dumped() {
+        let rytm = RytmObject::new(RytmProject::try_default().unwrap());
+        let levels: Vec<(ObjectTypeSelector, Vec<RytmValue>)> = vec![
+            (ObjectTypeSelector::PatternWorkBuffer, vec![]),
+            (ObjectTypeSelector::PatternWorkBuffer, vec![12_isize.into()]),
+            (
+                ObjectTypeSelector::PatternWorkBuffer,
+                vec![12_isize.into(), 63_isize.into()],
+            ),
+            (ObjectTypeSelector::KitWorkBuffer, vec![]),
+            (
+                ObjectTypeSelector::KitWorkBuffer,
+                vec!["sound".into(), 11_isize.into()],
+            ),
+            (ObjectTypeSelector::SoundWorkBuffer(0), vec![]),
+            (ObjectTypeSelector::GlobalWorkBuffer, vec![]),
+            (ObjectTypeSelector::Settings, vec![]),
+        ];
+
+        for (object, scope) in levels {
+            let mut command = match object {
+                ObjectTypeSelector::SoundWorkBuffer(index) => {
+                    vec!["sound_wb".into(), (index as isize).into()]
+                }
+                ObjectTypeSelector::PatternWorkBuffer => vec!["pattern_wb".into()],
+                ObjectTypeSelector::KitWorkBuffer => vec!["kit_wb".into()],
+                ObjectTypeSelector::GlobalWorkBuffer => vec!["global_wb".into()],
+                _ => vec!["settings".into()],
+            };
+            command.extend(scope.clone());
+            command.push("dump".into());
+
+            let Response::Many(responses) = rytm
+                .command(CommandType::Get, RytmValueList::from(command))
+                .unwrap()
+            else {
+                panic!("A dump should respond with every parameter.");
+            };
+            assert_eq!(
+                responses.len(),
+                level_paths(object, &scope).unwrap().len(),
+                "Every getter of {object:?} {scope:?} should be read."
+            );
+        }
+    }
+}
@@ -0,0 +1,169 @@
+//! "Did you mean" suggestions for symbols which are neither an identifier nor an enum type, e.g. `filtcutof`.
+//!
+//! Candidates come from the identifier and enum tables of the level the command addresses and are ranked by edit distance.
+
+use crate::schema::ParameterScope;
+
+/// The most suggestions offered for a symbol.
+pub const MAX_SUGGESTIONS: usize = 3;
+
+/// The identifiers and the enum types of the scope which are close to the symbol, closest first.
+///
+/// Enum types are suggested the way they are written, keeping the variant of the symbol, e.g. `speed:2x` for `sped:2x`.
+pub fn suggestions(symbol: &str, scope: ParameterScope) -> Vec<String> {
+    let (name, variant) = match symbol.split_once(':') {
+        Some((name, variant)) => (name, Some(variant)),
+        None => (symbol, None),
+    };
+    let (identifiers, enum_types) = candidates(scope);
+    // Only enum types are written with a `:`.
+    let identifiers = if variant.is_some() {
+        Vec::new()
+    } else {
+        identifiers
+    };
+
+    // Tolerates about one typo in every three characters.
+    let max_distance = (name.chars().count() / 3).max(1);
+    let mut ranked = identifiers
+        .into_iter()
+        .map(|candidate| (candidate, false))
+        .chain(enum_types.into_iter().map(|candidate| (candidate, true)))
+        .filter_map(|(candidate, is_enum)| {
+            let distance = edit_distance(name, candidate);
+            (distance <= max_distance).then_some((distance, candidate, is_enum))
+        })
+        .collect::<Vec<_>>();
+    ranked.sort_unstable();
+    ranked.dedup();
+
+    ranked
+        .into_iter()
+        .take(MAX_SUGGESTIONS)
+        .map(|(_, candidate, is_enum)| {
+            if is_enum {
+                format!("{candidate}:{}", variant.unwrap_or_default())
+            } else {
+                candidate.to_owned()
+            }
+        })
+        .collect()
+}
+
+/// The identifiers and the enum types a symbol in the scope may be.
+fn candidates(scope: ParameterScope) -> (Vec<&'static str>, Vec<&'static str>) {
+    // Parameter locks lock the parameters of sounds and kits.
+    let scopes: &[ParameterScope] = match scope {
+        ParameterScope::ParameterLock => &[ParameterScope::Sound, ParameterScope::Kit],
+        _ => &[scope],
+    };
+
+    scopes.iter().fold(
+        (Vec::new(), Vec::new()),
+        |(mut identifiers, mut enum_types), scope| {
+            let (scope_identifiers, scope_enum_types) = scope.identifiers();
+            identifiers.extend_from_slice(scope_identifiers);
+            enum_types.extend_from_slice(scope_enum_types);
+            (identifiers, enum_types)
+        },
+    )
+}
+
+/// The number of single character insertions, deletions and substitutions which turn one string to the other.
+pub fn edit_distance(from: &str, to: &str) -> usize {
+    let to = to.chars().collect::<Vec<_>>();
+    let mut previous = (0..=to.len()).collect::<Vec<_>>();
+    let mut current = vec![0; to.len() + 1];
+
+    for (i, from_char) in from.chars().enumerate() {
+        current[0] = i + 1;
+        for (j, to_char) in to.iter().enumerate() {
+            let substitution = previous[j] + usize::from(from_char != *to_char);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+
+    previous[to.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{types::CommandType, value::RytmValueList, RytmObject};
+    use rytm_rs::RytmProject;
+
+    #[test]
+    fn edit_distance_counts_single_character_edits() {
+        assert_eq!(edit_distance("filtcutoff", "filtcutoff"), 0);
+        assert_eq!(edit_distance("filtcutof", "filtcutoff"), 1);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        assert_eq!(edit_distance("", "vel"), 3);
+    }
+
+    #[test]
+    fn suggestions_come_from_the_addressed_level() {
+        assert_eq!(
+            suggestions("filtcutof", ParameterScope::Sound)[0],
+            "filtcutoff"
+        );
+        assert_eq!(
+            suggestions("sped:2x", ParameterScope::Pattern),
+            vec!["speed:2x"]
+        );
+        assert_eq!(
+            suggestions("filtcutof", ParameterScope::ParameterLock)[0],
+            "filtcutoff"
+        );
+        // Sound parameters are not suggested for a pattern.
+        assert!(suggestions("filtcutof", ParameterScope::Pattern).is_empty());
+        assert!(suggestions("xyz", ParameterScope::Sound).is_empty());
+    }
+
+    #[test]
+    fn typos_are_answered_with_suggestions() {
+        let rytm = RytmObject::new(RytmProject::try_default().unwrap());
+        let err = rytm
+            .command(
+                CommandType::Set,
+                RytmValueList::from(vec![
+                    "kit_wb".into(),
+                    "sound".into(),
+                    2_isize.into(),
+                    "filtcutof".into(),
+                    100_isize.into(),
+                ]),
+            )
+            .unwrap_err();
+
+        let (symbol, suggestions) = err.suggestions().unwrap();
+        assert_eq!(symbol, "filtcutof");
+        assert_eq!(suggestions.first().map(String::as_str), Some("filtcutoff"));
+        assert!(rytm.dirty_objects().is_empty());
+
+        // An enum type of a sound is not one of a kit.
+        let err = rytm
+            .command(
+                CommandType::Get,
+                RytmValueList::from(vec!["kit_wb".into(), "lfodest:".into()]),
+            )
+            .unwrap_err();
+        let (symbol, suggestions) = err.suggestions().unwrap();
+        assert_eq!(symbol, "lfodest:");
+        assert_eq!(suggestions.first().map(String::as_str), Some("fxlfodest:"));
+
+        // An identifier of a pattern is not one of a trig.
+        let err = rytm
+            .command(
+                CommandType::Get,
+                RytmValueList::from(vec![
+                    "pattern_wb".into(),
+                    0_isize.into(),
+                    0_isize.into(),
+                    "swingamount".into(),
+                ]),
+            )
+            .unwrap_err();
+        assert!(err.to_string().contains("Unexpected symbol 'swingamount'"));
+    }
+}
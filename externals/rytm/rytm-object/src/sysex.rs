@@ -0,0 +1,687 @@
+use crate::{
+    error::RytmObjectError,
+    parse::types::{DeviceObject, ObjectTypeSelector},
+    snapshot::Mismatch,
+};
+use std::collections::VecDeque;
+use tracing::warn;
+
+// Constants for MIDI SysEx messages
+pub const SYSEX_START: u8 = 0xF0;
+pub const SYSEX_END: u8 = 0xF7;
+// Any status byte other than realtime messages ends a sysex message.
+const STATUS_BYTE_START: u8 = 0x80;
+// Realtime messages may arrive at any time, even in the middle of a sysex message.
+const REALTIME_START: u8 = 0xF8;
+
+// Elektron sysex header: F0 00 20 3C <product id> <device id> <object type> <version major> <version minor> <object number>
+const ELEKTRON_MANUFACTURER_ID: [u8; 3] = [0x00, 0x20, 0x3C];
+pub const ANALOG_RYTM_PRODUCT_ID: u8 = 0x07;
+const HEADER_LENGTH: usize = 10;
+
+const KIT_DUMP: u8 = 0x52;
+const SOUND_DUMP: u8 = 0x53;
+const PATTERN_DUMP: u8 = 0x54;
+const SETTINGS_DUMP: u8 = 0x56;
+const GLOBAL_DUMP: u8 = 0x57;
+
+// A query for an object uses the dump type of the object plus this offset.
+const QUERY_TYPE_OFFSET: u8 = 0x10;
+
+const WORK_BUFFER_FLAG: u8 = 0x80;
+
+// Universal device inquiry: F0 7E <device id> 06 01 F7
+// Identity reply: F0 7E <device id> 06 02 <manufacturer id> <family> <member> <version> F7
+const UNIVERSAL_NON_REALTIME: u8 = 0x7E;
+const GENERAL_INFORMATION: u8 = 0x06;
+const IDENTITY_REQUEST: u8 = 0x01;
+const IDENTITY_REPLY: u8 = 0x02;
+const IDENTITY_REPLY_LENGTH: usize = 17;
+
+/// The only firmware version the sysex format of `rytm` is compatible with.
+pub const SUPPORTED_FIRMWARE: FirmwareVersion = FirmwareVersion {
+    major: 1,
+    minor: 70,
+};
+
+/// Collects bytes of a MIDI stream into complete sysex messages.
+#[derive(Debug, Default)]
+pub struct SysexAssembler {
+    buffer: Vec<u8>,
+    buffering: bool,
+}
+
+impl SysexAssembler {
+    pub const fn is_buffering(&self) -> bool {
+        self.buffering
+    }
+
+    /// Checks if a byte may arrive in the current state.
+    ///
+    /// Outside of a message only the start of a sysex message and realtime messages are expected.
+    pub const fn accepts(&self, byte: u8) -> bool {
+        self.buffering || byte == SYSEX_START || byte >= REALTIME_START
+    }
+
+    /// Adds a byte to the message being collected.
+    ///
+    /// Returns the message if the byte completes it.
+    pub fn push(&mut self, byte: u8) -> Option<Vec<u8>> {
+        if byte >= REALTIME_START {
+            return None;
+        }
+
+        match byte {
+            SYSEX_START => {
+                if self.buffering {
+                    warn!(
+                        "Discarding an incomplete sysex message of {} bytes. A new message started before it ended.",
+                        self.buffer.len()
+                    );
+                }
+                self.buffering = true;
+                self.buffer.clear();
+                self.buffer.push(byte);
+                None
+            }
+            _ if !self.buffering => {
+                warn!("Ignoring byte {byte:#04X} which is not a part of a sysex message.");
+                None
+            }
+            SYSEX_END => {
+                self.buffering = false;
+                self.buffer.push(byte);
+                Some(std::mem::take(&mut self.buffer))
+            }
+            _ if byte >= STATUS_BYTE_START => {
+                warn!(
+                    "Discarding an incomplete sysex message of {} bytes. It is interrupted by status byte {byte:#04X}.",
+                    self.buffer.len()
+                );
+                self.buffering = false;
+                self.buffer.clear();
+                None
+            }
+            _ => {
+                self.buffer.push(byte);
+                None
+            }
+        }
+    }
+}
+
+/// The identifying part of an Analog Rytm object dump or query.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct ObjectHeader {
+    pub device_id: u8,
+    pub object: ObjectTypeSelector,
+}
+
+impl ObjectHeader {
+    pub const fn device_object(&self) -> DeviceObject {
+        DeviceObject::new(self.device_id, self.object)
+    }
+}
+
+/// Reads the header of an Analog Rytm object dump.
+///
+/// Returns `None` if the message is not a dump of an object `rytm` knows about.
+pub fn parse_dump_header(message: &[u8]) -> Option<ObjectHeader> {
+    parse_object_header(message, 0)
+}
+
+/// Whether a message can be the reply of a device to the warm-up query, see [`crate::connection`].
+///
+/// That is a settings dump of the device, complete or not, or a message of the device which is too short to have a readable header.
+pub fn is_warm_up_reply(message: &[u8], device_id: u8) -> bool {
+    let from_device = message.len() > 5
+        && message[0] == SYSEX_START
+        && message[1..4] == ELEKTRON_MANUFACTURER_ID
+        && message[4] == ANALOG_RYTM_PRODUCT_ID
+        && message[5] == device_id;
+    if !from_device {
+        return false;
+    }
+    match parse_dump_header(message) {
+        Some(header) => header.object == ObjectTypeSelector::Settings,
+        None => message.len() < HEADER_LENGTH,
+    }
+}
+
+/// Reads the header of an Analog Rytm object query, e.g. the ones made by [`crate::RytmObject::query_sysex`].
+///
+/// Returns `None` if the message is not a query of an object `rytm` knows about.
+pub fn parse_query_header(message: &[u8]) -> Option<ObjectHeader> {
+    parse_object_header(message, QUERY_TYPE_OFFSET)
+}
+
+fn parse_object_header(message: &[u8], type_offset: u8) -> Option<ObjectHeader> {
+    if message.len() < HEADER_LENGTH
+        || message[0] != SYSEX_START
+        || message[1..4] != ELEKTRON_MANUFACTURER_ID
+        || message[4] != ANALOG_RYTM_PRODUCT_ID
+    {
+        return None;
+    }
+
+    let device_id = message[5];
+    let object_type = message[6].checked_sub(type_offset)?;
+    let object_number = message[9];
+    let is_work_buffer = object_number & WORK_BUFFER_FLAG != 0;
+    let index = (object_number & !WORK_BUFFER_FLAG) as usize;
+
+    let object = match (object_type, is_work_buffer) {
+        (PATTERN_DUMP, false) => ObjectTypeSelector::Pattern(index),
+        (PATTERN_DUMP, true) => ObjectTypeSelector::PatternWorkBuffer,
+        (KIT_DUMP, false) => ObjectTypeSelector::Kit(index),
+        (KIT_DUMP, true) => ObjectTypeSelector::KitWorkBuffer,
+        (SOUND_DUMP, false) => ObjectTypeSelector::Sound(index),
+        (SOUND_DUMP, true) => ObjectTypeSelector::SoundWorkBuffer(index),
+        (GLOBAL_DUMP, false) => ObjectTypeSelector::Global(index),
+        (GLOBAL_DUMP, true) => ObjectTypeSelector::GlobalWorkBuffer,
+        (SETTINGS_DUMP, _) => ObjectTypeSelector::Settings,
+        _ => return None,
+    };
+
+    Some(ObjectHeader { device_id, object })
+}
+
+/// Makes a universal device inquiry message for the given device id.
+pub const fn device_inquiry(device_id: u8) -> [u8; 6] {
+    [
+        SYSEX_START,
+        UNIVERSAL_NON_REALTIME,
+        device_id,
+        GENERAL_INFORMATION,
+        IDENTITY_REQUEST,
+        SYSEX_END,
+    ]
+}
+
+/// Reads the device id from a universal device inquiry.
+///
+/// Returns `None` if the message is not a device inquiry.
+pub fn parse_device_inquiry(message: &[u8]) -> Option<u8> {
+    match message {
+        [SYSEX_START, UNIVERSAL_NON_REALTIME, device_id, GENERAL_INFORMATION, IDENTITY_REQUEST, SYSEX_END] => {
+            Some(*device_id)
+        }
+        _ => None,
+    }
+}
+
+/// Firmware version as reported in the identity reply, e.g. `1.70`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct FirmwareVersion {
+    pub major: u8,
+    pub minor: u8,
+}
+
+impl std::fmt::Display for FirmwareVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}.{:02}", self.major, self.minor)
+    }
+}
+
+/// A device which answered a universal device inquiry.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct DeviceIdentity {
+    pub device_id: u8,
+    pub family: u16,
+    pub member: u16,
+    pub firmware: FirmwareVersion,
+}
+
+impl DeviceIdentity {
+    /// Elektron uses the product id of the sysex header as the device family.
+    pub const fn is_analog_rytm(&self) -> bool {
+        self.family == ANALOG_RYTM_PRODUCT_ID as u16
+    }
+
+    pub const fn model(&self) -> &'static str {
+        if self.is_analog_rytm() {
+            "analog_rytm"
+        } else {
+            "unknown"
+        }
+    }
+
+    pub fn is_supported(&self) -> bool {
+        self.is_analog_rytm() && self.firmware == SUPPORTED_FIRMWARE
+    }
+
+    /// Makes the identity reply a device with this identity sends.
+    pub fn as_reply(&self) -> Vec<u8> {
+        // Family and member codes are 14 bit values sent LSB first.
+        let split_u14 = |value: u16| [(value & 0x7F) as u8, (value >> 7 & 0x7F) as u8];
+
+        let mut reply = vec![
+            SYSEX_START,
+            UNIVERSAL_NON_REALTIME,
+            self.device_id,
+            GENERAL_INFORMATION,
+            IDENTITY_REPLY,
+        ];
+        reply.extend(ELEKTRON_MANUFACTURER_ID);
+        reply.extend(split_u14(self.family));
+        reply.extend(split_u14(self.member));
+        reply.extend([self.firmware.major, self.firmware.minor, 0x00, 0x00]);
+        reply.push(SYSEX_END);
+        reply
+    }
+}
+
+/// Reads an identity reply of an Elektron device.
+///
+/// Returns `None` if the message is not an identity reply or it is sent from a device of another manufacturer.
+pub fn parse_identity_reply(message: &[u8]) -> Option<DeviceIdentity> {
+    if message.len() != IDENTITY_REPLY_LENGTH
+        || message[0] != SYSEX_START
+        || message[1] != UNIVERSAL_NON_REALTIME
+        || message[3] != GENERAL_INFORMATION
+        || message[4] != IDENTITY_REPLY
+        || message[5..8] != ELEKTRON_MANUFACTURER_ID
+        || message[16] != SYSEX_END
+    {
+        return None;
+    }
+
+    // Family and member codes are 14 bit values sent LSB first.
+    let read_u14 = |at: usize| u16::from(message[at]) | u16::from(message[at + 1]) << 7;
+
+    Some(DeviceIdentity {
+        device_id: message[2],
+        family: read_u14(8),
+        member: read_u14(10),
+        // Elektron sends the major and minor version in the first two bytes of the version field.
+        firmware: FirmwareVersion {
+            major: message[12],
+            minor: message[13],
+        },
+    })
+}
+
+/// How outgoing sysex messages leave `rytm`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum OutputMode {
+    /// One int per byte, the way `sysexin` outputs them.
+    #[default]
+    Bytes,
+    /// Lists of bytes, a complete message or a chunk of it per list.
+    List,
+}
+
+impl TryFrom<isize> for OutputMode {
+    type Error = RytmObjectError;
+
+    fn try_from(mode: isize) -> Result<Self, Self::Error> {
+        match mode {
+            0 => Ok(Self::Bytes),
+            1 => Ok(Self::List),
+            other => Err(format!(
+                "Output Error: Invalid output mode {other}. Output mode should be 0 for bytes or 1 for lists."
+            )
+            .into()),
+        }
+    }
+}
+
+impl From<OutputMode> for isize {
+    fn from(mode: OutputMode) -> Self {
+        match mode {
+            OutputMode::Bytes => 0,
+            OutputMode::List => 1,
+        }
+    }
+}
+
+/// Holds outgoing sysex messages split to the pieces which leave `rytm` one at a time.
+///
+/// In [`OutputMode::Bytes`] a piece is a complete message which is sent byte by byte.
+/// In [`OutputMode::List`] a piece is a list of at most `chunk_size` bytes, or a complete message if `chunk_size` is 0.
+#[derive(Debug, Default)]
+pub struct SysexOutbox {
+    mode: OutputMode,
+    chunk_size: usize,
+    pending: VecDeque<Vec<u8>>,
+}
+
+impl SysexOutbox {
+    pub const fn mode(&self) -> OutputMode {
+        self.mode
+    }
+
+    /// Takes effect for the messages pushed after the change.
+    pub fn set_mode(&mut self, mode: OutputMode) {
+        self.mode = mode;
+    }
+
+    pub const fn chunk_size(&self) -> usize {
+        self.chunk_size
+    }
+
+    /// Takes effect for the messages pushed after the change.
+    pub fn set_chunk_size(&mut self, chunk_size: usize) {
+        self.chunk_size = chunk_size;
+    }
+
+    /// Splits a message and queues its pieces after the ones which are still pending.
+    pub fn push(&mut self, message: &[u8]) {
+        match (self.mode, self.chunk_size) {
+            (OutputMode::Bytes, _) | (OutputMode::List, 0) => {
+                self.pending.push_back(message.to_vec());
+            }
+            (OutputMode::List, chunk_size) => self
+                .pending
+                .extend(message.chunks(chunk_size).map(<[u8]>::to_vec)),
+        }
+    }
+
+    /// The next piece to send, `None` if everything is sent.
+    pub fn pop(&mut self) -> Option<Vec<u8>> {
+        self.pending.pop_front()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+
+    /// Drops the pieces which are not sent yet.
+    pub fn clear(&mut self) {
+        self.pending.clear();
+    }
+}
+
+/// What happened to a complete sysex message which arrived from the device.
+#[derive(Debug)]
+pub enum SysexEvent {
+    /// A device answered a device inquiry.
+    Identified(DeviceIdentity),
+    /// The message is applied to the project.
+    Applied(DeviceObject),
+    /// The message could not be applied to the project.
+    ///
+    /// The object is `None` if the header of the message could not be read.
+    Rejected {
+        object: Option<DeviceObject>,
+        error: RytmObjectError,
+    },
+    /// The first message after the warm-up query arrived and is swallowed, the connection is ready.
+    Ready,
+    /// A sent object is read back from the device and applied to the project.
+    ///
+    /// The mismatches are the parameters which differ from what was sent, empty if the device has the same object.
+    Verified {
+        object: DeviceObject,
+        mismatches: Vec<Mismatch>,
+    },
+    /// A query did not get a response after all retries, it is not tracked anymore.
+    TimedOut(DeviceObject),
+}
+
+impl SysexEvent {
+    /// What the event reports from the status outlet, `None` if it only reports its results.
+    pub fn status(&self) -> Option<Status> {
+        match self {
+            Self::Identified(identity) if identity.is_supported() => Some(Status::Success),
+            Self::Identified(_) => Some(Status::Warning),
+            Self::Ready => Some(Status::Success),
+            Self::Applied(_) => None,
+            Self::Rejected { .. } | Self::TimedOut(_) => Some(Status::Error),
+            Self::Verified { mismatches, .. } if mismatches.is_empty() => Some(Status::Success),
+            Self::Verified { .. } => Some(Status::Warning),
+        }
+    }
+}
+
+/// The outcome the status outlet reports.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Status {
+    Success,
+    Error,
+    Warning,
+}
+
+impl Status {
+    /// The number sent from the status outlet.
+    pub const fn code(self) -> isize {
+        match self {
+            Self::Success => 0,
+            Self::Error => 1,
+            Self::Warning => 2,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn header(object_type: u8, object_number: u8) -> Vec<u8> {
+        vec![
+            0xF0,
+            0x00,
+            0x20,
+            0x3C,
+            0x07,
+            0x00,
+            object_type,
+            0x01,
+            0x01,
+            object_number,
+            0x00,
+            0xF7,
+        ]
+    }
+
+    #[test]
+    fn reads_object_from_dump_header() {
+        assert_eq!(
+            parse_dump_header(&header(KIT_DUMP, 5)).map(|header| header.object),
+            Some(ObjectTypeSelector::Kit(5))
+        );
+        assert_eq!(
+            parse_dump_header(&header(KIT_DUMP, 0x80)).map(|header| header.object),
+            Some(ObjectTypeSelector::KitWorkBuffer)
+        );
+        assert_eq!(
+            parse_dump_header(&header(SOUND_DUMP, 0x83)).map(|header| header.object),
+            Some(ObjectTypeSelector::SoundWorkBuffer(3))
+        );
+        assert_eq!(
+            parse_dump_header(&header(SETTINGS_DUMP, 0)).map(|header| header.object),
+            Some(ObjectTypeSelector::Settings)
+        );
+    }
+
+    #[test]
+    fn ignores_foreign_messages() {
+        // Song dumps are not supported.
+        assert_eq!(parse_dump_header(&header(0x55, 0)), None);
+        // Universal device inquiry.
+        assert_eq!(
+            parse_dump_header(&[0xF0, 0x7E, 0x7F, 0x06, 0x01, 0xF7]),
+            None
+        );
+
+        let mut other_product = header(KIT_DUMP, 1);
+        other_product[4] = 0x08;
+        assert_eq!(parse_dump_header(&other_product), None);
+    }
+
+    #[test]
+    fn recognizes_warm_up_replies() {
+        assert!(is_warm_up_reply(&header(SETTINGS_DUMP, 0), 0));
+        // The reply was cut short.
+        assert!(is_warm_up_reply(&header(SETTINGS_DUMP, 0)[..8], 0));
+
+        assert!(!is_warm_up_reply(&header(SETTINGS_DUMP, 0), 1));
+        assert!(!is_warm_up_reply(&header(KIT_DUMP, 0), 0));
+        assert!(!is_warm_up_reply(&header(0x55, 0), 0));
+        assert!(!is_warm_up_reply(&[0xF0, 0x7E, 0x00, 0x06, 0x02, 0xF7], 0));
+    }
+
+    #[test]
+    fn assembles_messages_around_realtime_bytes() {
+        let mut assembler = SysexAssembler::default();
+        let mut messages = Vec::new();
+
+        // Clock and active sensing bytes before and inside the message.
+        for byte in [0xF8, 0xF0, 0x00, 0xF8, 0x20, 0xFE, 0x3C, 0xF7, 0xFA] {
+            messages.extend(assembler.push(byte));
+        }
+
+        assert_eq!(messages, vec![vec![0xF0, 0x00, 0x20, 0x3C, 0xF7]]);
+        assert!(!assembler.is_buffering());
+    }
+
+    #[test]
+    fn assembles_concatenated_messages() {
+        let mut assembler = SysexAssembler::default();
+        let messages = [0xF0, 0x01, 0xF7, 0xF0, 0x02, 0x03, 0xF7]
+            .into_iter()
+            .filter_map(|byte| assembler.push(byte))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            messages,
+            vec![vec![0xF0, 0x01, 0xF7], vec![0xF0, 0x02, 0x03, 0xF7]]
+        );
+    }
+
+    #[test]
+    fn discards_interrupted_messages() {
+        let mut assembler = SysexAssembler::default();
+
+        // A stray start byte restarts the message.
+        for byte in [0xF0, 0x01, 0x02, 0xF0, 0x03] {
+            assert_eq!(assembler.push(byte), None);
+        }
+        assert_eq!(assembler.push(0xF7), Some(vec![0xF0, 0x03, 0xF7]));
+
+        // A note on status byte ends the message without completing it.
+        for byte in [0xF0, 0x01, 0x90, 0x3C, 0x7F] {
+            assert_eq!(assembler.push(byte), None);
+        }
+        assert_eq!(assembler.push(0xF7), None);
+    }
+
+    #[test]
+    fn accepts_only_sysex_outside_of_messages() {
+        let mut assembler = SysexAssembler::default();
+        assert!(assembler.accepts(0xF0));
+        assert!(assembler.accepts(0xF8));
+        assert!(!assembler.accepts(0x01));
+
+        assembler.push(0xF0);
+        assert!(assembler.accepts(0x01));
+    }
+
+    #[test]
+    fn reads_object_from_query_header() {
+        assert_eq!(
+            parse_query_header(&header(PATTERN_DUMP + QUERY_TYPE_OFFSET, 12)),
+            Some(ObjectHeader {
+                device_id: 0,
+                object: ObjectTypeSelector::Pattern(12)
+            })
+        );
+        assert_eq!(
+            parse_query_header(&header(GLOBAL_DUMP + QUERY_TYPE_OFFSET, 0x80))
+                .map(|header| header.object),
+            Some(ObjectTypeSelector::GlobalWorkBuffer)
+        );
+        // Dumps are not queries and the other way around.
+        assert_eq!(parse_query_header(&header(KIT_DUMP, 5)), None);
+        assert_eq!(
+            parse_dump_header(&header(KIT_DUMP + QUERY_TYPE_OFFSET, 5)),
+            None
+        );
+    }
+
+    #[test]
+    fn parses_identity_reply() {
+        let reply = [
+            0xF0, 0x7E, 0x03, 0x06, 0x02, 0x00, 0x20, 0x3C, 0x07, 0x00, 0x01, 0x00, 0x01, 0x46,
+            0x00, 0x00, 0xF7,
+        ];
+        let identity = parse_identity_reply(&reply).unwrap();
+
+        assert_eq!(identity.device_id, 3);
+        assert_eq!(identity.model(), "analog_rytm");
+        assert_eq!(identity.member, 1);
+        assert_eq!(identity.firmware.to_string(), "1.70");
+        assert!(identity.is_supported());
+
+        let mut older = reply;
+        older[13] = 0x40;
+        let identity = parse_identity_reply(&older).unwrap();
+        assert_eq!(identity.firmware.to_string(), "1.64");
+        assert!(!identity.is_supported());
+
+        assert_eq!(identity.as_reply(), older);
+        assert_eq!(parse_device_inquiry(&device_inquiry(3)), Some(3));
+        assert_eq!(parse_device_inquiry(&reply), None);
+
+        // Dumps and inquiries are not replies.
+        assert_eq!(parse_identity_reply(&device_inquiry(3)), None);
+        let mut other_manufacturer = reply;
+        other_manufacturer[7] = 0x3B;
+        assert_eq!(parse_identity_reply(&other_manufacturer), None);
+    }
+
+    #[test]
+    fn events_report_their_status() {
+        let object = DeviceObject::new(0, ObjectTypeSelector::Kit(5));
+
+        let timed_out = SysexEvent::TimedOut(object).status().unwrap();
+        assert_eq!(timed_out, Status::Error);
+        assert_eq!(timed_out.code(), 1);
+        assert_eq!(
+            SysexEvent::Rejected {
+                object: Some(object),
+                error: "Parse Error: Invalid sysex.".into(),
+            }
+            .status(),
+            Some(Status::Error)
+        );
+        assert_eq!(
+            SysexEvent::Verified {
+                object,
+                mismatches: Vec::new(),
+            }
+            .status(),
+            Some(Status::Success)
+        );
+        assert_eq!(SysexEvent::Ready.status(), Some(Status::Success));
+        assert_eq!(SysexEvent::Applied(object).status(), None);
+    }
+
+    #[test]
+    fn outbox_splits_messages_to_chunks() {
+        let message = [0xF0, 0x01, 0x02, 0x03, 0x04, 0xF7];
+        let mut outbox = SysexOutbox::default();
+
+        outbox.push(&message);
+        assert_eq!(outbox.pop(), Some(message.to_vec()));
+        assert!(outbox.is_empty());
+
+        outbox.set_mode(OutputMode::List);
+        outbox.set_chunk_size(4);
+        outbox.push(&message);
+        outbox.push(&device_inquiry(0));
+        let pieces = std::iter::from_fn(|| outbox.pop()).collect::<Vec<_>>();
+        assert_eq!(
+            pieces,
+            vec![
+                vec![0xF0, 0x01, 0x02, 0x03],
+                vec![0x04, 0xF7],
+                vec![0xF0, 0x7E, 0x00, 0x06],
+                vec![0x01, 0xF7],
+            ]
+        );
+
+        assert_eq!(OutputMode::try_from(1).unwrap(), OutputMode::List);
+        assert!(OutputMode::try_from(2).is_err());
+    }
+}
@@ -5,7 +5,7 @@ use median::{
 };
 use std::ffi::CString;
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct RytmValueList(Vec<RytmValue>);
 
 impl std::fmt::Display for RytmValueList {
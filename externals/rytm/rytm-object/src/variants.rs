@@ -0,0 +1,672 @@
+//! Variants of every enum type, listed with `get <object> <enum-type>:?`, e.g. `get pattern 0 speed:?`.
+//!
+//! The variants are the ones listed in the api docs, in the order they are listed.
+
+use crate::api::{
+    global_enum_type, kit_element_type, kit_enum_type, pattern_enum_type, settings_enum_type,
+    sound_enum_type, track_enum_type, trig_enum_type,
+};
+
+/// The variants of an enum type, `None` for an unknown enum type or one without a fixed set of variants.
+pub fn enum_variants(enum_type: &str) -> Option<&'static [&'static str]> {
+    Some(match enum_type {
+        pattern_enum_type::SPEED => &["1x", "2x", "3/2x", "3/4x", "1/2x", "1/4x", "1/8x"],
+        pattern_enum_type::TIME_MODE => &["normal", "advanced"],
+        track_enum_type::ROOT_NOTE => &[
+            "c", "c#", "d", "eb", "e", "f", "f#", "g", "g#", "a", "bb", "b",
+        ],
+        track_enum_type::PAD_SCALE => &[
+            "chromatic",
+            "ionianmajor",
+            "dorian",
+            "phrygian",
+            "lydian",
+            "mixolydian",
+            "aeolianminor",
+            "locrian",
+            "pentatonicminor",
+            "pentatonicmajor",
+            "melodicminor",
+            "harmonicminor",
+            "wholetone",
+            "blues",
+            "combominor",
+            "persian",
+            "iwato",
+            "insen",
+            "hirajoshi",
+            "pelog",
+            "phrygiandominant",
+            "wholehalfdiminished",
+            "halfwholediminished",
+            "spanish",
+            "majorlocrian",
+            "superlocrian",
+            "dorianb2",
+            "lydianaugmented",
+            "lydiandominant",
+            "doubleharmonicmajor",
+            "lydian26",
+            "ultraphrygian",
+            "hungarianminor",
+            "oriental",
+            "ionian25",
+            "locrianbb3bb7",
+        ],
+        track_enum_type::DEFAULT_NOTE_LENGTH
+        | trig_enum_type::NOTE_LENGTH
+        | trig_enum_type::RETRIG_LENGTH
+        | kit_element_type::TRACK_RETRIG_LENGTH => NOTE_LENGTHS,
+        trig_enum_type::MICRO_TIME => &[
+            "-23/384", "-11/192", "-7/128", "-5/96", "-19/384", "-3/64", "-17/384", "-1/24",
+            "-5/128", "-7/192", "-13/384", "-1/32", "-11/384", "-5/192", "-3/128", "-1/48",
+            "-7/384", "-1/64", "-5/384", "-1/96", "-1/128", "-1/192", "-1/384", "ongrid", "1/384",
+            "1/192", "1/128", "1/96", "5/384", "1/64", "7/384", "1/48", "3/128", "5/192", "11/384",
+            "1/32", "13/384", "7/192", "5/128", "1/24", "17/384", "3/64", "19/384", "5/96",
+            "7/128", "11/192", "23/384",
+        ],
+        trig_enum_type::RETRIG_RATE | kit_element_type::TRACK_RETRIG_RATE => RETRIG_RATES,
+        trig_enum_type::TRIG_CONDITION => &[
+            "1%", "3%", "4%", "6%", "9%", "13%", "19%", "25%", "33%", "41%", "50%", "59%", "67%",
+            "75%", "81%", "87%", "91%", "94%", "96%", "98%", "99%", "100%", "fill", "fillnot",
+            "pre", "prenot", "nei", "neinot", "1st", "1stnot", "1:2", "2:2", "1:3", "2:3", "3:3",
+            "1:4", "2:4", "3:4", "4:4", "1:5", "2:5", "3:5", "4:5", "5:5", "1:6", "2:6", "3:6",
+            "4:6", "5:6", "6:6", "1:7", "2:7", "3:7", "4:7", "5:7", "6:7", "7:7", "1:8", "2:8",
+            "3:8", "4:8", "5:8", "6:8", "7:8", "8:8", "unset",
+        ],
+        kit_enum_type::CONTROL_IN_1_MOD_TARGET | kit_enum_type::CONTROL_IN_2_MOD_TARGET => {
+            CONTROL_IN_MOD_TARGETS
+        }
+        kit_enum_type::FX_LFO_DESTINATION => &[
+            "unset",
+            "delaytime",
+            "delaypingpong",
+            "delaystereowidth",
+            "delayfeedback",
+            "delayhpfilter",
+            "delaylpfilter",
+            "delayreverbsend",
+            "delaymixvolume",
+            "delayoverdrive",
+            "reverbpredelay",
+            "reverbdecay",
+            "reverbshelvingfreq",
+            "reverbshelvinggain",
+            "reverbhpfilter",
+            "reverblpfilter",
+            "reverbmixvolume",
+            "distortionamount",
+            "distortionsymmetry",
+            "compressorthreshold",
+            "compressorattack",
+            "compressorrelease",
+            "compressorratio",
+            "compressorsidechaineq",
+            "compressormakeupgain",
+            "compressordrywetmix",
+            "compressorvolume",
+        ],
+        kit_enum_type::FX_COMP_ATTACK => &["0.03", "0.1", "0.3", "1", "3", "10", "30"],
+        kit_enum_type::FX_COMP_RELEASE => &["0.1", "0.2", "0.4", "0.6", "1", "2", "A1", "A2"],
+        kit_enum_type::FX_COMP_RATIO => &["1:2", "1:4", "1:8", "max"],
+        kit_enum_type::FX_COMP_SIDE_CHAIN_EQ => &["off", "lpf", "hpf", "hit"],
+        kit_enum_type::FX_DELAY_TIME_ON_THE_GRID => &[
+            "128th",
+            "64th",
+            "64thdotted",
+            "32nd",
+            "32nddotted",
+            "16th",
+            "16thdotted",
+            "8th",
+            "8thdotted",
+            "quarter",
+            "quarterdotted",
+            "half",
+            "halfdotted",
+            "whole",
+        ],
+        sound_enum_type::MACHINE_TYPE => &[
+            "bdhard",
+            "bdclassic",
+            "sdhard",
+            "sdclassic",
+            "rshard",
+            "rsclassic",
+            "cpclassic",
+            "btclassic",
+            "xtclassic",
+            "chclassic",
+            "ohclassic",
+            "cyclassic",
+            "cbclassic",
+            "bdfm",
+            "sdfm",
+            "utnoise",
+            "utimpulse",
+            "chmetallic",
+            "ohmetallic",
+            "cymetallic",
+            "cbmetallic",
+            "bdplastic",
+            "bdsilky",
+            "sdnatural",
+            "hhbasic",
+            "cyride",
+            "bdsharp",
+            "disable",
+            "sydualvco",
+            "sychip",
+            "bdacoustic",
+            "sdacoustic",
+            "syraw",
+            "hhlab",
+            "unset",
+        ],
+        sound_enum_type::LFO_DESTINATION => &[
+            "syn1",
+            "syn2",
+            "syn3",
+            "syn4",
+            "syn5",
+            "syn6",
+            "syn7",
+            "syn8",
+            "sampletune",
+            "samplefinetune",
+            "sampleslice",
+            "samplebitreduction",
+            "samplestart",
+            "sampleend",
+            "sampleloop",
+            "samplelevel",
+            "filterenvelope",
+            "filterattack",
+            "filterdecay",
+            "filtersustain",
+            "filterrelease",
+            "filterfrequency",
+            "filterresonance",
+            "ampattack",
+            "amphold",
+            "ampdecay",
+            "ampoverdrive",
+            "ampvolume",
+            "amppan",
+            "ampaccent",
+            "ampdelaysend",
+            "ampreverb_send",
+            "unset",
+        ],
+        sound_enum_type::VELOCITY_MOD_TARGET | sound_enum_type::AFTER_TOUCH_MOD_TARGET => {
+            SOUND_MOD_TARGETS
+        }
+        sound_enum_type::FILTER_TYPE => &["lp2", "lp1", "bp", "hp1", "hp2", "bs", "pk"],
+        sound_enum_type::LFO_MULTIPLIER => &[
+            "x1", "x2", "x4", "x8", "x16", "x32", "x64", "x128", "x256", "x512", "x1k", "x2k",
+            ".1", ".2", ".4", ".8", ".16", ".32", ".64", ".128", ".256", ".512", ".1k", ".2k",
+        ],
+        sound_enum_type::LFO_WAVEFORM => &["tri", "sin", "sqr", "saw", "exp", "rmp", "rnd"],
+        sound_enum_type::LFO_MODE => &["free", "trig", "hold", "one", "half"],
+        sound_enum_type::SOUND_SETTINGS_CHROMATIC_MODE => &["off", "syn", "samp", "syn+samp"],
+        global_enum_type::METRONOME_TIME_SIGNATURE => &[
+            "1/1", "2/1", "3/1", "4/1", "5/1", "6/1", "7/1", "8/1", "9/1", "10/1", "11/1", "12/1",
+            "13/1", "14/1", "15/1", "16/1", "1/2", "2/2", "3/2", "4/2", "5/2", "6/2", "7/2", "8/2",
+            "9/2", "10/2", "11/2", "12/2", "13/2", "14/2", "15/2", "16/2", "1/4", "2/4", "3/4",
+            "4/4", "5/4", "6/4", "7/4", "8/4", "9/4", "10/4", "11/4", "12/4", "13/4", "14/4",
+            "15/4", "16/4", "1/8", "2/8", "3/8", "4/8", "5/8", "6/8", "7/8", "8/8", "9/8", "10/8",
+            "11/8", "12/8", "13/8", "14/8", "15/8", "16/8", "1/16", "2/16", "3/16", "4/16", "5/16",
+            "6/16", "7/16", "8/16", "9/16", "10/16", "11/16", "12/16", "13/16", "14/16", "15/16",
+            "16/16",
+        ],
+        global_enum_type::AUTO_CHANNEL
+        | global_enum_type::TRACK_CHANNELS
+        | global_enum_type::TRACK_FX_CHANNEL
+        | global_enum_type::PROGRAM_CHANGE_IN_CHANNEL
+        | global_enum_type::PROGRAM_CHANGE_OUT_CHANNEL
+        | global_enum_type::PERFORMANCE_CHANNEL => MIDI_CHANNELS,
+        global_enum_type::OUT_PORT_FUNCTION | global_enum_type::THRU_PORT_FUNCTION => {
+            PORT_FUNCTIONS
+        }
+        global_enum_type::INPUT_FROM | global_enum_type::OUTPUT_TO => MIDI_ROUTES,
+        global_enum_type::PAD_DEST
+        | global_enum_type::PRESSURE_DEST
+        | global_enum_type::ENCODER_DEST
+        | global_enum_type::MUTE_DEST => DESTINATIONS,
+        global_enum_type::ROUTING_USB_TO_MAIN_DB => &["0db", "+6db", "+12db", "+18db"],
+        global_enum_type::PARAM_OUTPUT => &["nrpn", "cc"],
+        global_enum_type::PORTS_OUTPUT_CHANNEL => &["auto", "track"],
+        global_enum_type::ROUTING_USB_IN_OPTIONS => &[
+            "pre-fx",
+            "post-fx",
+            "1",
+            "2",
+            "3:4",
+            "5",
+            "6",
+            "7:8",
+            "9:10",
+            "11:12",
+            "l:1r:2",
+            "l:1r:3:4",
+            "l:1r:5",
+            "l:1r:6",
+            "l:1r:7:8",
+            "l:1r:9:10",
+            "l:1r:11:12",
+            "l:2r:1",
+            "l:2r:3:4",
+            "l:2r:5",
+            "l:2r:6",
+            "l:2r:7:8",
+            "l:2r:9:10",
+            "l:2r:11:12",
+            "l:3:4r:1",
+            "l:3:4r:2",
+            "l:3:4r:5",
+            "l:3:4r:6",
+            "l:3:4r:7:8",
+            "l:3:4r:9:10",
+            "l:3:4r:11:12",
+            "l:5r:1",
+            "l:5r:2",
+            "l:5r:3:4",
+            "l:5r:6",
+            "l:5r:7:8",
+            "l:5r:9:10",
+            "l:5r:11:12",
+            "l:6r:1",
+            "l:6r:2",
+            "l:6r:3:4",
+            "l:6r:5",
+            "l:6r:7:8",
+            "l:6r:9:10",
+            "l:6r:11:12",
+            "l:7:8r:1",
+            "l:7:8r:2",
+            "l:7:8r:3:4",
+            "l:7:8r:5",
+            "l:7:8r:6",
+            "l:7:8r:9:10",
+            "l:7:8r:11:12",
+            "l:9:10r:1",
+            "l:9:10r:2",
+            "l:9:10r:3:4",
+            "l:9:10r:5",
+            "l:9:10r:6",
+            "l:9:10r:7:8",
+            "l:9:10r:11:12",
+            "l:11:12r:1",
+            "l:11:12r:2",
+            "l:11:12r:3:4",
+            "l:11:12r:5",
+            "l:11:12r:6",
+            "l:11:12r:7:8",
+            "l:11:12r:9:10",
+        ],
+        global_enum_type::ROUTING_USB_OUT_OPTIONS => &[
+            "mainout",
+            "1",
+            "2",
+            "3:4",
+            "5",
+            "6",
+            "7:8",
+            "9:10",
+            "11:12",
+            "l:1r:2",
+            "l:1r:3:4",
+            "l:1r:5",
+            "l:1r:6",
+            "l:1r:7:8",
+            "l:1r:9:10",
+            "l:1r:11:12",
+            "l:2r:1",
+            "l:2r:3:4",
+            "l:2r:5",
+            "l:2r:6",
+            "l:2r:7:8",
+            "l:2r:9:10",
+            "l:2r:11:12",
+            "l:3:4r:1",
+            "l:3:4r:2",
+            "l:3:4r:5",
+            "l:3:4r:6",
+            "l:3:4r:7:8",
+            "l:3:4r:9:10",
+            "l:3:4r:11:12",
+            "l:5r:1",
+            "l:5r:2",
+            "l:5r:3:4",
+            "l:5r:6",
+            "l:5r:7:8",
+            "l:5r:9:10",
+            "l:5r:11:12",
+            "l:6r:1",
+            "l:6r:2",
+            "l:6r:3:4",
+            "l:6r:5",
+            "l:6r:7:8",
+            "l:6r:9:10",
+            "l:6r:11:12",
+            "l:7:8r:1",
+            "l:7:8r:2",
+            "l:7:8r:3:4",
+            "l:7:8r:5",
+            "l:7:8r:6",
+            "l:7:8r:9:10",
+            "l:7:8r:11:12",
+            "l:9:10r:1",
+            "l:9:10r:2",
+            "l:9:10r:3:4",
+            "l:9:10r:5",
+            "l:9:10r:6",
+            "l:9:10r:7:8",
+            "l:9:10r:11:12",
+            "l:11:12r:1",
+            "l:11:12r:2",
+            "l:11:12r:3:4",
+            "l:11:12r:5",
+            "l:11:12r:6",
+            "l:11:12r:7:8",
+            "l:11:12r:9:10",
+        ],
+        settings_enum_type::PARAMETER_MENU_ITEM => &["trig", "src", "smpl", "fltr", "amp", "lfo"],
+        settings_enum_type::FX_PARAMETER_MENU_ITEM => {
+            &["trig", "delay", "reverb", "dist", "comp", "lfo"]
+        }
+        settings_enum_type::SEQUENCER_MODE => &["normal", "chain", "song"],
+        settings_enum_type::PATTERN_MODE => {
+            &["sequential", "directstart", "directjump", "tempjump"]
+        }
+        settings_enum_type::SAMPLE_RECORDER_SOURCE => &[
+            "audl+r", "audl", "audr", "bd", "sd", "rs/cp", "bt", "lt", "mt/ht", "ch/oh", "cy/cb",
+            "main", "usbl", "usbr", "usbl+r",
+        ],
+        settings_enum_type::SAMPLE_RECORDER_RECORDING_LENGTH => &[
+            "1step", "2steps", "4steps", "8steps", "16steps", "32steps", "64steps", "128steps",
+            "max",
+        ],
+        _ => return None,
+    })
+}
+
+/// Note and retrig lengths of trigs, tracks and kit elements.
+const NOTE_LENGTHS: &[&str] = &[
+    "1/128", ".188", "1/64", ".313", ".375", ".438", "1/32", ".563", ".625", ".688", ".75", ".813",
+    ".875", ".938", "1/16", "1.06", "1.13", "1.19", "1.25", "1.31", "1.38", "1.44", "1.5", "1.56",
+    "1.63", "1.69", "1.75", "1.81", "1.88", "1.94", "1/8", "2.13", "2.25", "2.38", "2.5", "2.63",
+    "2.75", "2.88", "3", "3.13", "3.25", "3.38", "3.5", "3.63", "3.75", "3.88", "1/4", "4.25",
+    "4.5", "4.75", "5", "5.25", "5.5", "5.75", "6", "6.25", "6.5", "6.75", "7", "7.25", "7.5",
+    "7.75", "1/2", "8.5", "9", "9.5", "10", "10.5", "11", "11.5", "12", "12.5", "13", "13.5", "14",
+    "14.5", "15", "15.5", "1/1", "17", "18", "19", "20", "21", "22", "23", "24", "25", "26", "27",
+    "28", "29", "30", "31", "32", "34", "36", "38", "40", "42", "44", "46", "48", "50", "52", "54",
+    "56", "58", "60", "62", "64", "68", "72", "76", "80", "84", "88", "92", "96", "100", "104",
+    "108", "112", "116", "120", "124", "128", "inf", "unset",
+];
+
+/// Retrig rates of trigs and kit elements.
+const RETRIG_RATES: &[&str] = &[
+    "1/1", "1/2", "1/3", "1/4", "1/5", "1/6", "1/8", "1/10", "1/12", "1/16", "1/20", "1/24",
+    "1/32", "1/40", "1/48", "1/64", "1/80",
+];
+
+/// Targets of the control inputs of a kit.
+const CONTROL_IN_MOD_TARGETS: &[&str] = &[
+    "unset",
+    "lfomultiplier",
+    "lfowaveform",
+    "lfotrigmode",
+    "lfospeed",
+    "lfofade",
+    "lfophase",
+    "lfodepth",
+    "sampletune",
+    "samplefinetune",
+    "sampleslice",
+    "samplebitreduction",
+    "samplestart",
+    "sampleend",
+    "sampleloop",
+    "samplelevel",
+    "filterenvelope",
+    "filterattack",
+    "filterdecay",
+    "filtersustain",
+    "filterrelease",
+    "filterfrequency",
+    "filterresonance",
+    "ampattack",
+    "amphold",
+    "ampdecay",
+    "ampoverdrive",
+    "ampvolume",
+    "amppan",
+    "ampaccent",
+    "ampdelaysend",
+    "ampreverbsend",
+];
+
+/// Targets of the velocity and aftertouch modulation of a sound.
+const SOUND_MOD_TARGETS: &[&str] = &[
+    "unset",
+    "lfomultiplier",
+    "lfowaveform",
+    "lfotrigmode",
+    "lfospeed",
+    "lfofade",
+    "lfophase",
+    "lfodepth",
+    "syn1",
+    "syn2",
+    "syn3",
+    "syn4",
+    "syn5",
+    "syn6",
+    "syn7",
+    "syn8",
+    "sampletune",
+    "samplefinetune",
+    "sampleslice",
+    "samplebitreduction",
+    "samplestart",
+    "sampleend",
+    "sampleloop",
+    "samplelevel",
+    "filterenvelope",
+    "filterattack",
+    "filterdecay",
+    "filtersustain",
+    "filterrelease",
+    "filterfrequency",
+    "filterresonance",
+    "ampattack",
+    "amphold",
+    "ampdecay",
+    "ampoverdrive",
+    "ampvolume",
+    "amppan",
+    "ampaccent",
+    "ampdelaysend",
+    "ampreverbsend",
+];
+
+/// Midi channels of the global settings.
+const MIDI_CHANNELS: &[&str] = &[
+    "1", "2", "3", "4", "5", "6", "7", "8", "9", "10", "11", "12", "13", "14", "15", "16",
+];
+
+/// Functions of the midi ports.
+const PORT_FUNCTIONS: &[&str] = &["midi", "din24", "din48"];
+
+/// Where midi is received from and sent to.
+const MIDI_ROUTES: &[&str] = &["disabled", "midi", "usb", "midi+usb"];
+
+/// Destinations of the pads, the encoders and the mutes.
+const DESTINATIONS: &[&str] = &["int", "int+ext", "ext"];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        api::{
+            global_enum_type::GLOBAL_ENUM_TYPES, kit_element_type::KIT_ELEMENTS_ENUM,
+            kit_enum_type::KIT_ENUM_TYPES, pattern_enum_type::PATTERN_ENUM_TYPES,
+            settings_enum_type::SETTINGS_ENUM_TYPES, sound_enum_type::SOUND_ENUM_TYPES,
+            track_enum_type::TRACK_ENUM_TYPES, trig_enum_type::TRIG_ENUM_TYPES, Response,
+        },
+        schema::{ParameterScope, ValueType},
+        types::CommandType,
+        value::{RytmValue, RytmValueList},
+        RytmObject,
+    };
+    use rytm_rs::RytmProject;
+
+    #[test]
+    fn every_enum_type_has_variants() {
+        let enum_types = [
+            PATTERN_ENUM_TYPES,
+            TRACK_ENUM_TYPES,
+            TRIG_ENUM_TYPES,
+            KIT_ENUM_TYPES,
+            KIT_ELEMENTS_ENUM,
+            SOUND_ENUM_TYPES,
+            GLOBAL_ENUM_TYPES,
+            SETTINGS_ENUM_TYPES,
+        ]
+        .concat();
+
+        for enum_type in enum_types {
+            // Machine parameters depend on the machine of the sound.
+            if enum_type == sound_enum_type::MACHINE_PARAMETERS {
+                assert!(enum_variants(enum_type).is_none());
+                continue;
+            }
+            let variants = enum_variants(enum_type).unwrap();
+            assert!(!variants.is_empty());
+        }
+
+        assert_eq!(
+            enum_variants("speed"),
+            Some(&["1x", "2x", "3/2x", "3/4x", "1/2x", "1/4x", "1/8x"][..])
+        );
+        assert!(enum_variants("tracklevel").is_none());
+    }
+
+    #[test]
+    fn every_listed_variant_is_accepted_by_its_setter() {
+        let rytm = RytmObject::new(RytmProject::try_default().unwrap());
+        // Enums which set one of many values take the index of the value after the variant.
+        let indexed = [
+            "ctrlinmod1target:",
+            "ctrlinmod2target:",
+            "velmodtarget:",
+            "atmodtarget:",
+            "trackchannels:",
+        ];
+
+        for object_type in ["pattern_wb", "kit_wb", "sound_wb", "global_wb", "settings"] {
+            let parameters = rytm
+                .describe(RytmValueList::from(vec![object_type.into()]))
+                .unwrap();
+            for parameter in parameters {
+                if parameter.value_type != Some(ValueType::Enum) {
+                    continue;
+                }
+                let name = parameter.identifier.trim_end_matches(':');
+                // Machine parameters depend on the machine of the sound.
+                let Some(variants) = enum_variants(name) else {
+                    continue;
+                };
+                // Machines only fit the tracks they are made for, so every sound is tried for them.
+                let sounds = if name == "machinetype" { 0..12 } else { 0..1 };
+
+                for variant in variants {
+                    let command = |sound: isize| {
+                        let mut command: Vec<RytmValue> = match parameter.scope {
+                            ParameterScope::Pattern => vec!["pattern_wb".into()],
+                            ParameterScope::Track => vec!["pattern_wb".into(), 0_isize.into()],
+                            ParameterScope::Trig | ParameterScope::ParameterLock => {
+                                vec!["pattern_wb".into(), 0_isize.into(), 0_isize.into()]
+                            }
+                            ParameterScope::Kit => vec!["kit_wb".into()],
+                            ParameterScope::KitElement => {
+                                vec!["kit_wb".into(), name.into(), 0_isize.into()]
+                            }
+                            ParameterScope::Sound => vec!["sound_wb".into(), sound.into()],
+                            ParameterScope::Global => vec!["global_wb".into()],
+                            ParameterScope::Settings => vec!["settings".into()],
+                        };
+                        command.push(format!("{name}:{variant}").into());
+                        if indexed.contains(&parameter.identifier.as_str()) {
+                            command.push(0_isize.into());
+                        }
+                        RytmValueList::from(command)
+                    };
+                    assert!(
+                        sounds
+                            .clone()
+                            .any(|sound| rytm.command(CommandType::Set, command(sound)).is_ok()),
+                        "{object_type} should accept {name}:{variant}."
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn enum_variants_are_listed_for_every_object_type() {
+        let rytm = RytmObject::new(RytmProject::try_default().unwrap());
+        let Response::Variants {
+            enum_type,
+            variants,
+        } = rytm
+            .command(
+                CommandType::Get,
+                RytmValueList::from(vec!["pattern".into(), 0_isize.into(), "speed:?".into()]),
+            )
+            .unwrap()
+        else {
+            panic!("A query should respond with the variants of the enum.");
+        };
+        assert_eq!(enum_type, RytmValue::from("speed:"));
+        assert_eq!(variants.first(), Some(&RytmValue::from("1x")));
+
+        for command in [
+            vec!["pattern_wb".into(), 0_isize.into(), "padscale:?".into()],
+            vec![
+                "pattern_wb".into(),
+                0_isize.into(),
+                0_isize.into(),
+                "trigcondition:?".into(),
+            ],
+            vec!["kit_wb".into(), "fxcompratio:?".into()],
+            vec!["sound".into(), 3_isize.into(), "machinetype:?".into()],
+            vec!["global_wb".into(), "clockreceive:?".into()],
+            vec!["settings".into(), "sequencermode:?".into()],
+        ] {
+            assert!(matches!(
+                rytm.command(CommandType::Get, RytmValueList::from(command)),
+                Ok(Response::Variants { variants, .. }) if !variants.is_empty()
+            ));
+        }
+
+        // Machine parameters depend on the machine of the sound.
+        assert!(rytm
+            .command(
+                CommandType::Get,
+                RytmValueList::from(vec![
+                    "sound".into(),
+                    0_isize.into(),
+                    "machineparameters:?".into(),
+                ]),
+            )
+            .is_err());
+        assert!(rytm
+            .command(
+                CommandType::Set,
+                RytmValueList::from(vec!["settings".into(), "sequencermode:?".into()]),
+            )
+            .is_err());
+    }
+}
@@ -0,0 +1,203 @@
+//! A virtual Analog Rytm MKII for exercising `rytm` without the device.
+//!
+//! [`VirtualRytm`] holds its own [`RytmProject`] and talks sysex like the device does.
+//! Queries are answered with dumps of its project, incoming dumps are written to its project
+//! and device inquiries are answered with an identity reply.
+//!
+//! Faults can be injected to the replies to test how the sync logic recovers from them.
+
+use rytm_object::{
+    error::RytmObjectError,
+    parse::types::ObjectTypeSelector,
+    sysex::{
+        parse_device_inquiry, parse_dump_header, parse_query_header, DeviceIdentity,
+        FirmwareVersion, SysexAssembler, ANALOG_RYTM_PRODUCT_ID, SUPPORTED_FIRMWARE,
+    },
+    RytmObject,
+};
+use rytm_rs::{error::RytmError, RytmProject};
+use std::collections::VecDeque;
+use tracing::warn;
+
+// Devices answer inquiries sent to this id regardless of their own id.
+const ALL_CALL_DEVICE_ID: u8 = 0x7F;
+const ANALOG_RYTM_MKII_MEMBER: u16 = 0x01;
+
+#[derive(thiserror::Error, Debug)]
+pub enum SimulatorError {
+    #[error("Simulator Error: {0}")]
+    Rytm(#[from] RytmError),
+    #[error("Simulator Error: {0}")]
+    Object(#[from] RytmObjectError),
+}
+
+/// Something going wrong with a reply of the virtual device.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Fault {
+    /// The reply is never sent.
+    DropReply,
+    /// The data byte at the offset of the reply is inverted.
+    ///
+    /// Status bytes are left as they are so the message is still complete.
+    CorruptByte(usize),
+    /// Only this many bytes from the start of the reply are sent.
+    ShortRead(usize),
+}
+
+/// A virtual Analog Rytm MKII.
+pub struct VirtualRytm {
+    project: RytmProject,
+    identity: DeviceIdentity,
+    input: SysexAssembler,
+    faults: VecDeque<Fault>,
+    first_contact_short_read: bool,
+    replies_sent: usize,
+    writes: Vec<ObjectTypeSelector>,
+    rejected_writes: usize,
+}
+
+impl VirtualRytm {
+    /// Makes a device with a default project which answers to the given sysex id.
+    pub fn new(device_id: u8) -> Result<Self, SimulatorError> {
+        let mut project = RytmProject::try_default()?;
+        project.set_device_id(device_id);
+
+        Ok(Self {
+            project,
+            identity: DeviceIdentity {
+                device_id,
+                family: u16::from(ANALOG_RYTM_PRODUCT_ID),
+                member: ANALOG_RYTM_MKII_MEMBER,
+                firmware: SUPPORTED_FIRMWARE,
+            },
+            input: SysexAssembler::default(),
+            faults: VecDeque::new(),
+            first_contact_short_read: false,
+            replies_sent: 0,
+            writes: Vec::new(),
+            rejected_writes: 0,
+        })
+    }
+
+    /// Reports another firmware version in the identity reply.
+    #[must_use]
+    pub fn with_firmware(mut self, firmware: FirmwareVersion) -> Self {
+        self.identity.firmware = firmware;
+        self
+    }
+
+    /// Cuts the very first reply in half, like the device sometimes does on first contact.
+    #[must_use]
+    pub fn with_first_contact_short_read(mut self) -> Self {
+        self.first_contact_short_read = true;
+        self
+    }
+
+    pub const fn device_id(&self) -> u8 {
+        self.identity.device_id
+    }
+
+    pub const fn project(&self) -> &RytmProject {
+        &self.project
+    }
+
+    pub fn project_mut(&mut self) -> &mut RytmProject {
+        &mut self.project
+    }
+
+    /// Objects which are written by incoming dumps, in the order they arrived.
+    pub fn writes(&self) -> &[ObjectTypeSelector] {
+        &self.writes
+    }
+
+    /// Number of incoming dumps which could not be written to the project.
+    pub const fn rejected_writes(&self) -> usize {
+        self.rejected_writes
+    }
+
+    /// Number of replies sent so far, including the faulty ones.
+    pub const fn replies_sent(&self) -> usize {
+        self.replies_sent
+    }
+
+    /// Applies a fault to the next reply, faults are applied in the order they are injected.
+    pub fn inject(&mut self, fault: Fault) {
+        self.faults.push_back(fault);
+    }
+
+    /// Takes bytes sent to the device and returns the bytes the device sends back.
+    ///
+    /// Bytes may arrive in any chunks, messages are answered as soon as they are complete.
+    pub fn receive(&mut self, bytes: &[u8]) -> Vec<u8> {
+        let messages = bytes
+            .iter()
+            .filter_map(|&byte| self.input.push(byte))
+            .collect::<Vec<_>>();
+
+        let mut replies = Vec::new();
+        for message in messages {
+            if let Some(reply) = self.respond(&message) {
+                replies.extend(self.send(reply));
+            }
+        }
+        replies
+    }
+
+    fn respond(&mut self, message: &[u8]) -> Option<Vec<u8>> {
+        if let Some(device_id) = parse_device_inquiry(message) {
+            return (device_id == self.device_id() || device_id == ALL_CALL_DEVICE_ID)
+                .then(|| self.identity.as_reply());
+        }
+
+        if let Some(header) = parse_query_header(message) {
+            if header.device_id != self.device_id() {
+                return None;
+            }
+            return RytmObject::project_object_sysex(&self.project, header.object)
+                .inspect_err(|err| warn!("Virtual device could not answer a query: {}", err))
+                .ok();
+        }
+
+        if let Some(header) = parse_dump_header(message) {
+            if header.device_id != self.device_id() {
+                return None;
+            }
+            match self.project.update_from_sysex_response(message) {
+                Ok(()) => self.writes.push(header.object),
+                Err(err) => {
+                    warn!("Virtual device rejected a dump: {}", err);
+                    self.rejected_writes += 1;
+                }
+            }
+            return None;
+        }
+
+        warn!(
+            "Virtual device ignored a sysex message of {} bytes.",
+            message.len()
+        );
+        None
+    }
+
+    fn send(&mut self, mut reply: Vec<u8>) -> Vec<u8> {
+        if self.first_contact_short_read && self.replies_sent == 0 {
+            reply.truncate(reply.len() / 2);
+        }
+        self.replies_sent += 1;
+
+        match self.faults.pop_front() {
+            Some(Fault::DropReply) => Vec::new(),
+            Some(Fault::CorruptByte(offset)) => {
+                if let Some(byte) = reply.get_mut(offset).filter(|byte| **byte < 0x80) {
+                    *byte ^= 0x7F;
+                }
+                reply
+            }
+            Some(Fault::ShortRead(length)) => {
+                reply.truncate(length);
+                reply
+            }
+            None => reply,
+        }
+    }
+}
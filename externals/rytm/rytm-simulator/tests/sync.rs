@@ -0,0 +1,702 @@
+use rytm_object::{
+    connection::ConnectionState,
+    parse::types::{DeviceObject, ObjectTypeSelector},
+    progress::{Operation, Phase, ProgressEvent},
+    recording::{read_recording, Direction},
+    scheduler::{ManualClock, Request, RequestScheduler},
+    snapshot::Mismatch,
+    sysex::{self, device_inquiry, FirmwareVersion, Status, SysexEvent},
+    types::CommandType,
+    value::{RytmValue, RytmValueList},
+    RytmObject,
+};
+use rytm_rs::RytmProject;
+use rytm_simulator::{Fault, VirtualRytm};
+
+const DEVICE_ID: u8 = 0;
+
+/// Makes a `rytm` which already warmed up its connection to a device.
+fn connect() -> (RytmObject, VirtualRytm, ManualClock) {
+    let (rytm, device, clock) = connect_cold();
+    *rytm.connection.lock() = ConnectionState::Ready;
+    (rytm, device, clock)
+}
+
+fn connect_cold() -> (RytmObject, VirtualRytm, ManualClock) {
+    let rytm = RytmObject::new(RytmProject::try_default().unwrap());
+    let clock = ManualClock::default();
+    *rytm.scheduler.lock() = RequestScheduler::new(Box::new(clock.clone()));
+
+    (rytm, VirtualRytm::new(DEVICE_ID).unwrap(), clock)
+}
+
+fn values(values: Vec<RytmValue>) -> RytmValueList {
+    RytmValueList::from(values)
+}
+
+/// Sends the next due request to the device and feeds its reply back.
+fn exchange(rytm: &RytmObject, device: &mut VirtualRytm) -> Vec<SysexEvent> {
+    let (_, sysex) = rytm
+        .next_scheduled_request()
+        .expect("A request should be due.");
+    let reply = device.receive(&sysex.unwrap());
+    rytm.handle_sysex_bytes(&reply).unwrap()
+}
+
+#[test]
+fn query_is_answered_with_the_dump() {
+    let (rytm, mut device, _) = connect();
+    device.project_mut().patterns_mut()[3]
+        .set_kit_number(7)
+        .unwrap();
+
+    rytm.schedule_query(values(vec!["pattern".into(), 3_isize.into()]))
+        .unwrap();
+    let events = exchange(&rytm, &mut device);
+
+    assert!(matches!(
+        events.as_slice(),
+        [SysexEvent::Applied(DeviceObject {
+            device_id: DEVICE_ID,
+            object: ObjectTypeSelector::Pattern(3),
+        })]
+    ));
+    assert_eq!(rytm.project.lock().patterns()[3].kit_number(), 7);
+    assert_eq!(rytm.scheduler.lock().awaiting_response().count(), 0);
+}
+
+#[test]
+fn changed_objects_are_written_to_the_device() {
+    let (rytm, mut device, _) = connect();
+
+    rytm.command(
+        CommandType::Set,
+        values(vec![
+            "pattern".into(),
+            3_isize.into(),
+            "kitnumber".into(),
+            7_isize.into(),
+        ]),
+    )
+    .unwrap();
+    assert_eq!(
+        rytm.dirty_objects(),
+        vec![DeviceObject::new(DEVICE_ID, ObjectTypeSelector::Pattern(3))]
+    );
+
+    rytm.schedule_send(values(vec!["changed".into()])).unwrap();
+    let events = exchange(&rytm, &mut device);
+
+    // The device does not acknowledge writes.
+    assert!(events.is_empty());
+    assert_eq!(device.writes(), [ObjectTypeSelector::Pattern(3)]);
+    assert_eq!(device.project().patterns()[3].kit_number(), 7);
+    assert!(rytm.dirty_objects().is_empty());
+}
+
+#[test]
+fn dropped_reply_is_queried_again() {
+    let (rytm, mut device, clock) = connect();
+    device.inject(Fault::DropReply);
+
+    rytm.schedule_query(values(vec!["kit".into(), 5_isize.into()]))
+        .unwrap();
+    assert!(exchange(&rytm, &mut device).is_empty());
+
+    clock.advance(rytm.scheduler.lock().timeout());
+    assert!(rytm.expire_queries().is_empty());
+    let events = exchange(&rytm, &mut device);
+
+    assert!(matches!(
+        events.as_slice(),
+        [SysexEvent::Applied(DeviceObject {
+            device_id: DEVICE_ID,
+            object: ObjectTypeSelector::Kit(5),
+        })]
+    ));
+    assert_eq!(device.replies_sent(), 2);
+}
+
+#[test]
+fn unanswered_query_times_out_after_retries() {
+    let (rytm, mut device, clock) = connect();
+    rytm.scheduler.lock().set_max_retries(1);
+    device.inject(Fault::DropReply);
+    device.inject(Fault::DropReply);
+
+    rytm.schedule_query(values(vec!["settings".into()]))
+        .unwrap();
+    assert!(exchange(&rytm, &mut device).is_empty());
+
+    clock.advance(rytm.scheduler.lock().timeout());
+    assert!(rytm.expire_queries().is_empty());
+    assert!(exchange(&rytm, &mut device).is_empty());
+
+    clock.advance(rytm.scheduler.lock().timeout());
+    let events = rytm.expire_queries();
+    let [event @ SysexEvent::TimedOut(object)] = events.as_slice() else {
+        panic!("Expected the query to time out: {events:?}");
+    };
+    assert_eq!(
+        *object,
+        DeviceObject::new(DEVICE_ID, ObjectTypeSelector::Settings)
+    );
+    assert_eq!(event.status(), Some(Status::Error));
+    assert!(rytm.scheduler.lock().is_empty());
+}
+
+#[test]
+fn corrupted_reply_is_rejected() {
+    let (rytm, mut device, _) = connect();
+    // Turns the object type of the dump into one which does not exist.
+    device.inject(Fault::CorruptByte(6));
+
+    rytm.schedule_query(values(vec!["sound".into(), 1_isize.into()]))
+        .unwrap();
+    let events = exchange(&rytm, &mut device);
+
+    assert!(matches!(
+        events.as_slice(),
+        [SysexEvent::Rejected { object: None, .. }]
+    ));
+}
+
+#[test]
+fn first_contact_short_read_is_recovered() {
+    let (rytm, device, clock) = connect();
+    let mut device = device.with_first_contact_short_read();
+
+    rytm.schedule_query(values(vec!["global".into(), 0_isize.into()]))
+        .unwrap();
+    assert!(exchange(&rytm, &mut device).is_empty());
+    assert!(rytm.sysex_input.lock().is_buffering());
+
+    // The next reply starts a new message which replaces the incomplete one.
+    clock.advance(rytm.scheduler.lock().timeout());
+    rytm.expire_queries();
+    let events = exchange(&rytm, &mut device);
+
+    assert!(matches!(
+        events.as_slice(),
+        [SysexEvent::Applied(DeviceObject {
+            device_id: DEVICE_ID,
+            object: ObjectTypeSelector::Global(0),
+        })]
+    ));
+}
+
+#[test]
+fn queries_for_other_devices_are_ignored() {
+    let (_, mut device, _) = connect();
+    let query =
+        RytmObject::query_sysex(ObjectTypeSelector::KitWorkBuffer, Some(DEVICE_ID + 1)).unwrap();
+
+    assert!(device.receive(&query).is_empty());
+    assert!(device.receive(&device_inquiry(DEVICE_ID + 1)).is_empty());
+}
+
+#[test]
+fn device_inquiry_is_answered_with_identity() {
+    let (rytm, device, _) = connect();
+    let mut device = device.with_firmware(FirmwareVersion {
+        major: 1,
+        minor: 61,
+    });
+
+    // The all call id reaches every device.
+    let reply = device.receive(&device_inquiry(0x7F));
+    let events = rytm.handle_sysex_bytes(&reply).unwrap();
+
+    let [SysexEvent::Identified(identity)] = events.as_slice() else {
+        panic!("Expected an identity reply but got {events:?}");
+    };
+    assert_eq!(identity.device_id, DEVICE_ID);
+    assert_eq!(identity.model(), "analog_rytm");
+    assert!(!identity.is_supported());
+}
+
+#[test]
+fn identify_scan_is_paced_and_adopts_the_first_rytm() {
+    let (rytm, _, clock) = connect_cold();
+    let mut device = VirtualRytm::new(2).unwrap();
+
+    rytm.schedule_identify(0..=127, true);
+    let mut events = Vec::new();
+    while events.is_empty() {
+        events = exchange(&rytm, &mut device);
+        // The next inquiry waits for the inquiry interval.
+        assert!(rytm.next_scheduled_request().is_none());
+        clock.advance(RequestScheduler::INQUIRY_INTERVAL);
+    }
+
+    let [SysexEvent::Identified(identity)] = events.as_slice() else {
+        panic!("Expected an identity reply but got {events:?}");
+    };
+    assert!(rytm.finish_identify_scan(identity));
+    assert_eq!(rytm.default_device_id(), 2);
+    // The rest of the scan is dropped and the connection warms up with the found device.
+    let (scheduled, _) = rytm.next_scheduled_request().unwrap();
+    assert_eq!(
+        scheduled.request,
+        Request::WarmUp(ConnectionState::warm_up_query(2))
+    );
+}
+
+#[test]
+fn identify_scan_ignores_other_devices_and_times_out() {
+    let (rytm, mut device, clock) = connect();
+
+    rytm.schedule_identify([DEVICE_ID], true);
+    let events = exchange(&rytm, &mut device);
+    let [SysexEvent::Identified(identity)] = events.as_slice() else {
+        panic!("Expected an identity reply but got {events:?}");
+    };
+    let other = sysex::DeviceIdentity {
+        family: 0,
+        ..*identity
+    };
+    assert!(!rytm.finish_identify_scan(&other));
+
+    clock.advance(rytm.scheduler.lock().timeout());
+    assert!(rytm.expire_inquiries());
+    assert!(!rytm.identify_scan.load(std::sync::atomic::Ordering::SeqCst));
+    // A later reply does not change the default device.
+    assert!(!rytm.finish_identify_scan(identity));
+}
+
+#[test]
+fn bulk_query_syncs_every_object() {
+    let (rytm, mut device, clock) = connect();
+    for (index, pattern) in device.project_mut().patterns_mut()[..4]
+        .iter_mut()
+        .enumerate()
+    {
+        pattern.set_kit_number(index + 10).unwrap();
+    }
+
+    rytm.schedule_query(values(vec!["patterns".into(), "0..3".into()]))
+        .unwrap();
+    for _ in 0..4 {
+        exchange(&rytm, &mut device);
+        clock.advance(rytm.scheduler.lock().interval());
+    }
+
+    for index in 0..4 {
+        assert_eq!(
+            rytm.project.lock().patterns()[index].kit_number(),
+            index + 10
+        );
+    }
+    let scheduler = rytm.scheduler.lock();
+    assert!(scheduler.progress().is_none());
+    assert_eq!(scheduler.awaiting_response().count(), 0);
+}
+
+#[test]
+fn cancelled_bulk_query_drops_the_rest() {
+    let (rytm, mut device, clock) = connect();
+
+    rytm.schedule_query(values(vec!["patterns".into(), "0..3".into()]))
+        .unwrap();
+    exchange(&rytm, &mut device);
+    clock.advance(rytm.scheduler.lock().interval());
+
+    assert_eq!(rytm.cancel(), vec![Operation::Transfer]);
+    assert!(rytm.next_scheduled_request().is_none());
+    assert!(rytm.cancel().is_empty());
+
+    let events = rytm.take_progress_events();
+    assert!(matches!(
+        events.as_slice(),
+        [
+            ProgressEvent::Busy(true),
+            ProgressEvent::Advanced {
+                operation: Operation::Transfer,
+                phase: Phase::Transferring,
+                percent: 0,
+            },
+            ProgressEvent::Advanced { percent: 25, .. },
+            ProgressEvent::Cancelled(Operation::Transfer),
+            ProgressEvent::Busy(false),
+        ]
+    ));
+}
+
+#[test]
+fn recorded_session_replays_the_same_events() {
+    let (rytm, mut device, _) = connect();
+    device.project_mut().kits_mut()[2]
+        .set_name("REPLAY")
+        .unwrap();
+    let path = std::env::temp_dir().join(format!("rytm-simulator-{}.txt", std::process::id()));
+
+    rytm.start_recording(path.to_str().unwrap()).unwrap();
+    rytm.schedule_query(values(vec!["kit".into(), 2_isize.into()]))
+        .unwrap();
+    exchange(&rytm, &mut device);
+    assert!(rytm.stop_recording().unwrap());
+
+    let messages = read_recording(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(messages.len(), 1);
+    assert_eq!(messages[0].direction, Direction::In);
+
+    let (replayed, _, _) = connect();
+    let events = replayed.replay(&messages).unwrap();
+    assert!(matches!(
+        events.as_slice(),
+        [SysexEvent::Applied(DeviceObject {
+            device_id: DEVICE_ID,
+            object: ObjectTypeSelector::Kit(2),
+        })]
+    ));
+    assert_eq!(replayed.project.lock().kits()[2].name(), "REPLAY");
+}
+
+#[test]
+fn verified_send_reports_differing_parameters() {
+    let (rytm, mut device, clock) = connect();
+    rytm.command(
+        CommandType::Set,
+        values(vec![
+            "kit".into(),
+            2_isize.into(),
+            "name".into(),
+            "SENT".into(),
+        ]),
+    )
+    .unwrap();
+
+    rytm.schedule_verified_send(values(vec!["kit".into(), 2_isize.into()]))
+        .unwrap();
+    assert!(exchange(&rytm, &mut device).is_empty());
+    assert_eq!(device.project().kits()[2].name(), "SENT");
+
+    // Someone edits the kit on the device before it is read back.
+    device.project_mut().kits_mut()[2]
+        .set_name("EDITED")
+        .unwrap();
+    clock.advance(rytm.scheduler.lock().interval());
+    let events = exchange(&rytm, &mut device);
+
+    let [SysexEvent::Verified { object, mismatches }] = events.as_slice() else {
+        panic!("Expected a verification but got {events:?}");
+    };
+    assert_eq!(
+        *object,
+        DeviceObject::new(DEVICE_ID, ObjectTypeSelector::Kit(2))
+    );
+    assert_eq!(
+        mismatches,
+        &[Mismatch {
+            path: vec!["name".into()],
+            local: Some("SENT".into()),
+            device: Some("EDITED".into()),
+        }]
+    );
+    assert!(rytm.verifications.lock().is_empty());
+}
+
+#[test]
+fn verified_send_matches_an_unchanged_device() {
+    let (rytm, mut device, clock) = connect();
+
+    rytm.schedule_verified_send(values(vec!["settings".into()]))
+        .unwrap();
+    exchange(&rytm, &mut device);
+    clock.advance(rytm.scheduler.lock().interval());
+    let events = exchange(&rytm, &mut device);
+
+    assert!(matches!(
+        events.as_slice(),
+        [SysexEvent::Verified { object, mismatches }]
+            if object.object == ObjectTypeSelector::Settings && mismatches.is_empty()
+    ));
+}
+
+#[test]
+fn patterns_are_copied_between_devices() {
+    let (rytm, mut first, clock) = connect();
+    let mut second = VirtualRytm::new(DEVICE_ID + 1).unwrap();
+    second.project_mut().patterns_mut()[3]
+        .set_kit_number(9)
+        .unwrap();
+
+    rytm.schedule_query(values(vec![
+        "@device".into(),
+        1_isize.into(),
+        "pattern".into(),
+        3_isize.into(),
+    ]))
+    .unwrap();
+    // Both devices are on the same MIDI interface.
+    let (_, query) = rytm.next_scheduled_request().unwrap();
+    let query = query.unwrap();
+    let mut replies = first.receive(&query);
+    replies.extend(second.receive(&query));
+    let events = rytm.handle_sysex_bytes(&replies).unwrap();
+
+    assert!(matches!(
+        events.as_slice(),
+        [SysexEvent::Applied(DeviceObject {
+            device_id: 1,
+            object: ObjectTypeSelector::Pattern(3),
+        })]
+    ));
+    assert_eq!(
+        rytm.device_project(1).unwrap().lock().patterns()[3].kit_number(),
+        9
+    );
+    assert_eq!(rytm.device_ids(), vec![DEVICE_ID, 1]);
+
+    rytm.command(
+        CommandType::Copy,
+        values(vec![
+            "@device".into(),
+            1_isize.into(),
+            "pattern".into(),
+            3_isize.into(),
+            5_isize.into(),
+            "@device".into(),
+            0_isize.into(),
+        ]),
+    )
+    .unwrap();
+    assert_eq!(rytm.project.lock().patterns()[5].kit_number(), 9);
+    assert_eq!(
+        rytm.dirty_objects(),
+        vec![DeviceObject::new(DEVICE_ID, ObjectTypeSelector::Pattern(5))]
+    );
+
+    clock.advance(rytm.scheduler.lock().interval());
+    rytm.schedule_send(values(vec!["changed".into()])).unwrap();
+    assert!(exchange(&rytm, &mut first).is_empty());
+    assert_eq!(first.project().patterns()[5].kit_number(), 9);
+    assert!(second.writes().is_empty());
+}
+
+#[test]
+fn dumps_of_unknown_devices_are_ignored() {
+    let (rytm, _, _) = connect();
+    let mut stranger = VirtualRytm::new(5).unwrap();
+    stranger.project_mut().patterns_mut()[3]
+        .set_kit_number(9)
+        .unwrap();
+    let dump = RytmObject::project_object_sysex(stranger.project(), ObjectTypeSelector::Pattern(3))
+        .unwrap();
+
+    let events = rytm.handle_sysex_bytes(&dump).unwrap();
+    assert!(matches!(
+        events.as_slice(),
+        [SysexEvent::Rejected {
+            object: Some(DeviceObject { device_id: 5, .. }),
+            ..
+        }]
+    ));
+    assert_eq!(rytm.device_ids(), vec![DEVICE_ID]);
+
+    // Once the device answers an inquiry its dumps are accepted.
+    let reply = stranger.receive(&device_inquiry(5));
+    rytm.handle_sysex_bytes(&reply).unwrap();
+    let events = rytm.handle_sysex_bytes(&dump).unwrap();
+    assert!(matches!(
+        events.as_slice(),
+        [SysexEvent::Applied(DeviceObject { device_id: 5, .. })]
+    ));
+    assert_eq!(rytm.device_ids(), vec![DEVICE_ID, 5]);
+}
+
+#[test]
+fn dumps_are_decoded_on_the_worker_thread() {
+    let (rytm, mut device, _) = connect();
+    device.project_mut().patterns_mut()[3]
+        .set_kit_number(7)
+        .unwrap();
+    let (decoded, notifications) = std::sync::mpsc::channel();
+    rytm.start_decoder(Box::new(move || decoded.send(()).unwrap()))
+        .unwrap();
+
+    rytm.schedule_query(values(vec!["pattern".into(), 3_isize.into()]))
+        .unwrap();
+    // The dump is handed to the worker, nothing is reported where it arrives.
+    assert!(exchange(&rytm, &mut device).is_empty());
+
+    notifications
+        .recv_timeout(std::time::Duration::from_secs(5))
+        .expect("The worker should notify when the dump is decoded.");
+    let events = rytm.take_decoded_events();
+
+    assert!(matches!(
+        events.as_slice(),
+        [SysexEvent::Applied(DeviceObject {
+            device_id: DEVICE_ID,
+            object: ObjectTypeSelector::Pattern(3),
+        })]
+    ));
+    assert_eq!(rytm.project.lock().patterns()[3].kit_number(), 7);
+    assert_eq!(rytm.scheduler.lock().awaiting_response().count(), 0);
+    assert!(rytm.take_decoded_events().is_empty());
+    rytm.stop_decoder();
+}
+
+#[test]
+fn autosend_coalesces_edits_into_one_dump() {
+    let (rytm, mut device, clock) = connect();
+    rytm.autosend
+        .store(true, std::sync::atomic::Ordering::SeqCst);
+
+    for level in [10_isize, 20, 30] {
+        rytm.command(
+            CommandType::Set,
+            values(vec![
+                "kit_wb".into(),
+                "tracklevel".into(),
+                0_isize.into(),
+                level.into(),
+            ]),
+        )
+        .unwrap();
+    }
+    assert!(rytm.next_scheduled_request().is_none());
+
+    clock.advance(rytm.scheduler.lock().debounce());
+    assert!(exchange(&rytm, &mut device).is_empty());
+    assert!(rytm.next_scheduled_request().is_none());
+
+    assert_eq!(device.writes(), [ObjectTypeSelector::KitWorkBuffer]);
+    assert_eq!(
+        device.project().work_buffer().kit().track_level(0).unwrap(),
+        30
+    );
+    assert!(rytm.dirty_objects().is_empty());
+}
+
+#[test]
+fn sends_follow_the_index_base() {
+    let (rytm, mut device, _) = connect();
+    rytm.index_base
+        .store(1, std::sync::atomic::Ordering::SeqCst);
+
+    rytm.schedule_send(values(vec!["pattern".into(), 2_isize.into()]))
+        .unwrap();
+    exchange(&rytm, &mut device);
+    assert_eq!(device.writes(), [ObjectTypeSelector::Pattern(1)]);
+}
+
+#[test]
+fn first_reply_after_warm_up_is_swallowed() {
+    let (rytm, device, clock) = connect_cold();
+    let mut device = device.with_first_contact_short_read();
+
+    rytm.schedule_query(values(vec!["global".into(), 0_isize.into()]))
+        .unwrap();
+
+    // The warm-up query leaves before the requested one, its short reply is not a complete message.
+    let (scheduled, query) = rytm.next_scheduled_request().unwrap();
+    assert_eq!(
+        scheduled.request,
+        Request::WarmUp(ConnectionState::warm_up_query(DEVICE_ID))
+    );
+    let reply = device.receive(&query.unwrap());
+    assert!(rytm.handle_sysex_bytes(&reply).unwrap().is_empty());
+    assert!(matches!(
+        *rytm.connection.lock(),
+        ConnectionState::WarmingUp(_)
+    ));
+
+    // The dump of the requested object is not the reply to the warm-up query.
+    clock.advance(rytm.scheduler.lock().interval());
+    let events = exchange(&rytm, &mut device);
+    assert!(matches!(
+        events.as_slice(),
+        [SysexEvent::Applied(DeviceObject {
+            device_id: DEVICE_ID,
+            object: ObjectTypeSelector::Global(0),
+        })]
+    ));
+    assert!(matches!(
+        *rytm.connection.lock(),
+        ConnectionState::WarmingUp(_)
+    ));
+
+    // The warm-up query is sent again and its reply is swallowed.
+    clock.advance(rytm.scheduler.lock().timeout());
+    rytm.expire_queries();
+    let events = exchange(&rytm, &mut device);
+    assert!(matches!(events.as_slice(), [SysexEvent::Ready]));
+    assert_eq!(*rytm.connection.lock(), ConnectionState::Ready);
+    assert_eq!(rytm.scheduler.lock().awaiting_response().count(), 0);
+}
+
+#[test]
+fn requested_settings_are_not_swallowed_with_the_warm_up() {
+    let (rytm, mut device, clock) = connect_cold();
+
+    rytm.schedule_query(values(vec!["settings".into()]))
+        .unwrap();
+
+    // The warm-up leaves on its own, the requested query is still waiting for its turn.
+    let events = exchange(&rytm, &mut device);
+    assert!(matches!(events.as_slice(), [SysexEvent::Ready]));
+    assert_eq!(rytm.scheduler.lock().pending(), 1);
+
+    clock.advance(rytm.scheduler.lock().interval());
+    let events = exchange(&rytm, &mut device);
+    assert!(matches!(
+        events.as_slice(),
+        [SysexEvent::Applied(DeviceObject {
+            device_id: DEVICE_ID,
+            object: ObjectTypeSelector::Settings,
+        })]
+    ));
+    assert_eq!(rytm.scheduler.lock().awaiting_response().count(), 0);
+}
+
+#[test]
+fn messages_of_other_devices_are_not_swallowed_while_warming_up() {
+    let (rytm, mut device, _) = connect_cold();
+    device.inject(Fault::DropReply);
+    let other = VirtualRytm::new(DEVICE_ID + 1).unwrap();
+    rytm.device_project(DEVICE_ID + 1).unwrap();
+
+    rytm.schedule_query(values(vec!["kit".into(), 1_isize.into()]))
+        .unwrap();
+    assert!(exchange(&rytm, &mut device).is_empty());
+
+    let settings =
+        RytmObject::project_object_sysex(other.project(), ObjectTypeSelector::Settings).unwrap();
+    let events = rytm.handle_sysex_bytes(&settings).unwrap();
+    assert!(matches!(
+        events.as_slice(),
+        [SysexEvent::Applied(DeviceObject {
+            device_id: 1,
+            object: ObjectTypeSelector::Settings,
+        })]
+    ));
+    assert!(matches!(
+        *rytm.connection.lock(),
+        ConnectionState::WarmingUp(_)
+    ));
+}
+
+#[test]
+fn unanswered_warm_up_is_tried_again() {
+    let (rytm, mut device, clock) = connect_cold();
+    rytm.scheduler.lock().set_max_retries(0);
+    device.inject(Fault::DropReply);
+
+    rytm.schedule_query(values(vec!["kit".into(), 1_isize.into()]))
+        .unwrap();
+    assert!(exchange(&rytm, &mut device).is_empty());
+
+    clock.advance(rytm.scheduler.lock().timeout());
+    assert!(matches!(
+        rytm.expire_queries().as_slice(),
+        [SysexEvent::TimedOut(object)] if *object == ConnectionState::warm_up_query(DEVICE_ID)
+    ));
+    assert_eq!(*rytm.connection.lock(), ConnectionState::Unknown);
+
+    // The kit query is still waiting, the warm-up goes in front of it again.
+    let events = exchange(&rytm, &mut device);
+    assert!(matches!(events.as_slice(), [SysexEvent::Ready]));
+}